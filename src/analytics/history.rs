@@ -0,0 +1,212 @@
+use std::str::FromStr;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use solana_client::client_error::reqwest;
+use solana_sdk::pubkey::Pubkey;
+use tokio::task::JoinSet;
+
+use crate::tx::{rpc_call, transaction_account_keys, DecodedEvents};
+
+/// `getSignaturesForAddress` page size limit
+const SIGNATURE_PAGE_SIZE: usize = 1000;
+/// `getTransaction` calls in flight at once
+const TRANSACTION_CONCURRENCY: usize = 8;
+
+/// One bin swap event of a pair, as emitted by the program
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SwapRecord {
+    pub signature: String,
+    /// Position among the transaction's decoded events
+    pub event_index: u32,
+    pub slot: u64,
+    pub block_time: i64,
+    pub pair: String,
+    pub swap_for_y: bool,
+    pub bin_id: u32,
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub fee: u64,
+    pub protocol_fee: u64,
+}
+
+impl SwapRecord {
+    /// Swap events of `pair` in a successful `getTransaction` result (json encoding)
+    pub fn from_transaction(
+        signature: &str,
+        result: &Value,
+        pair: &Pubkey,
+        program_id: &Pubkey,
+    ) -> Vec<SwapRecord> {
+        if !result["meta"]["err"].is_null() {
+            return vec![];
+        }
+
+        SwapRecord::from_events(
+            signature,
+            result["slot"].as_u64().unwrap_or_default(),
+            result["blockTime"].as_i64().unwrap_or_default(),
+            &transaction_events(result, program_id).events,
+            pair,
+        )
+    }
+
+    pub fn from_events(
+        signature: &str,
+        slot: u64,
+        block_time: i64,
+        events: &[Value],
+        pair: &Pubkey,
+    ) -> Vec<SwapRecord> {
+        events
+            .iter()
+            .enumerate()
+            .filter(|(_, event)| event["type"] == "swap" && event_pair(event) == Some(*pair))
+            .map(|(event_index, event)| SwapRecord {
+                signature: signature.to_string(),
+                event_index: event_index as u32,
+                slot,
+                block_time,
+                pair: pair.to_string(),
+                swap_for_y: event["swap_for_y"].as_bool().unwrap_or_default(),
+                bin_id: event["bin_id"].as_u64().unwrap_or_default() as u32,
+                amount_in: event["amount_in"].as_u64().unwrap_or_default(),
+                amount_out: event["amount_out"].as_u64().unwrap_or_default(),
+                fee: event["fee"].as_u64().unwrap_or_default(),
+                protocol_fee: event["protocol_fee"].as_u64().unwrap_or_default(),
+            })
+            .collect()
+    }
+}
+
+/// Events `program_id` emitted in a `getTransaction` result (json encoding)
+pub fn transaction_events(result: &Value, program_id: &Pubkey) -> DecodedEvents {
+    let meta = &result["meta"];
+    let logs: Vec<String> = meta["logMessages"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|log| log.as_str().map(str::to_string))
+        .collect();
+
+    DecodedEvents::decode(
+        &meta["innerInstructions"],
+        &logs,
+        &transaction_account_keys(result),
+        program_id,
+    )
+}
+
+pub fn event_pair(event: &Value) -> Option<Pubkey> {
+    Pubkey::from_str(event["pair"].as_str()?).ok()
+}
+
+pub struct SwapHistory {
+    pub swaps: Vec<SwapRecord>,
+    /// False when `max_signatures` ran out before reaching `since`
+    pub complete: bool,
+}
+
+/// Walk a pair's signatures back to `since` (unix seconds) and decode the
+/// swaps of every successful transaction.
+pub async fn fetch_recent_swaps(
+    http: &reqwest::Client,
+    url: &str,
+    pair: Pubkey,
+    program_id: Pubkey,
+    since: i64,
+    max_signatures: usize,
+) -> Result<SwapHistory> {
+    let mut signatures = vec![];
+    let mut before: Option<String> = None;
+    let mut complete = false;
+
+    while signatures.len() < max_signatures {
+        let limit = SIGNATURE_PAGE_SIZE.min(max_signatures - signatures.len());
+        let mut config = json!({ "limit": limit, "commitment": "finalized" });
+        if let Some(before) = &before {
+            config["before"] = json!(before);
+        }
+
+        let page = rpc_call(
+            http,
+            url,
+            "getSignaturesForAddress",
+            json!([pair.to_string(), config]),
+        )
+        .await?;
+        let page = page.as_array().cloned().unwrap_or_default();
+
+        for entry in page.iter() {
+            if entry["blockTime"].as_i64().is_some_and(|time| time < since) {
+                complete = true;
+                break;
+            }
+            if entry["err"].is_null() {
+                if let Some(signature) = entry["signature"].as_str() {
+                    signatures.push(signature.to_string());
+                }
+            }
+        }
+
+        if complete || page.len() < limit {
+            complete = true;
+            break;
+        }
+        before = page
+            .last()
+            .and_then(|entry| entry["signature"].as_str())
+            .map(str::to_string);
+    }
+
+    let mut swaps = vec![];
+    for chunk in signatures.chunks(TRANSACTION_CONCURRENCY) {
+        let mut tasks = JoinSet::new();
+        for signature in chunk.iter().cloned() {
+            let http = http.clone();
+            let url = url.to_string();
+            tasks.spawn(async move {
+                let result = fetch_transaction(&http, &url, &signature).await;
+                (signature, result)
+            });
+        }
+
+        while let Some(joined) = tasks.join_next().await {
+            let (signature, result) = joined?;
+            match result {
+                Ok(result) if !result.is_null() => {
+                    swaps.extend(SwapRecord::from_transaction(
+                        &signature,
+                        &result,
+                        &pair,
+                        &program_id,
+                    ));
+                }
+                Ok(_) => {}
+                Err(e) => tracing::warn!("⚠️ Skipping transaction {}: {}", signature, e),
+            }
+        }
+    }
+
+    swaps.sort_by_key(|swap| (swap.slot, swap.block_time));
+    Ok(SwapHistory { swaps, complete })
+}
+
+pub async fn fetch_transaction(
+    http: &reqwest::Client,
+    url: &str,
+    signature: &str,
+) -> Result<Value> {
+    rpc_call(
+        http,
+        url,
+        "getTransaction",
+        json!([signature, {
+            "encoding": "json",
+            "commitment": "finalized",
+            "maxSupportedTransactionVersion": 0
+        }]),
+    )
+    .await
+}
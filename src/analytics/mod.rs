@@ -0,0 +1,9 @@
+mod history;
+mod pnl;
+mod price;
+mod stats;
+
+pub use history::*;
+pub use pnl::*;
+pub use price::*;
+pub use stats::*;
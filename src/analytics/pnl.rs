@@ -0,0 +1,460 @@
+use std::collections::HashMap;
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use solana_client::client_error::reqwest;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::{
+    analytics::{fetch_usd_prices, ui_amount, SwapRecord},
+    app::AppContext,
+    dlmm::get_price_from_id,
+    indexer::LiquidityRecord,
+    state::{PositionBin, State},
+};
+
+/// Deposit figures supplied by the caller, taking precedence over the index
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct DepositInput {
+    /// Net X deposited, raw units
+    pub deposit_x: Option<u64>,
+    /// Net Y deposited, raw units
+    pub deposit_y: Option<u64>,
+    /// Y per X in UI units when the position was opened
+    pub deposit_price: Option<f64>,
+}
+
+/// Token amounts the position started from and took out, raw units
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DepositHistory {
+    pub deposited_x: u64,
+    pub deposited_y: u64,
+    pub withdrawn_x: u64,
+    pub withdrawn_y: u64,
+    pub fees_claimed_x: u64,
+    pub fees_claimed_y: u64,
+    pub first_deposit_time: Option<i64>,
+    pub last_claim_time: Option<i64>,
+}
+
+/// Values are in Y, UI units
+#[derive(Debug, Clone, Serialize)]
+pub struct PositionPnl {
+    pub position: String,
+    pub pair: String,
+    pub lower_bin_id: u32,
+    pub upper_bin_id: u32,
+    pub bins: Vec<PositionBin>,
+    /// Y per X in UI units
+    pub price: f64,
+    pub deposit_price: f64,
+    /// `index` when deposits come from indexed events, `input` when any were supplied
+    pub deposit_source: String,
+    pub amount_x: u64,
+    pub amount_y: u64,
+    pub deposit_x: u64,
+    pub deposit_y: u64,
+    pub history: DepositHistory,
+    /// Not read from chain, positions keep no fee accounting: the position's
+    /// current share of each bin applied to every swap since its last claim,
+    /// so shares changed in between skew it
+    pub fees_unclaimed_estimate_x: u64,
+    pub fees_unclaimed_estimate_y: u64,
+    #[serde(flatten)]
+    pub values: PnlValues,
+    pub price_y_usd: Option<f64>,
+    pub net_pnl_usd: Option<f64>,
+}
+
+/// Values are in Y, UI units
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct PnlValues {
+    pub current_value: f64,
+    pub hodl_value: f64,
+    pub deposit_value: f64,
+    /// Claimed fees plus the unclaimed estimate
+    pub fees_value: f64,
+    pub impermanent_loss: f64,
+    pub impermanent_loss_pct: f64,
+    pub net_pnl: f64,
+    pub net_pnl_pct: f64,
+}
+
+/// Raw amounts of X and Y valued in Y at `price` (Y per X, UI units)
+struct Valuer {
+    price: f64,
+    decimals_x: u8,
+    decimals_y: u8,
+}
+
+impl Valuer {
+    fn value(&self, amount_x: u64, amount_y: u64) -> f64 {
+        ui_amount(amount_x, self.decimals_x) * self.price + ui_amount(amount_y, self.decimals_y)
+    }
+}
+
+/// Current value against holding the deposit, fees earned and net PnL of a
+/// position. Deposits and fees come from the swap index unless supplied.
+pub async fn compute_position_pnl(
+    ctx: &AppContext,
+    position_key: Pubkey,
+    input: &DepositInput,
+) -> Result<PositionPnl> {
+    let position =
+        State::generate_position_state(ctx.rpc_client.clone(), position_key, &ctx.config.retry)
+            .await?;
+
+    let dlmm_client = ctx.get_or_spawn_client(position.pair).await?;
+    let (bin_step, active_id, [meta_x, meta_y]) = {
+        let saros_dlmm = dlmm_client.saros_dlmm.read().await;
+        (
+            saros_dlmm.pair.bin_step,
+            saros_dlmm.pair.active_id,
+            ctx.fetch_pair_token_info(&saros_dlmm).await?,
+        )
+    };
+    let decimals_scale = 10f64.powi(meta_x.decimals as i32 - meta_y.decimals as i32);
+    let ui_price = |bin_id: u32| get_price_from_id(bin_step, bin_id) * decimals_scale;
+
+    let pair = position.pair.to_string();
+    let history = match &ctx.index_db {
+        Some(db) => {
+            let position = position_key.to_string();
+            deposit_history(&db.run(move |db| db.position_liquidity(&position)).await?)
+        }
+        None => DepositHistory::default(),
+    };
+
+    let (fees_unclaimed_estimate_x, fees_unclaimed_estimate_y) =
+        match (&ctx.index_db, history.first_deposit_time) {
+            (Some(db), Some(first_deposit_time)) => {
+                let since = history.last_claim_time.unwrap_or(first_deposit_time);
+                let (pair, lower_bin_id, upper_bin_id) =
+                    (pair.clone(), position.lower_bin_id, position.upper_bin_id);
+                let swaps = db
+                    .run(move |db| db.swaps_in_bins_since(&pair, lower_bin_id, upper_bin_id, since))
+                    .await?;
+                estimate_unclaimed_fees(&position.bins, &swaps)
+            }
+            _ => (0, 0),
+        };
+
+    let user_supplied =
+        input.deposit_x.is_some() || input.deposit_y.is_some() || input.deposit_price.is_some();
+    let deposit_x = input
+        .deposit_x
+        .unwrap_or(history.deposited_x.saturating_sub(history.withdrawn_x));
+    let deposit_y = input
+        .deposit_y
+        .unwrap_or(history.deposited_y.saturating_sub(history.withdrawn_y));
+    if deposit_x == 0 && deposit_y == 0 {
+        bail!(
+            "No deposits indexed for {}, pass deposit_x and deposit_y",
+            position_key
+        );
+    }
+
+    let deposit_price = match input.deposit_price {
+        Some(deposit_price) => deposit_price,
+        None => {
+            let indexed_bin = match (&ctx.index_db, history.first_deposit_time) {
+                (Some(db), Some(first_deposit_time)) => {
+                    let pair = pair.clone();
+                    db.run(move |db| db.bin_at(&pair, first_deposit_time))
+                        .await?
+                }
+                _ => None,
+            };
+            match indexed_bin {
+                Some(bin_id) => ui_price(bin_id),
+                None => bail!(
+                    "Deposit price unknown for {}, pass deposit_price",
+                    position_key
+                ),
+            }
+        }
+    };
+
+    let price = ui_price(active_id);
+    let now = Valuer {
+        price,
+        decimals_x: meta_x.decimals,
+        decimals_y: meta_y.decimals,
+    };
+    let then = Valuer {
+        price: deposit_price,
+        ..now
+    };
+
+    let values = pnl_values(
+        &now,
+        &then,
+        (position.amount_x, position.amount_y),
+        (deposit_x, deposit_y),
+        (
+            history
+                .fees_claimed_x
+                .saturating_add(fees_unclaimed_estimate_x),
+            history
+                .fees_claimed_y
+                .saturating_add(fees_unclaimed_estimate_y),
+        ),
+    );
+
+    let price_y_usd = match &ctx.config.price_api_url {
+        Some(url) => fetch_usd_prices(&reqwest::Client::new(), url, &[meta_y.mint])
+            .await
+            .ok()
+            .and_then(|prices| prices.get(&meta_y.mint).copied()),
+        None => None,
+    };
+
+    Ok(PositionPnl {
+        position: position_key.to_string(),
+        pair,
+        lower_bin_id: position.lower_bin_id,
+        upper_bin_id: position.upper_bin_id,
+        bins: position.bins,
+        price,
+        deposit_price,
+        deposit_source: if user_supplied { "input" } else { "index" }.to_string(),
+        amount_x: position.amount_x,
+        amount_y: position.amount_y,
+        deposit_x,
+        deposit_y,
+        history,
+        fees_unclaimed_estimate_x,
+        fees_unclaimed_estimate_y,
+        values,
+        price_y_usd,
+        net_pnl_usd: price_y_usd.map(|price_y| values.net_pnl * price_y),
+    })
+}
+
+/// LP fees of `swaps` at the position's current share of each bin
+fn estimate_unclaimed_fees(bins: &[PositionBin], swaps: &[SwapRecord]) -> (u64, u64) {
+    let shares: HashMap<u32, f64> = bins
+        .iter()
+        .map(|bin| (bin.bin_id, bin.share_of_bin))
+        .collect();
+
+    swaps.iter().fold((0u64, 0u64), |(x, y), swap| {
+        let share = shares.get(&swap.bin_id).copied().unwrap_or_default();
+        let fee = (swap.fee.saturating_sub(swap.protocol_fee) as f64 * share) as u64;
+        if swap.swap_for_y {
+            (x.saturating_add(fee), y)
+        } else {
+            (x, y.saturating_add(fee))
+        }
+    })
+}
+
+/// Value the position now against holding its deposit, and against the
+/// deposit at the price it went in at. Amounts are raw `(x, y)` pairs.
+fn pnl_values(
+    now: &Valuer,
+    then: &Valuer,
+    holding: (u64, u64),
+    deposit: (u64, u64),
+    fees: (u64, u64),
+) -> PnlValues {
+    let current_value = now.value(holding.0, holding.1);
+    let hodl_value = now.value(deposit.0, deposit.1);
+    let deposit_value = then.value(deposit.0, deposit.1);
+    let fees_value = now.value(fees.0, fees.1);
+    let impermanent_loss = current_value - hodl_value;
+    let net_pnl = current_value + fees_value - deposit_value;
+
+    PnlValues {
+        current_value,
+        hodl_value,
+        deposit_value,
+        fees_value,
+        impermanent_loss,
+        impermanent_loss_pct: pct(impermanent_loss, hodl_value),
+        net_pnl,
+        net_pnl_pct: pct(net_pnl, deposit_value),
+    }
+}
+
+/// Sum a position's indexed add, remove and claim events
+pub fn deposit_history(events: &[LiquidityRecord]) -> DepositHistory {
+    let mut history = DepositHistory::default();
+    let sum = |amounts: &[u64]| {
+        amounts
+            .iter()
+            .fold(0u64, |sum, amount| sum.saturating_add(*amount))
+    };
+
+    for event in events {
+        let amount_x = sum(&event.amounts_x);
+        let amount_y = sum(&event.amounts_y);
+        match event.kind.as_str() {
+            "add_liquidity" => {
+                history.deposited_x = history.deposited_x.saturating_add(amount_x);
+                history.deposited_y = history.deposited_y.saturating_add(amount_y);
+                history.first_deposit_time.get_or_insert(event.block_time);
+            }
+            "remove_liquidity" => {
+                history.withdrawn_x = history.withdrawn_x.saturating_add(amount_x);
+                history.withdrawn_y = history.withdrawn_y.saturating_add(amount_y);
+            }
+            "collect_fees" => {
+                history.fees_claimed_x = history.fees_claimed_x.saturating_add(amount_x);
+                history.fees_claimed_y = history.fees_claimed_y.saturating_add(amount_y);
+                history.last_claim_time = Some(event.block_time);
+            }
+            _ => {}
+        }
+    }
+
+    history
+}
+
+fn pct(change: f64, base: f64) -> f64 {
+    if base > 0.0 {
+        change / base * 100.0
+    } else {
+        0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(kind: &str, block_time: i64, amounts_x: &[u64], amounts_y: &[u64]) -> LiquidityRecord {
+        LiquidityRecord {
+            signature: format!("{}-{}", kind, block_time),
+            event_index: 0,
+            slot: block_time as u64,
+            block_time,
+            pair: "pair".to_string(),
+            position: "position".to_string(),
+            kind: kind.to_string(),
+            bin_ids: vec![],
+            amounts_x: amounts_x.to_vec(),
+            amounts_y: amounts_y.to_vec(),
+        }
+    }
+
+    fn swap(bin_id: u32, swap_for_y: bool, fee: u64, protocol_fee: u64) -> SwapRecord {
+        SwapRecord {
+            signature: "swap".to_string(),
+            event_index: 0,
+            slot: 0,
+            block_time: 0,
+            pair: "pair".to_string(),
+            swap_for_y,
+            bin_id,
+            amount_in: 0,
+            amount_out: 0,
+            fee,
+            protocol_fee,
+        }
+    }
+
+    fn position_bin(bin_id: u32, share_of_bin: f64) -> PositionBin {
+        PositionBin {
+            bin_id,
+            shares: "0".to_string(),
+            share_of_bin,
+            amount_x: 0,
+            amount_y: 0,
+        }
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-9,
+            "{} != {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn deposit_history_sums_events_by_kind() {
+        let history = deposit_history(&[
+            event("add_liquidity", 100, &[10, 20], &[5]),
+            event("add_liquidity", 200, &[5], &[5]),
+            event("remove_liquidity", 300, &[3], &[1]),
+            event("collect_fees", 400, &[2], &[1]),
+            event("collect_fees", 500, &[1], &[0]),
+            event("close_position", 600, &[100], &[100]),
+        ]);
+
+        assert_eq!((history.deposited_x, history.deposited_y), (35, 10));
+        assert_eq!((history.withdrawn_x, history.withdrawn_y), (3, 1));
+        assert_eq!((history.fees_claimed_x, history.fees_claimed_y), (3, 1));
+        assert_eq!(history.first_deposit_time, Some(100));
+        assert_eq!(history.last_claim_time, Some(500));
+
+        let empty = deposit_history(&[]);
+        assert_eq!(empty.first_deposit_time, None);
+        assert_eq!(empty.last_claim_time, None);
+
+        let overflowing = deposit_history(&[
+            event("add_liquidity", 100, &[u64::MAX, 1], &[]),
+            event("add_liquidity", 200, &[1], &[]),
+        ]);
+        assert_eq!(overflowing.deposited_x, u64::MAX);
+    }
+
+    #[test]
+    fn unclaimed_fees_follow_bin_shares() {
+        let bins = [position_bin(10, 0.5), position_bin(11, 0.25)];
+        let swaps = [
+            // LP share of X paid in, half of it the position's
+            swap(10, true, 1_000, 200),
+            swap(11, false, 400, 0),
+            // Outside the position
+            swap(12, true, 1_000, 0),
+        ];
+
+        assert_eq!(estimate_unclaimed_fees(&bins, &swaps), (400, 100));
+        assert_eq!(estimate_unclaimed_fees(&bins, &[]), (0, 0));
+    }
+
+    #[test]
+    fn pnl_values_against_hodl_and_deposit() {
+        let now = Valuer {
+            price: 2.0,
+            decimals_x: 9,
+            decimals_y: 6,
+        };
+        let then = Valuer { price: 1.0, ..now };
+
+        let values = pnl_values(
+            &now,
+            &then,
+            (250_000_000, 2_000_000),
+            (1_000_000_000, 1_000_000),
+            (100_000_000, 100_000),
+        );
+
+        // 0.25 X + 2 Y at 2 Y per X
+        assert_close(values.current_value, 2.5);
+        assert_close(values.hodl_value, 3.0);
+        assert_close(values.deposit_value, 2.0);
+        assert_close(values.fees_value, 0.3);
+        assert_close(values.impermanent_loss, -0.5);
+        assert_close(values.impermanent_loss_pct, -0.5 / 3.0 * 100.0);
+        assert_close(values.net_pnl, 0.8);
+        assert_close(values.net_pnl_pct, 40.0);
+    }
+
+    #[test]
+    fn pnl_percentages_of_nothing_are_zero() {
+        let valuer = Valuer {
+            price: 1.0,
+            decimals_x: 6,
+            decimals_y: 6,
+        };
+
+        let values = pnl_values(&valuer, &valuer, (1_000_000, 0), (0, 0), (0, 0));
+        assert_close(values.net_pnl, 1.0);
+        assert_close(values.impermanent_loss_pct, 0.0);
+        assert_close(values.net_pnl_pct, 0.0);
+    }
+}
@@ -0,0 +1,62 @@
+use std::{collections::HashMap, str::FromStr};
+
+use anyhow::Result;
+use serde_json::Value;
+use solana_client::client_error::reqwest;
+use solana_sdk::pubkey::Pubkey;
+
+/// USD prices from a Jupiter-compatible price API (`GET {url}?ids=a,b`)
+pub async fn fetch_usd_prices(
+    http: &reqwest::Client,
+    url: &str,
+    mints: &[Pubkey],
+) -> Result<HashMap<Pubkey, f64>> {
+    let ids = mints
+        .iter()
+        .map(Pubkey::to_string)
+        .collect::<Vec<_>>()
+        .join(",");
+
+    let response: Value = http
+        .get(url)
+        .query(&[("ids", ids)])
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    // v2 nests prices under `data`, v3 returns the map directly
+    let entries = response
+        .get("data")
+        .unwrap_or(&response)
+        .as_object()
+        .cloned()
+        .unwrap_or_default();
+
+    Ok(entries
+        .into_iter()
+        .filter_map(|(mint, entry)| {
+            let price = entry.get("price").or_else(|| entry.get("usdPrice"))?;
+            let price = match price {
+                Value::String(price) => price.parse::<f64>().ok()?,
+                price => price.as_f64()?,
+            };
+            Some((Pubkey::from_str(&mint).ok()?, price))
+        })
+        .collect())
+}
+
+/// Fill in whichever side the price API missed from the pair's own price
+/// (`price_y_per_x` in UI units).
+pub fn complete_pair_prices(
+    price_x: Option<f64>,
+    price_y: Option<f64>,
+    price_y_per_x: f64,
+) -> (Option<f64>, Option<f64>) {
+    match (price_x, price_y) {
+        (Some(x), None) if price_y_per_x > 0.0 => (Some(x), Some(x / price_y_per_x)),
+        (None, Some(y)) => (Some(y * price_y_per_x), Some(y)),
+        prices => prices,
+    }
+}
@@ -0,0 +1,312 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use solana_client::client_error::reqwest;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::{
+    analytics::{complete_pair_prices, fetch_recent_swaps, fetch_usd_prices, SwapRecord},
+    app::AppContext,
+    dlmm::get_price_from_id,
+    state::{BinLiquidity, State},
+};
+
+/// Bins on each side of the active id that fee APR is reported for
+pub const APR_RANGES: [u32; 4] = [5, 10, 25, 50];
+const SECONDS_PER_DAY: i64 = 86_400;
+const DAYS_PER_YEAR: f64 = 365.0;
+/// Hard cap on the RPC scan for pairs the indexer does not watch, each
+/// signature costs a `getTransaction` inside the request
+pub const MAX_RPC_SIGNATURES: usize = 200;
+
+/// Fee APR earned by liquidity within `range` bins of the active id
+#[derive(Debug, Clone, Serialize)]
+pub struct RangeApr {
+    pub range: u32,
+    pub lower_bin_id: u32,
+    pub upper_bin_id: u32,
+    pub liquidity_usd: Option<f64>,
+    pub fees_24h_usd: Option<f64>,
+    pub fee_apr_pct: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PairStats {
+    pub pair: String,
+    pub active_id: u32,
+    /// Y per X in UI units
+    pub price: f64,
+    pub price_x_usd: Option<f64>,
+    pub price_y_usd: Option<f64>,
+    pub reserve_x: u64,
+    pub reserve_y: u64,
+    pub tvl_usd: Option<f64>,
+    pub swaps_24h: usize,
+    pub volume_24h_x: u64,
+    pub volume_24h_y: u64,
+    pub volume_24h_usd: Option<f64>,
+    pub fees_24h_x: u64,
+    pub fees_24h_y: u64,
+    pub fees_24h_usd: Option<f64>,
+    pub protocol_fees_24h_usd: Option<f64>,
+    /// APR of LP fees, the protocol's cut left out
+    pub fee_apr_pct: Option<f64>,
+    pub ranges: Vec<RangeApr>,
+    /// False when the signature scan hit its limit before covering 24h
+    pub history_complete: bool,
+    pub computed_at: i64,
+}
+
+/// Token amounts in raw units with the decimals and USD price to value them
+struct Valuation {
+    decimals_x: u8,
+    decimals_y: u8,
+    price_x: Option<f64>,
+    price_y: Option<f64>,
+}
+
+impl Valuation {
+    fn usd(&self, amount_x: u64, amount_y: u64) -> Option<f64> {
+        Some(
+            ui_amount(amount_x, self.decimals_x) * self.price_x?
+                + ui_amount(amount_y, self.decimals_y) * self.price_y?,
+        )
+    }
+
+    /// Swap amounts are denominated in the input token
+    fn swap_usd(&self, swap: &SwapRecord, amount: u64) -> Option<f64> {
+        if swap.swap_for_y {
+            self.usd(amount, 0)
+        } else {
+            self.usd(0, amount)
+        }
+    }
+}
+
+pub fn ui_amount(amount: u64, decimals: u8) -> f64 {
+    amount as f64 / 10f64.powi(decimals as i32)
+}
+
+/// TVL from vault balances, 24h volume and fees from the pair's swap events
+/// and fee APR for liquidity around the active bin.
+pub async fn compute_pair_stats(ctx: &AppContext, pair: Pubkey) -> Result<PairStats> {
+    let dlmm_client = ctx.get_or_spawn_client(pair).await?;
+    let (active_id, bin_step, vaults, [meta_x, meta_y]) = {
+        let saros_dlmm = dlmm_client.saros_dlmm.read().await;
+        (
+            saros_dlmm.pair.active_id,
+            saros_dlmm.pair.bin_step,
+            saros_dlmm.token_vault,
+            ctx.fetch_pair_token_info(&saros_dlmm).await?,
+        )
+    };
+
+    let client = ctx.rpc_client.clone();
+    let [reserve_x, reserve_y] = ctx
+        .config
+        .retry
+        .run_blocking("vault balances", move || {
+            let accounts = client.get_multiple_accounts(&vaults)?;
+            let mut balances = [0u64; 2];
+            for (balance, account) in balances.iter_mut().zip(accounts) {
+                let account = account.context("Pair vault not found")?;
+                *balance = token_account_amount(&account.data)?;
+            }
+            Ok(balances)
+        })
+        .await?;
+
+    let price = get_price_from_id(bin_step, active_id)
+        * 10f64.powi(meta_x.decimals as i32 - meta_y.decimals as i32);
+
+    let http = reqwest::Client::new();
+    let (price_x, price_y) = match &ctx.config.price_api_url {
+        Some(url) => match fetch_usd_prices(&http, url, &[meta_x.mint, meta_y.mint]).await {
+            Ok(prices) => complete_pair_prices(
+                prices.get(&meta_x.mint).copied(),
+                prices.get(&meta_y.mint).copied(),
+                price,
+            ),
+            Err(e) => {
+                tracing::warn!("⚠️ Failed to fetch USD prices: {}", e);
+                (None, None)
+            }
+        },
+        None => (None, None),
+    };
+    let valuation = Valuation {
+        decimals_x: meta_x.decimals,
+        decimals_y: meta_y.decimals,
+        price_x,
+        price_y,
+    };
+
+    let computed_at = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64;
+    let since = computed_at - SECONDS_PER_DAY;
+    let (swaps, history_complete) = match indexed_swaps(ctx, &pair, since).await? {
+        Some(swaps) => (swaps, true),
+        None => {
+            let history = fetch_recent_swaps(
+                &http,
+                &ctx.rpc_client.url(),
+                pair,
+                ctx.config.lb_program_id,
+                since,
+                ctx.config.stats_max_signatures.min(MAX_RPC_SIGNATURES),
+            )
+            .await?;
+            (history.swaps, history.complete)
+        }
+    };
+    let swaps: Vec<&SwapRecord> = swaps
+        .iter()
+        .filter(|swap| swap.block_time >= since)
+        .collect();
+
+    let max_range = APR_RANGES.iter().copied().max().unwrap_or_default();
+    let bin_range =
+        State::generate_bin_range(ctx.rpc_client.clone(), pair, max_range, &ctx.config.retry)
+            .await?;
+
+    let (volume_24h_x, volume_24h_y) = sum_by_side(&swaps, |swap| swap.amount_in);
+    let (fees_24h_x, fees_24h_y) = sum_by_side(&swaps, |swap| swap.fee);
+    let (protocol_fees_x, protocol_fees_y) = sum_by_side(&swaps, |swap| swap.protocol_fee);
+
+    let tvl_usd = valuation.usd(reserve_x, reserve_y);
+    let fees_24h_usd = valuation.usd(fees_24h_x, fees_24h_y);
+    // LP fees exclude the protocol's cut, as in `range_apr`
+    let lp_fees_24h_usd = valuation.usd(
+        fees_24h_x.saturating_sub(protocol_fees_x),
+        fees_24h_y.saturating_sub(protocol_fees_y),
+    );
+
+    let ranges = APR_RANGES
+        .iter()
+        .map(|range| {
+            range_apr(
+                *range,
+                bin_range.active_id,
+                &bin_range.bins,
+                &swaps,
+                &valuation,
+            )
+        })
+        .collect();
+
+    Ok(PairStats {
+        pair: pair.to_string(),
+        active_id,
+        price,
+        price_x_usd: price_x,
+        price_y_usd: price_y,
+        reserve_x,
+        reserve_y,
+        tvl_usd,
+        swaps_24h: swaps.len(),
+        volume_24h_x,
+        volume_24h_y,
+        volume_24h_usd: valuation.usd(volume_24h_x, volume_24h_y),
+        fees_24h_x,
+        fees_24h_y,
+        fees_24h_usd,
+        protocol_fees_24h_usd: valuation.usd(protocol_fees_x, protocol_fees_y),
+        fee_apr_pct: apr_pct(lp_fees_24h_usd, tvl_usd),
+        ranges,
+        history_complete,
+        computed_at,
+    })
+}
+
+/// Swaps since `since` from the index, when the indexer keeps this pair and
+/// its history reaches back that far
+async fn indexed_swaps(
+    ctx: &AppContext,
+    pair: &Pubkey,
+    since: i64,
+) -> Result<Option<Vec<SwapRecord>>> {
+    let Some(db) = &ctx.index_db else {
+        return Ok(None);
+    };
+    if !ctx.config.watchlist.contains(pair) {
+        return Ok(None);
+    }
+
+    let key = pair.to_string();
+    db.run(move |db| {
+        let cursor = db.cursor(&key)?;
+        let covered = cursor.backfill_complete
+            || cursor
+                .oldest_block_time
+                .is_some_and(|block_time| block_time <= since);
+        if cursor.newest_signature.is_none() || !covered {
+            return Ok(None);
+        }
+
+        Ok(Some(db.swaps_since(&key, since)?))
+    })
+    .await
+}
+
+fn range_apr(
+    range: u32,
+    active_id: u32,
+    bins: &[BinLiquidity],
+    swaps: &[&SwapRecord],
+    valuation: &Valuation,
+) -> RangeApr {
+    let lower_bin_id = active_id.saturating_sub(range);
+    let upper_bin_id = active_id.saturating_add(range);
+    let in_range = |bin_id: u32| (lower_bin_id..=upper_bin_id).contains(&bin_id);
+
+    let (liquidity_x, liquidity_y) =
+        bins.iter()
+            .filter(|bin| in_range(bin.bin_id))
+            .fold((0u64, 0u64), |(x, y), bin| {
+                (
+                    x.saturating_add(bin.amount_x),
+                    y.saturating_add(bin.amount_y),
+                )
+            });
+    let liquidity_usd = valuation.usd(liquidity_x, liquidity_y);
+
+    // LP fees exclude the protocol's cut
+    let fees_24h_usd = swaps
+        .iter()
+        .filter(|swap| in_range(swap.bin_id))
+        .map(|swap| valuation.swap_usd(swap, swap.fee.saturating_sub(swap.protocol_fee)))
+        .sum::<Option<f64>>();
+
+    RangeApr {
+        range,
+        lower_bin_id,
+        upper_bin_id,
+        liquidity_usd,
+        fees_24h_usd,
+        fee_apr_pct: apr_pct(fees_24h_usd, liquidity_usd),
+    }
+}
+
+fn sum_by_side(swaps: &[&SwapRecord], amount: impl Fn(&SwapRecord) -> u64) -> (u64, u64) {
+    swaps.iter().fold((0u64, 0u64), |(x, y), swap| {
+        if swap.swap_for_y {
+            (x.saturating_add(amount(swap)), y)
+        } else {
+            (x, y.saturating_add(amount(swap)))
+        }
+    })
+}
+
+fn apr_pct(fees_24h_usd: Option<f64>, liquidity_usd: Option<f64>) -> Option<f64> {
+    let liquidity_usd = liquidity_usd.filter(|liquidity| *liquidity > 0.0)?;
+    Some(fees_24h_usd? / liquidity_usd * DAYS_PER_YEAR * 100.0)
+}
+
+/// SPL token and Token-2022 accounts share the base layout: mint, owner, amount
+fn token_account_amount(data: &[u8]) -> Result<u64> {
+    let amount = data
+        .get(64..72)
+        .context("Token account is too short")?
+        .try_into()?;
+    Ok(u64::from_le_bytes(amount))
+}
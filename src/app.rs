@@ -1,15 +1,34 @@
-use std::{collections::HashMap, sync::Arc, time::Duration};
+use std::{
+    collections::HashMap,
+    path::Path,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
 
 use crate::{
-    dlmm::DLMMClient,
-    state::{MintAccount, PoolState, State, TokenMeta},
+    analytics::{compute_pair_stats, PairStats},
+    cache::{CacheAdmin, TtlCache},
+    dlmm::{fetch_lookup_table, DLMMClient},
+    indexer::IndexDb,
+    retry::RetryPolicy,
+    state::{
+        AccountMap, HookRewards, MetadataSource, MintAccount, OffChainMeta, PairIndex, PairStatic,
+        PoolState, State, TokenList, TokenMeta,
+    },
+    store::{DiskStore, StoredEntry},
+    tx::{SendConfig, TxTrackers},
 };
 use anyhow::{Context, Ok, Result};
 use jupiter_amm_interface::{Amm, AmmContext, ClockRef, KeyedAccount};
-use solana_client::rpc_client::RpcClient;
-use solana_sdk::{clock::Clock, pubkey::Pubkey, sysvar};
-use tokio::{sync::RwLock, time::Instant};
-use tracing::info;
+use solana_client::{client_error::reqwest, rpc_client::RpcClient};
+use solana_sdk::{
+    address_lookup_table::AddressLookupTableAccount, clock::Clock, pubkey::Pubkey, sysvar,
+};
+use tokio::sync::{Mutex, RwLock};
+use tracing::{info, warn};
 
 use saros_dlmm::SarosDlmm;
 #[derive(Clone)]
@@ -17,6 +36,8 @@ pub struct TTLConfig {
     pub pool_ttl: Duration,
     pub token_ttl: Duration,
     pub bin_ttl: Duration,
+    pub pair_index_ttl: Duration,
+    pub stats_ttl: Duration,
 }
 
 #[derive(Clone)]
@@ -25,6 +46,35 @@ pub struct AppConfig {
     pub cache_ttl: TTLConfig,
     pub lb_program_id: Pubkey,
     pub hook_program_id: Pubkey,
+    /// Solana/Jupiter token list JSON used when a mint has no on-chain metadata
+    pub token_list_path: Option<String>,
+    /// Maximum entries per cache before the least recently used is evicted
+    pub cache_capacity: usize,
+    /// Gap between a simulated swap's output and its quote that triggers a warning
+    pub quote_divergence_bps: u64,
+    /// Directory for persisted token metadata and static pair data, `None` disables it
+    pub cache_dir: Option<String>,
+    /// Timeouts and backoff for RPC reads
+    pub retry: RetryPolicy,
+    /// How `/api/send_tx` submits signed transactions
+    pub send: SendConfig,
+    /// Address lookup tables used to compress v0 transactions
+    pub lookup_tables: Vec<Pubkey>,
+    /// Jupiter-compatible USD price API for pool analytics, `None` disables USD values
+    pub price_api_url: Option<String>,
+    /// Most signatures scanned over RPC when summing 24h volume of a pair the
+    /// indexer does not watch, at most `MAX_RPC_SIGNATURES`
+    pub stats_max_signatures: usize,
+    /// SQLite database of indexed swaps and liquidity events, `None` disables the indexer
+    pub index_db_path: Option<String>,
+    /// How often watched pairs are indexed
+    pub index_interval: Duration,
+    /// How far back the indexer backfills history, `None` walks it all
+    pub index_backfill: Option<Duration>,
+    /// Pairs kept warm in the background so quotes never wait on RPC
+    pub watchlist: Vec<Pubkey>,
+    /// Bearer token for `/api/admin/*`, `None` leaves the admin routes unmounted
+    pub admin_token: Option<String>,
 }
 
 impl Default for AppConfig {
@@ -32,162 +82,546 @@ impl Default for AppConfig {
         AppConfig {
             rpc_url: "https://api.mainnet-beta.solana.com".to_string(),
             cache_ttl: TTLConfig {
-                pool_ttl: Duration::from_secs(15),     // 15 seconds
-                token_ttl: Duration::from_secs(43200), // 12 hours
-                bin_ttl: Duration::from_secs(15),      // 15 seconds
+                pool_ttl: Duration::from_secs(15),        // 15 seconds
+                token_ttl: Duration::from_secs(43200),    // 12 hours
+                bin_ttl: Duration::from_secs(15),         // 15 seconds
+                pair_index_ttl: Duration::from_secs(300), // 5 minutes
+                stats_ttl: Duration::from_secs(300),      // 5 minutes
             },
             lb_program_id: Pubkey::from_str_const("1qbkdrr3z4ryLA7pZykqxvxWPoeifcVKo6ZG9CfkvVE"),
             hook_program_id: Pubkey::from_str_const("mdmavMvJpF4ZcLJNg6VSjuKVMiBo5uKwERTg1ZB9yUH"),
+            token_list_path: None,
+            cache_capacity: 10_000,
+            quote_divergence_bps: 50,
+            cache_dir: Some(".dlmm-cache".to_string()),
+            retry: RetryPolicy::default(),
+            send: SendConfig::default(),
+            lookup_tables: vec![],
+            price_api_url: Some("https://lite-api.jup.ag/price/v2".to_string()),
+            stats_max_signatures: 200,
+            index_db_path: Some(".dlmm-cache/index.sqlite".to_string()),
+            index_interval: Duration::from_secs(30),
+            index_backfill: Some(Duration::from_secs(7 * 86_400)), // 7 days
+            watchlist: vec![],
+            admin_token: None,
         }
     }
 }
 
-#[derive(Clone)]
-pub struct Cached<T> {
-    pub value: Arc<T>,
-    pub last_updated: Instant,
-}
-
-impl<T> Cached<T> {
-    pub fn new(value: T) -> Self {
-        Self {
-            value: Arc::new(value),
-            last_updated: Instant::now(),
-        }
-    }
-
-    pub fn is_expired(&self, ttl: Duration) -> bool {
-        self.last_updated.elapsed() > ttl
-    }
-}
-
 /// The minimal application context
 #[derive(Clone)]
 pub struct AppContext {
     pub config: AppConfig,
     pub rpc_client: Arc<RpcClient>,
-    pub pair_accounts: Arc<RwLock<HashMap<Pubkey, Cached<KeyedAccount>>>>,
-    pub pool_states: Arc<RwLock<HashMap<Pubkey, Cached<Option<PoolState>>>>>,
-    pub mint_accounts: Arc<RwLock<HashMap<Pubkey, Cached<MintAccount>>>>,
-    pub token_meta_cache: Arc<RwLock<HashMap<Pubkey, Cached<TokenMeta>>>>,
+    pub token_list: Arc<TokenList>,
+    pub clock_cache: Arc<TtlCache<Pubkey, Clock>>,
+    pub lookup_table_cache: Arc<TtlCache<Pubkey, AddressLookupTableAccount>>,
+    pub pair_accounts: Arc<TtlCache<Pubkey, KeyedAccount>>,
+    pub pool_states: Arc<TtlCache<Pubkey, PoolState>>,
+    /// Raw accounts a pair's AMM was last loaded from, so quotes can reuse a
+    /// warm read instead of going to the RPC
+    pub amm_accounts: Arc<TtlCache<Pubkey, AccountMap>>,
+    pub mint_accounts: Arc<TtlCache<Pubkey, MintAccount>>,
+    pub token_meta_cache: Arc<TtlCache<Pubkey, TokenMeta>>,
+    /// Off-chain JSON metadata keyed by URI
+    pub off_chain_meta_cache: Arc<TtlCache<String, OffChainMeta>>,
+    /// Recent off-chain metadata failures keyed by URI, so a dead URI is not
+    /// retried on every request
+    pub off_chain_failures: Arc<TtlCache<String, String>>,
+    /// Client for token URIs, `None` when it could not be built
+    pub off_chain_http: Option<reqwest::Client>,
+    pub pair_stats_cache: Arc<TtlCache<Pubkey, PairStats>>,
+    /// Reward hooks keyed by hook address
+    pub hook_rewards_cache: Arc<TtlCache<Pubkey, HookRewards>>,
+    pub pair_index: Arc<RwLock<PairIndex>>,
+    /// Held while the pair index is rebuilt, so only one rebuild runs at a time
+    pair_index_refresh: Arc<Mutex<()>>,
+    /// Epoch from the last clock read, used for Token-2022 transfer fee schedules
+    pub current_epoch: Arc<AtomicU64>,
+    /// Highest context slot seen in any read
+    pub latest_slot: Arc<AtomicU64>,
+    /// Immutable pair fields, kept across restarts
+    pub pair_statics: Arc<RwLock<HashMap<Pubkey, PairStatic>>>,
+    pub store: Option<Arc<DiskStore>>,
+    pub index_db: Option<Arc<IndexDb>>,
+    /// Status pollers of submitted transactions, shared by their subscribers
+    pub tx_trackers: Arc<TxTrackers>,
 }
 
+const TOKEN_META_STORE: &str = "token_meta";
+const PAIR_STATIC_STORE: &str = "pair_static";
+
 impl AppContext {
     pub fn new(config: AppConfig) -> Self {
-        let rpc_client = Arc::new(RpcClient::new(config.rpc_url.clone()));
+        let rpc_client = Arc::new(RpcClient::new_with_timeout(
+            config.rpc_url.clone(),
+            config.retry.timeout,
+        ));
+        let token_list = match &config.token_list_path {
+            Some(path) => TokenList::load(Path::new(path)).unwrap_or_else(|e| {
+                warn!("⚠️ Failed to load token list {}: {}", path, e);
+                TokenList::default()
+            }),
+            None => TokenList::default(),
+        };
+
+        let index_db =
+            config
+                .index_db_path
+                .as_ref()
+                .and_then(|path| match IndexDb::open(Path::new(path)) {
+                    Result::Ok(db) => Some(Arc::new(db)),
+                    Err(e) => {
+                        warn!("⚠️ Swap indexer disabled, cannot open {}: {}", path, e);
+                        None
+                    }
+                });
+
+        let off_chain_http = match OffChainMeta::http_client() {
+            Result::Ok(http) => Some(http),
+            Err(e) => {
+                warn!("⚠️ Off-chain metadata disabled, cannot build client: {}", e);
+                None
+            }
+        };
+
+        let ttl = config.cache_ttl.clone();
+        let capacity = config.cache_capacity;
+        let store = config
+            .cache_dir
+            .as_ref()
+            .and_then(|dir| match DiskStore::new(dir) {
+                Result::Ok(store) => Some(Arc::new(store)),
+                Err(e) => {
+                    warn!("⚠️ Disk cache disabled, cannot open {}: {}", dir, e);
+                    None
+                }
+            });
+
         AppContext {
-            config,
+            tx_trackers: Arc::new(TxTrackers::new(
+                config.rpc_url.clone(),
+                config.send.max_trackers,
+            )),
             rpc_client,
-            pair_accounts: Arc::new(RwLock::new(HashMap::new())),
-            pool_states: Arc::new(RwLock::new(HashMap::new())),
-            mint_accounts: Arc::new(RwLock::new(HashMap::new())),
-            token_meta_cache: Arc::new(RwLock::new(HashMap::new())),
+            token_list: Arc::new(token_list),
+            clock_cache: Arc::new(TtlCache::new("clock", ttl.pool_ttl, 1)),
+            lookup_table_cache: Arc::new(TtlCache::new(
+                "lookup_tables",
+                ttl.pair_index_ttl,
+                capacity,
+            )),
+            pair_accounts: Arc::new(TtlCache::new("pair_accounts", ttl.pool_ttl, capacity)),
+            pool_states: Arc::new(TtlCache::new("pool_states", ttl.bin_ttl, capacity)),
+            amm_accounts: Arc::new(TtlCache::new(
+                "amm_accounts",
+                ttl.pool_ttl.min(ttl.bin_ttl),
+                capacity,
+            )),
+            mint_accounts: Arc::new(TtlCache::new("mint_accounts", ttl.token_ttl, capacity)),
+            token_meta_cache: Arc::new(TtlCache::new("token_meta", ttl.token_ttl, capacity)),
+            off_chain_meta_cache: Arc::new(TtlCache::new(
+                "off_chain_meta",
+                ttl.token_ttl,
+                capacity,
+            )),
+            off_chain_failures: Arc::new(TtlCache::new(
+                "off_chain_failures",
+                ttl.pair_index_ttl,
+                capacity,
+            )),
+            off_chain_http,
+            pair_stats_cache: Arc::new(TtlCache::new("pair_stats", ttl.stats_ttl, capacity)),
+            hook_rewards_cache: Arc::new(TtlCache::new("hook_rewards", ttl.bin_ttl, capacity)),
+            config,
+            pair_index: Arc::new(RwLock::new(PairIndex::default())),
+            pair_index_refresh: Arc::new(Mutex::new(())),
+            current_epoch: Arc::new(AtomicU64::new(0)),
+            latest_slot: Arc::new(AtomicU64::new(0)),
+            pair_statics: Arc::new(RwLock::new(HashMap::new())),
+            store,
+            index_db,
         }
     }
 
+    /// Reload token metadata and static pair data saved by a previous run
+    pub async fn load_persisted(&self) {
+        let Some(store) = &self.store else {
+            return;
+        };
+
+        let token_ttl = self.config.cache_ttl.token_ttl;
+        for entry in store.load::<TokenMeta>(TOKEN_META_STORE) {
+            let age = entry.age();
+            if age < token_ttl && entry.value.source != MetadataSource::Placeholder {
+                self.token_meta_cache
+                    .insert_with_age(entry.value.mint, entry.value, age)
+                    .await;
+            }
+        }
+
+        let mut pair_statics = self.pair_statics.write().await;
+        for entry in store.load::<PairStatic>(PAIR_STATIC_STORE) {
+            pair_statics.insert(entry.value.pair, entry.value);
+        }
+    }
+
+    /// Write token metadata and static pair data to disk
+    pub async fn persist(&self) -> Result<()> {
+        let Some(store) = &self.store else {
+            return Ok(());
+        };
+
+        let token_metas = self
+            .token_meta_cache
+            .snapshot()
+            .await
+            .into_iter()
+            // A placeholder is a failed lookup, it should be retried after a restart
+            .filter(|(_, meta, _)| meta.source != MetadataSource::Placeholder)
+            .map(|(_, meta, age)| StoredEntry::new(meta.as_ref().clone(), age))
+            .collect();
+        store.save::<TokenMeta>(TOKEN_META_STORE, token_metas)?;
+
+        // Pair statics never expire, the age is irrelevant
+        let pair_statics = self
+            .pair_statics
+            .read()
+            .await
+            .values()
+            .map(|pair_static| StoredEntry::new(pair_static.clone(), Duration::ZERO))
+            .collect();
+        store.save::<PairStatic>(PAIR_STATIC_STORE, pair_statics)?;
+
+        Ok(())
+    }
+
+    /// Save persisted caches to disk in the background
+    pub fn spawn_persister(self: Arc<Self>, interval: Duration) {
+        if self.store.is_none() {
+            return;
+        }
+
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                if let Err(e) = self.persist().await {
+                    warn!("⚠️ Failed to persist caches: {}", e);
+                }
+            }
+        });
+    }
+
     pub async fn get_or_spawn_client(&self, pool_key: Pubkey) -> Result<Arc<DLMMClient>> {
-        let ttl = self.config.cache_ttl.clone();
-        let mut cached_pools = self.pair_accounts.write().await;
-        let mut cached_states = self.pool_states.write().await;
-        let mut cached_mints = self.mint_accounts.write().await;
+        let clock = self
+            .clock_cache
+            .get_or_try_insert_with(sysvar::clock::ID, || self.fetch_clock())
+            .await?;
 
+        let amm_context = AmmContext {
+            clock_ref: ClockRef::try_from(clock.as_ref().clone())?,
+        };
+
+        let pair_account = self
+            .pair_accounts
+            .get_or_try_insert_at_slot(pool_key, || async {
+                info!("Spawning new DLMMClient for pool: {:?}", pool_key);
+                self.load_pair_account(pool_key).await
+            })
+            .await?;
+
+        let saros_dlmm = SarosDlmm::from_keyed_account(&pair_account.value, &amm_context)?;
+        if !self.pair_statics.read().await.contains_key(&pool_key) {
+            self.pair_statics.write().await.insert(
+                pool_key,
+                PairStatic {
+                    pair: pool_key,
+                    program_id: saros_dlmm.program_id,
+                    mint_x: saros_dlmm.pair.token_mint_x,
+                    mint_y: saros_dlmm.pair.token_mint_y,
+                    vault_x: saros_dlmm.token_vault[0],
+                    vault_y: saros_dlmm.token_vault[1],
+                    token_program_x: saros_dlmm.token_program[0],
+                    token_program_y: saros_dlmm.token_program[1],
+                    bin_step: saros_dlmm.pair.bin_step,
+                },
+            );
+        }
+
+        Ok(Arc::new(DLMMClient::new(saros_dlmm, pair_account.slot)))
+    }
+
+    /// Read the clock sysvar and record the current epoch
+    pub async fn fetch_clock(&self) -> Result<Clock> {
+        let client = self.rpc_client.clone();
         let clock_data = self
-            .rpc_client
-            .get_account_with_commitment(&sysvar::clock::ID, self.rpc_client.commitment())?
-            .value
+            .config
+            .retry
+            .run_blocking("clock", move || {
+                Ok(client
+                    .get_account_with_commitment(&sysvar::clock::ID, client.commitment())?
+                    .value)
+            })
+            .await?
             .context("Failed to get clock account")?;
 
         let clock: Clock = bincode::deserialize(&clock_data.data)
             .context("Failed to deserialize clock account data")?;
+        self.current_epoch.store(clock.epoch, Ordering::Relaxed);
 
-        let amm_context = AmmContext {
-            clock_ref: ClockRef::try_from(clock)?,
+        Ok(clock)
+    }
+
+    /// Fetch a pair account, caching its pool state and mint accounts read
+    /// at the same slot, and return it with that slot
+    pub async fn load_pair_account(&self, pool_key: Pubkey) -> Result<(KeyedAccount, u64)> {
+        let state = State::generate_state_async(
+            self.rpc_client.clone(),
+            pool_key,
+            self.min_context_slot(),
+            &self.config.retry,
+        )
+        .await?;
+        self.observe_slot(state.slot);
+
+        if let Some(pool_state) = state.pool_state {
+            self.pool_states
+                .insert_at_slot(pool_key, pool_state, state.slot)
+                .await;
+        }
+        self.amm_accounts
+            .insert_at_slot(pool_key, state.accounts, state.slot)
+            .await;
+
+        for mint_account in state.mint_accounts.into_iter() {
+            self.mint_accounts
+                .insert_at_slot(mint_account.key, mint_account, state.slot)
+                .await;
+        }
+
+        Ok((state.pair_account, state.slot))
+    }
+
+    /// Lowest slot an RPC node may answer from, so reads never go back in time
+    pub fn min_context_slot(&self) -> Option<u64> {
+        match self.latest_slot.load(Ordering::Relaxed) {
+            0 => None,
+            slot => Some(slot),
+        }
+    }
+
+    pub fn observe_slot(&self, slot: u64) {
+        self.latest_slot.fetch_max(slot, Ordering::Relaxed);
+    }
+
+    pub async fn fetch_pair_token_info(&self, dlmm_client: &SarosDlmm) -> Result<[TokenMeta; 2]> {
+        let mint_a_state = self.get_token_meta(dlmm_client.pair.token_mint_x).await?;
+        let mint_b_state = self.get_token_meta(dlmm_client.pair.token_mint_y).await?;
+
+        Ok([mint_a_state, mint_b_state])
+    }
+
+    /// Fetch token metadata, bypassing the cache
+    pub async fn load_token_meta(&self, mint: Pubkey) -> Result<TokenMeta> {
+        State::generate_token_state(
+            self.rpc_client.clone(),
+            mint,
+            self.token_list.clone(),
+            &self.config.retry,
+        )
+        .await
+    }
+
+    pub async fn get_token_meta(&self, mint: Pubkey) -> Result<TokenMeta> {
+        let token_meta = self
+            .token_meta_cache
+            .get_or_try_insert_with(mint, || self.load_token_meta(mint))
+            .await?;
+
+        Ok(token_meta.as_ref().clone())
+    }
+
+    pub async fn get_off_chain_meta(&self, uri: &str) -> Result<OffChainMeta> {
+        let Some(http) = &self.off_chain_http else {
+            anyhow::bail!("Off-chain metadata is disabled");
         };
+        if let Some(failure) = self.off_chain_failures.get(&uri.to_string()).await {
+            anyhow::bail!("{} (cached)", failure);
+        }
 
-        if let Some(cached) = cached_pools.get(&pool_key) {
-            if !cached.is_expired(ttl.pool_ttl) {
-                info!("Using cached DLMMClient for pool: {:?}", pool_key);
-                let pair_account = cached.value.as_ref().clone();
-                let saros_dlmm = SarosDlmm::from_keyed_account(&pair_account, &amm_context)?;
-
-                return Ok(Arc::new(DLMMClient {
-                    saros_dlmm: Arc::new(RwLock::new(saros_dlmm)),
-                }));
-            } else {
-                // ⚡ cleanup lazy
-                cached_pools.remove(&pool_key);
-                cached_states.remove(&pool_key);
+        let off_chain_meta = match self
+            .off_chain_meta_cache
+            .get_or_try_insert_with(uri.to_string(), || OffChainMeta::fetch(http, uri))
+            .await
+        {
+            Result::Ok(off_chain_meta) => off_chain_meta,
+            Err(e) => {
+                self.off_chain_failures
+                    .insert(uri.to_string(), e.to_string())
+                    .await;
+                return Err(e);
             }
+        };
+
+        Ok(off_chain_meta.as_ref().clone())
+    }
+
+    /// Pool analytics, recomputed once `stats_ttl` has passed
+    pub async fn get_pair_stats(&self, pair: Pubkey) -> Result<PairStats> {
+        let stats = self
+            .pair_stats_cache
+            .get_or_try_insert_with(pair, || compute_pair_stats(self, pair))
+            .await?;
+
+        Ok(stats.as_ref().clone())
+    }
+
+    /// A hook's rewards around `active_id`, read again once `bin_ttl` has
+    /// passed or the pair moved to another active bin
+    pub async fn get_hook_rewards(
+        &self,
+        hook_key: Pubkey,
+        active_id: u32,
+    ) -> Result<Arc<HookRewards>> {
+        let rewards = self
+            .hook_rewards_cache
+            .get_or_try_insert_with(hook_key, || self.load_hook_rewards(hook_key, active_id))
+            .await?;
+        if rewards.active_id == active_id {
+            return Ok(rewards);
         }
 
-        info!("Spawning new DLMMClient for pool: {:?}", pool_key);
-        let pair_account = State::generate_keyed_account(self.rpc_client.clone(), pool_key).await?;
-        cached_pools.insert(pool_key, Cached::new(pair_account.clone()));
+        let rewards = self.load_hook_rewards(hook_key, active_id).await?;
+        Ok(self.hook_rewards_cache.insert(hook_key, rewards).await)
+    }
 
-        let state =
-            State::generate_state_async(self.rpc_client.clone(), pair_account.clone()).await;
+    async fn load_hook_rewards(&self, hook_key: Pubkey, active_id: u32) -> Result<HookRewards> {
+        State::generate_hook_rewards(
+            self.rpc_client.clone(),
+            hook_key,
+            self.config.hook_program_id,
+            active_id,
+            &self.config.retry,
+        )
+        .await
+    }
 
-        cached_states.insert(pool_key, Cached::new(state.pool_state));
+    /// Configured lookup tables, skipping any that cannot be loaded
+    pub async fn get_lookup_tables(&self) -> Vec<AddressLookupTableAccount> {
+        let mut tables = vec![];
+        for key in self.config.lookup_tables.iter().copied() {
+            let table = self
+                .lookup_table_cache
+                .get_or_try_insert_with(key, || {
+                    let client = self.rpc_client.clone();
+                    self.config
+                        .retry
+                        .run_blocking("lookup table", move || fetch_lookup_table(&client, key))
+                })
+                .await;
 
-        for mint_account in state.mint_accounts.iter() {
-            if cached_mints.contains_key(&mint_account.key) {
-                if let Some(cached) = cached_mints.get(&mint_account.key) {
-                    if !cached.is_expired(ttl.token_ttl) {
-                        continue;
-                    } else {
-                        // ⚡ cleanup lazy
-                        cached_mints.remove(&mint_account.key);
-                    }
-                }
+            match table {
+                Result::Ok(table) => tables.push(table.as_ref().clone()),
+                Err(e) => warn!("⚠️ Skipping lookup table {}: {}", key, e),
             }
-            cached_mints.insert(mint_account.key, Cached::new(mint_account.clone()));
         }
+        tables
+    }
 
-        let saros_dlmm = SarosDlmm::from_keyed_account(&pair_account.clone(), &amm_context)?;
-        let client = Arc::new(DLMMClient {
-            saros_dlmm: Arc::new(RwLock::new(saros_dlmm)),
-        });
-
-        Ok(client)
+    /// Every cache, for the sweeper and the admin endpoint
+    pub fn caches(&self) -> Vec<Arc<dyn CacheAdmin>> {
+        vec![
+            self.clock_cache.clone() as Arc<dyn CacheAdmin>,
+            self.lookup_table_cache.clone() as Arc<dyn CacheAdmin>,
+            self.pair_accounts.clone() as Arc<dyn CacheAdmin>,
+            self.pool_states.clone() as Arc<dyn CacheAdmin>,
+            self.amm_accounts.clone() as Arc<dyn CacheAdmin>,
+            self.mint_accounts.clone() as Arc<dyn CacheAdmin>,
+            self.token_meta_cache.clone() as Arc<dyn CacheAdmin>,
+            self.off_chain_meta_cache.clone() as Arc<dyn CacheAdmin>,
+            self.off_chain_failures.clone() as Arc<dyn CacheAdmin>,
+            self.pair_stats_cache.clone() as Arc<dyn CacheAdmin>,
+            self.hook_rewards_cache.clone() as Arc<dyn CacheAdmin>,
+        ]
     }
 
-    pub async fn fetch_pair_token_info(&self, dlmm_client: &SarosDlmm) -> Result<[TokenMeta; 2]> {
-        let mut mint_a_state = TokenMeta::default();
-        let mut mint_b_state = TokenMeta::default();
-
-        let mut token_cache = self.token_meta_cache.write().await;
-        let ttl = self.config.cache_ttl.token_ttl;
-
-        let mint_x = dlmm_client.pair.token_mint_x;
-        let mint_y = dlmm_client.pair.token_mint_y;
-
-        if let Some(cached_mint_x) = token_cache.get(&mint_x) {
-            if !cached_mint_x.is_expired(ttl) {
-                info!("Using cached token meta for mint_x: {:?}", mint_x);
-                mint_a_state = cached_mint_x.value.as_ref().clone();
-            } else {
-                // ⚡ cleanup lazy
-                token_cache.remove(&mint_x);
+    /// Rebuild the pair index from program accounts and resolve mint symbols.
+    /// A call made while a rebuild runs waits for it instead of starting another.
+    pub async fn refresh_pair_index(&self) -> Result<usize> {
+        let _refresh = match self.pair_index_refresh.try_lock() {
+            Result::Ok(guard) => guard,
+            Err(_) => {
+                let _running = self.pair_index_refresh.lock().await;
+                return Ok(self.pair_index.read().await.entries.len());
+            }
+        };
+
+        let mut index = State::generate_pair_index(
+            self.rpc_client.clone(),
+            self.config.lb_program_id,
+            &self.config.retry,
+        )
+        .await?;
+
+        let mut symbols = HashMap::new();
+        let mut missing = vec![];
+        for mint in index.mints() {
+            match self.token_meta_cache.get(&mint).await {
+                Some(token_meta) => {
+                    symbols.insert(mint, token_meta.symbol.clone());
+                }
+                None => missing.push(mint),
             }
-        } else {
-            mint_a_state = State::generate_token_state(self.rpc_client.clone(), mint_x).await?;
-            token_cache.insert(mint_x, Cached::new(mint_a_state.clone()));
         }
 
-        if let Some(cached_mint_y) = token_cache.get(&mint_y) {
-            if !cached_mint_y.is_expired(ttl) {
-                info!("Using cached token meta for mint_y: {:?}", mint_y);
-                mint_b_state = cached_mint_y.value.as_ref().clone();
-            } else {
-                // ⚡ cleanup lazy
-                token_cache.remove(&mint_y);
+        if !missing.is_empty() {
+            let token_metas = State::generate_token_states(
+                self.rpc_client.clone(),
+                missing,
+                self.token_list.clone(),
+                &self.config.retry,
+            )
+            .await?;
+            for token_meta in token_metas {
+                symbols.insert(token_meta.mint, token_meta.symbol.clone());
+                self.token_meta_cache
+                    .insert(token_meta.mint, token_meta)
+                    .await;
             }
-        } else {
-            mint_b_state = State::generate_token_state(self.rpc_client.clone(), mint_y).await?;
-            token_cache.insert(mint_y, Cached::new(mint_b_state.clone()));
         }
 
-        Ok([mint_a_state, mint_b_state])
+        for entry in index.entries.iter_mut() {
+            entry.symbol_x = symbols.get(&entry.mint_x).cloned();
+            entry.symbol_y = symbols.get(&entry.mint_y).cloned();
+        }
+
+        let count = index.entries.len();
+        *self.pair_index.write().await = index;
+
+        Ok(count)
+    }
+
+    /// Keep the pair index fresh in the background. A zero refresh period
+    /// builds the index once at startup instead.
+    pub fn spawn_pair_index_refresher(self: Arc<Self>) {
+        let period = self.config.cache_ttl.pair_index_ttl;
+        tokio::spawn(async move {
+            // `interval` panics on a zero period
+            let mut interval = (!period.is_zero()).then(|| tokio::time::interval(period));
+            loop {
+                if let Some(interval) = interval.as_mut() {
+                    interval.tick().await;
+                }
+                match self.refresh_pair_index().await {
+                    Result::Ok(count) => info!("📇 Pair index refreshed: {} pairs", count),
+                    Err(e) => warn!("⚠️ Failed to refresh pair index: {}", e),
+                }
+                if interval.is_none() {
+                    break;
+                }
+            }
+        });
     }
 
     fn _check() -> &'static str {
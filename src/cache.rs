@@ -0,0 +1,501 @@
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    future::Future,
+    hash::Hash,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, PoisonError,
+    },
+    time::Duration,
+};
+
+use anyhow::Result;
+use serde::Serialize;
+use tokio::{sync::Mutex, time::Instant};
+use tracing::info;
+
+pub struct Cached<T> {
+    pub value: Arc<T>,
+    pub last_updated: Instant,
+    /// Slot the value was read at, 0 when it does not come from chain state
+    pub slot: u64,
+}
+
+// Manual impl, the value is behind an `Arc` so `T` need not be `Clone`
+impl<T> Clone for Cached<T> {
+    fn clone(&self) -> Self {
+        Self {
+            value: self.value.clone(),
+            last_updated: self.last_updated,
+            slot: self.slot,
+        }
+    }
+}
+
+impl<T> Cached<T> {
+    pub fn new(value: T, slot: u64) -> Self {
+        Self {
+            value: Arc::new(value),
+            last_updated: Instant::now(),
+            slot,
+        }
+    }
+
+    pub fn is_expired(&self, ttl: Duration) -> bool {
+        self.last_updated.elapsed() > ttl
+    }
+}
+
+struct Entry<V> {
+    cached: Cached<V>,
+    /// Access tick, the lowest one is evicted first
+    last_access: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CacheStats {
+    pub name: &'static str,
+    pub entries: usize,
+    pub capacity: usize,
+    pub ttl_secs: u64,
+    pub hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+    pub expirations: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CacheEntryInfo {
+    pub key: String,
+    pub age_secs: u64,
+    pub slot: u64,
+    pub expired: bool,
+}
+
+type InFlight<K> = std::sync::Mutex<HashMap<K, Arc<Mutex<()>>>>;
+
+/// Removes a key's in-flight entry when its loader finishes or is dropped
+struct InFlightGuard<'a, K: Eq + Hash> {
+    in_flight: &'a InFlight<K>,
+    key: &'a K,
+    flight: &'a Arc<Mutex<()>>,
+}
+
+impl<K: Eq + Hash> Drop for InFlightGuard<'_, K> {
+    fn drop(&mut self) {
+        let mut in_flight = self
+            .in_flight
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        // A later flight may already own the key, leave that one alone
+        if in_flight
+            .get(self.key)
+            .is_some_and(|flight| Arc::ptr_eq(flight, self.flight))
+        {
+            in_flight.remove(self.key);
+        }
+    }
+}
+
+/// Bounded TTL cache with LRU eviction, hit/miss counters and single-flight
+/// loading so concurrent misses for one key only fetch once.
+pub struct TtlCache<K, V> {
+    name: &'static str,
+    ttl: Duration,
+    capacity: usize,
+    entries: Mutex<HashMap<K, Entry<V>>>,
+    in_flight: InFlight<K>,
+    tick: AtomicU64,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    evictions: AtomicU64,
+    expirations: AtomicU64,
+}
+
+impl<K, V> TtlCache<K, V>
+where
+    K: Eq + Hash + Clone + Display,
+{
+    pub fn new(name: &'static str, ttl: Duration, capacity: usize) -> Self {
+        TtlCache {
+            name,
+            ttl,
+            capacity: capacity.max(1),
+            entries: Mutex::new(HashMap::new()),
+            in_flight: std::sync::Mutex::new(HashMap::new()),
+            tick: AtomicU64::new(0),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            evictions: AtomicU64::new(0),
+            expirations: AtomicU64::new(0),
+        }
+    }
+
+    pub async fn get(&self, key: &K) -> Option<Arc<V>> {
+        self.get_cached(key).await.map(|cached| cached.value)
+    }
+
+    /// Like `get`, keeping the slot and age of the value
+    pub async fn get_cached(&self, key: &K) -> Option<Cached<V>> {
+        let mut entries = self.entries.lock().await;
+
+        let Some(entry) = entries.get_mut(key) else {
+            self.misses.fetch_add(1, Ordering::Relaxed);
+            return None;
+        };
+
+        if entry.cached.is_expired(self.ttl) {
+            // ⚡ cleanup lazy
+            entries.remove(key);
+            self.expirations.fetch_add(1, Ordering::Relaxed);
+            self.misses.fetch_add(1, Ordering::Relaxed);
+            return None;
+        }
+
+        entry.last_access = self.next_tick();
+        self.hits.fetch_add(1, Ordering::Relaxed);
+        Some(entry.cached.clone())
+    }
+
+    pub async fn insert(&self, key: K, value: V) -> Arc<V> {
+        self.put(key, Cached::new(value, 0)).await.value
+    }
+
+    /// Insert a value read from chain at `slot`
+    pub async fn insert_at_slot(&self, key: K, value: V, slot: u64) -> Cached<V> {
+        self.put(key, Cached::new(value, slot)).await
+    }
+
+    /// Insert a value that was already `age` old, e.g. reloaded from disk
+    pub async fn insert_with_age(&self, key: K, value: V, age: Duration) -> Arc<V> {
+        let mut cached = Cached::new(value, 0);
+        cached.last_updated = Instant::now()
+            .checked_sub(age)
+            .unwrap_or(cached.last_updated);
+        self.put(key, cached).await.value
+    }
+
+    async fn put(&self, key: K, cached: Cached<V>) -> Cached<V> {
+        let result = cached.clone();
+        let mut entries = self.entries.lock().await;
+
+        if !entries.contains_key(&key) && entries.len() >= self.capacity {
+            let lru_key = entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_access)
+                .map(|(key, _)| key.clone());
+            if let Some(lru_key) = lru_key {
+                entries.remove(&lru_key);
+                self.evictions.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        entries.insert(
+            key,
+            Entry {
+                cached,
+                last_access: self.next_tick(),
+            },
+        );
+
+        result
+    }
+
+    /// Return the cached value or load it, letting only one caller per key
+    /// run `load` while the others wait for its result.
+    pub async fn get_or_try_insert_with<F, Fut>(&self, key: K, load: F) -> Result<Arc<V>>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<V>>,
+    {
+        let cached = self
+            .get_or_try_insert_at_slot(key, || async { Ok((load().await?, 0)) })
+            .await?;
+
+        Ok(cached.value)
+    }
+
+    /// `get_or_try_insert_with` for loaders that report the slot they read at
+    pub async fn get_or_try_insert_at_slot<F, Fut>(&self, key: K, load: F) -> Result<Cached<V>>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<(V, u64)>>,
+    {
+        if let Some(cached) = self.get_cached(&key).await {
+            return Ok(cached);
+        }
+
+        let flight = self
+            .in_flight
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .entry(key.clone())
+            .or_insert_with(|| Arc::new(Mutex::new(())))
+            .clone();
+        let _lock = flight.lock().await;
+        // Declared after the lock so it drops first: the in-flight entry goes
+        // once the value is cached, or when the load fails or is cancelled
+        let _in_flight = InFlightGuard {
+            in_flight: &self.in_flight,
+            key: &key,
+            flight: &flight,
+        };
+
+        // Another caller may have loaded it while we waited
+        if let Some(value) = self.peek(&key).await {
+            return Ok(value);
+        }
+
+        let (value, slot) = load().await?;
+        Ok(self.insert_at_slot(key.clone(), value, slot).await)
+    }
+
+    pub async fn clear(&self) -> usize {
+        let mut entries = self.entries.lock().await;
+        let count = entries.len();
+        entries.clear();
+        count
+    }
+
+    /// Drop every expired entry, returning how many went
+    pub async fn sweep_expired(&self) -> usize {
+        let mut entries = self.entries.lock().await;
+        let before = entries.len();
+        entries.retain(|_, entry| !entry.cached.is_expired(self.ttl));
+
+        let swept = before - entries.len();
+        self.expirations.fetch_add(swept as u64, Ordering::Relaxed);
+        swept
+    }
+
+    /// Live entries with their age, for persisting
+    pub async fn snapshot(&self) -> Vec<(K, Arc<V>, Duration)> {
+        self.entries
+            .lock()
+            .await
+            .iter()
+            .filter(|(_, entry)| !entry.cached.is_expired(self.ttl))
+            .map(|(key, entry)| {
+                (
+                    key.clone(),
+                    entry.cached.value.clone(),
+                    entry.cached.last_updated.elapsed(),
+                )
+            })
+            .collect()
+    }
+
+    pub async fn keys(&self) -> Vec<K> {
+        self.entries.lock().await.keys().cloned().collect()
+    }
+
+    pub async fn stats(&self) -> CacheStats {
+        CacheStats {
+            name: self.name,
+            entries: self.entries.lock().await.len(),
+            capacity: self.capacity,
+            ttl_secs: self.ttl.as_secs(),
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            evictions: self.evictions.load(Ordering::Relaxed),
+            expirations: self.expirations.load(Ordering::Relaxed),
+        }
+    }
+
+    pub async fn entries_info(&self) -> Vec<CacheEntryInfo> {
+        self.entries
+            .lock()
+            .await
+            .iter()
+            .map(|(key, entry)| CacheEntryInfo {
+                key: key.to_string(),
+                age_secs: entry.cached.last_updated.elapsed().as_secs(),
+                slot: entry.cached.slot,
+                expired: entry.cached.is_expired(self.ttl),
+            })
+            .collect()
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Look up without touching counters or recency
+    async fn peek(&self, key: &K) -> Option<Cached<V>> {
+        self.entries
+            .lock()
+            .await
+            .get(key)
+            .filter(|entry| !entry.cached.is_expired(self.ttl))
+            .map(|entry| entry.cached.clone())
+    }
+
+    fn next_tick(&self) -> u64 {
+        self.tick.fetch_add(1, Ordering::Relaxed)
+    }
+}
+
+/// Object-safe view of a cache for sweeping and the admin endpoint
+#[async_trait::async_trait]
+pub trait CacheAdmin: Send + Sync {
+    fn name(&self) -> &'static str;
+    async fn stats(&self) -> CacheStats;
+    async fn entries_info(&self) -> Vec<CacheEntryInfo>;
+    async fn sweep_expired(&self) -> usize;
+    async fn clear(&self) -> usize;
+}
+
+#[async_trait::async_trait]
+impl<K, V> CacheAdmin for TtlCache<K, V>
+where
+    K: Eq + Hash + Clone + Display + Send + Sync,
+    V: Send + Sync,
+{
+    fn name(&self) -> &'static str {
+        TtlCache::name(self)
+    }
+
+    async fn stats(&self) -> CacheStats {
+        TtlCache::stats(self).await
+    }
+
+    async fn entries_info(&self) -> Vec<CacheEntryInfo> {
+        TtlCache::entries_info(self).await
+    }
+
+    async fn sweep_expired(&self) -> usize {
+        TtlCache::sweep_expired(self).await
+    }
+
+    async fn clear(&self) -> usize {
+        TtlCache::clear(self).await
+    }
+}
+
+/// Periodically sweep expired entries out of every cache
+pub fn spawn_sweeper(caches: Vec<Arc<dyn CacheAdmin>>, interval: Duration) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            for cache in caches.iter() {
+                let swept = cache.sweep_expired().await;
+                if swept > 0 {
+                    info!("🧹 Swept {} expired entries from {}", swept, cache.name());
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+
+    use super::*;
+
+    const TTL: Duration = Duration::from_secs(60);
+    const STALE: Duration = Duration::from_secs(61);
+
+    fn cache(capacity: usize) -> TtlCache<String, u32> {
+        TtlCache::new("test", TTL, capacity)
+    }
+
+    fn key(name: &str) -> String {
+        name.to_string()
+    }
+
+    #[tokio::test]
+    async fn counts_hits_misses_and_expirations() {
+        let cache = cache(8);
+        cache.insert(key("fresh"), 1).await;
+        cache.insert_with_age(key("stale"), 2, STALE).await;
+
+        assert_eq!(cache.get(&key("fresh")).await.as_deref(), Some(&1));
+        assert_eq!(cache.get(&key("missing")).await, None);
+        assert_eq!(cache.get(&key("stale")).await, None);
+
+        let stats = cache.stats().await;
+        assert_eq!((stats.hits, stats.misses, stats.expirations), (1, 2, 1));
+        // The expired entry was dropped on read
+        assert_eq!(stats.entries, 1);
+    }
+
+    #[tokio::test]
+    async fn evicts_the_least_recently_used() {
+        let cache = cache(2);
+        cache.insert(key("a"), 1).await;
+        cache.insert(key("b"), 2).await;
+        cache.get(&key("a")).await;
+        cache.insert(key("c"), 3).await;
+
+        let mut keys = cache.keys().await;
+        keys.sort();
+        assert_eq!(keys, ["a", "c"]);
+        assert_eq!(cache.stats().await.evictions, 1);
+
+        // Replacing a key does not make room
+        cache.insert(key("c"), 4).await;
+        assert_eq!(cache.stats().await.evictions, 1);
+        assert_eq!(cache.get(&key("c")).await.as_deref(), Some(&4));
+
+        assert_eq!(TtlCache::<String, u32>::new("test", TTL, 0).capacity, 1);
+    }
+
+    #[tokio::test]
+    async fn sweeps_and_snapshots_live_entries() {
+        let cache = cache(8);
+        cache.insert_at_slot(key("fresh"), 1, 42).await;
+        cache.insert_with_age(key("stale"), 2, STALE).await;
+
+        let snapshot = cache.snapshot().await;
+        assert_eq!(snapshot.len(), 1);
+        assert_eq!((snapshot[0].0.as_str(), *snapshot[0].1), ("fresh", 1));
+
+        assert_eq!(cache.sweep_expired().await, 1);
+        assert_eq!(cache.stats().await.expirations, 1);
+        assert_eq!(cache.get_cached(&key("fresh")).await.unwrap().slot, 42);
+
+        assert_eq!(cache.clear().await, 1);
+        assert!(cache.keys().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn concurrent_misses_load_once() {
+        let cache = cache(8);
+        let loads = AtomicUsize::new(0);
+        let load = || async move {
+            loads.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            Ok((7, 100))
+        };
+
+        let (first, second) = tokio::join!(
+            cache.get_or_try_insert_at_slot(key("a"), load),
+            cache.get_or_try_insert_at_slot(key("a"), load),
+        );
+
+        assert_eq!(loads.load(Ordering::SeqCst), 1);
+        assert_eq!((*first.unwrap().value, second.unwrap().slot), (7, 100));
+        assert!(cache.in_flight.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn failed_loads_are_not_cached() {
+        let cache = cache(8);
+
+        let failed = cache
+            .get_or_try_insert_with(key("a"), || async { anyhow::bail!("rpc down") })
+            .await;
+        assert!(failed.is_err());
+        assert!(cache.in_flight.lock().unwrap().is_empty());
+
+        let loaded = cache
+            .get_or_try_insert_with(key("a"), || async { Ok(3) })
+            .await
+            .unwrap();
+        assert_eq!(*loaded, 3);
+        assert_eq!(cache.get_cached(&key("a")).await.unwrap().slot, 0);
+    }
+}
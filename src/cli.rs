@@ -15,4 +15,88 @@ pub enum Commands {
         #[arg(long)]
         web: bool,
     },
-}
\ No newline at end of file
+    /// Find the best 1- or 2-hop route between two mints
+    Route {
+        /// Mint to swap from
+        #[arg(long)]
+        input_mint: String,
+        /// Mint to swap to
+        #[arg(long)]
+        output_mint: String,
+        /// Exact input amount (raw units)
+        #[arg(long)]
+        amount: u64,
+        /// Pair addresses to route through
+        #[arg(long, value_delimiter = ',', required = true)]
+        pairs: Vec<String>,
+        /// Wallet to build the unsigned v0 swap transaction for
+        #[arg(long)]
+        signer: Option<String>,
+        /// Slippage applied to every hop's minimum output
+        #[arg(long, default_value_t = 50)]
+        slippage_bps: u16,
+    },
+    /// Show a pair's reserves, 24h volume and fees, and fee APR
+    Pair {
+        /// Pair address
+        #[arg(long)]
+        address: String,
+    },
+    /// Show a position's value, impermanent loss, fees earned and net PnL
+    Pnl {
+        /// Position address
+        #[arg(long)]
+        position: String,
+        /// Net X deposited (raw units), overrides the indexed deposits
+        #[arg(long)]
+        deposit_x: Option<u64>,
+        /// Net Y deposited (raw units), overrides the indexed deposits
+        #[arg(long)]
+        deposit_y: Option<u64>,
+        /// Y per X (UI units) at deposit, overrides the indexed price
+        #[arg(long)]
+        deposit_price: Option<f64>,
+    },
+    /// Run one indexing pass over a pair's swap and liquidity history
+    Index {
+        /// Pair address
+        #[arg(long)]
+        pair: String,
+        /// Directory of recorded `getTransaction` results to replay instead of RPC
+        #[arg(long)]
+        fixtures: Option<String>,
+    },
+    /// Discover Saros pairs from on-chain program accounts
+    Pairs {
+        #[command(subcommand)]
+        command: PairsCommands,
+    },
+    /// Create or extend an address lookup table with a pair's static accounts
+    Lut {
+        /// Pair whose accounts go into the table
+        #[arg(long)]
+        pair: String,
+        /// Keypair file paying for and owning the table
+        #[arg(long)]
+        keypair: String,
+        /// Existing table to extend instead of creating a new one
+        #[arg(long)]
+        table: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum PairsCommands {
+    /// Search pairs by mint, symbol or bin step
+    Search {
+        /// Mint contained in the pair (X or Y)
+        #[arg(long)]
+        mint: Option<String>,
+        /// Token symbol contained in the pair
+        #[arg(long)]
+        symbol: Option<String>,
+        /// Pair bin step
+        #[arg(long)]
+        bin_step: Option<u8>,
+    },
+}
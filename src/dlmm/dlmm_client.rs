@@ -1,57 +1,215 @@
-use anyhow::Result;
-use std::{collections::HashMap, sync::Arc};
+use anyhow::{bail, Result};
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
 use tokio::sync::RwLock;
 
-use ahash::RandomState;
-use jupiter_amm_interface::Amm;
+use jupiter_amm_interface::{Amm, QuoteParams, SwapMode};
 use saros_dlmm::SarosDlmm;
-use solana_sdk::{account::Account, pubkey::Pubkey};
+use saros_sdk::{
+    state::bin_array::BinArray,
+    utils::helper::{get_pair_bin_array, is_swap_for_y},
+};
+use solana_sdk::program_pack::Pack;
 
-use crate::app::AppContext;
+use crate::{
+    app::AppContext,
+    dlmm::{build_quote_response, TransferFees},
+    state::{AccountMap, PoolState, QuoteResponse, SlotAccounts, TokenMeta},
+};
 
 pub struct DLMMClient {
     pub saros_dlmm: Arc<RwLock<SarosDlmm>>,
+    /// Slot the pair and bin arrays were last read at
+    pub slot: AtomicU64,
 }
 
+/// A swap can move the pair into another bin array between reads, in which
+/// case the accounts are read again
+const MAX_UPDATE_ROUNDS: usize = 3;
+
 pub trait UpdateAmm: Amm {
-    async fn update_amm(&mut self, ctx: &AppContext) -> Result<()>;
+    /// Refresh every dependent account from one slot, returning that slot
+    async fn update_amm(&mut self, ctx: &AppContext) -> Result<u64>;
 }
 
 impl UpdateAmm for SarosDlmm {
-    async fn update_amm(&mut self, ctx: &AppContext) -> Result<()> {
-        let accounts_to_update = self.get_accounts_to_update();
+    async fn update_amm(&mut self, ctx: &AppContext) -> Result<u64> {
+        let mut accounts_to_update = self.get_accounts_to_update();
+        let mut min_context_slot = ctx.min_context_slot();
+
+        for _ in 0..MAX_UPDATE_ROUNDS {
+            let client = ctx.rpc_client.clone();
+            let keys = accounts_to_update.clone();
+            let accounts = ctx
+                .config
+                .retry
+                .run_blocking("pair update", move || {
+                    SlotAccounts::fetch(&client, &keys, min_context_slot)
+                })
+                .await?;
+            let slot = accounts.slot;
+            ctx.observe_slot(slot);
 
-        let cached_state = ctx.pool_states.read().await;
-        let ttl = ctx.config.cache_ttl.clone();
+            let account_map: AccountMap = accounts.into_map();
+            self.update(&account_map)?;
 
-        if let Some(cached_account) = cached_state.get(&self.key) {
-            if !cached_account.is_expired(ttl.bin_ttl) {
-                // continue;
+            // The accounts were chosen from the previous pair state, read again
+            // if the updated pair now points at different bin arrays
+            let next_accounts = self.get_accounts_to_update();
+            if next_accounts != accounts_to_update {
+                accounts_to_update = next_accounts;
+                min_context_slot = Some(slot);
+                continue;
             }
-        }
 
-        let account_map: HashMap<Pubkey, Account, RandomState> = ctx
-            .rpc_client
-            .get_multiple_accounts(&accounts_to_update)
-            .unwrap()
-            .into_iter()
-            .zip(accounts_to_update)
-            .fold(HashMap::default(), |mut m, (account, address)| {
-                if let Some(account) = account {
-                    m.insert(address, account);
-                }
-                m
-            });
-        self.update(&account_map).unwrap();
+            // Keep the cached bins in step with the state the quote is computed from
+            let (bin_array_lower_key, bin_array_upper_key) =
+                get_pair_bin_array(self.pair.bin_array_index(), &self.key, &self.program_id);
+            if let (Some(lower), Some(upper)) = (
+                account_map.get(&bin_array_lower_key),
+                account_map.get(&bin_array_upper_key),
+            ) {
+                let pool_state = PoolState::new(
+                    &self.pair,
+                    BinArray::unpack(&lower.data)?,
+                    BinArray::unpack(&upper.data)?,
+                );
+                ctx.pool_states
+                    .insert_at_slot(self.key, pool_state, slot)
+                    .await;
+            }
+            ctx.amm_accounts
+                .insert_at_slot(self.key, account_map, slot)
+                .await;
 
-        Ok(())
+            return Ok(slot);
+        }
+
+        bail!("Pair {} kept moving bin arrays while being read", self.key)
     }
 }
 
 impl DLMMClient {
-    pub async fn update(&self, ctx: &AppContext) -> Result<()> {
+    pub fn new(saros_dlmm: SarosDlmm, slot: u64) -> Self {
+        DLMMClient {
+            saros_dlmm: Arc::new(RwLock::new(saros_dlmm)),
+            slot: AtomicU64::new(slot),
+        }
+    }
+
+    /// Load the pair from the accounts the warmer or an earlier request read,
+    /// going to the RPC only when they expired or miss a bin array
+    pub async fn refresh(&self, ctx: &AppContext) -> Result<()> {
         let mut s = self.saros_dlmm.write().await;
-        s.update_amm(ctx).await?;
+
+        if let Some(cached) = ctx.amm_accounts.get_cached(&s.key).await {
+            let keys = s.get_accounts_to_update();
+            if cached.slot >= self.slot.load(Ordering::Relaxed)
+                && keys.iter().all(|key| cached.value.contains_key(key))
+            {
+                s.update(&cached.value)?;
+                // The pair may point at bin arrays this read did not include
+                if s.get_accounts_to_update() == keys {
+                    self.slot.store(cached.slot, Ordering::Relaxed);
+                    return Ok(());
+                }
+            }
+        }
+
+        let slot = s.update_amm(ctx).await?;
+        self.slot.store(slot, Ordering::Relaxed);
         Ok(())
     }
+
+    /// Quote against the current pair state and enrich it with bin-walk details.
+    /// Token-2022 transfer fees on either side are taken out of what the pool
+    /// receives and what the user ends up with.
+    pub async fn quote(&self, ctx: &AppContext, params: &QuoteParams) -> Result<QuoteResponse> {
+        let epoch = ctx.current_epoch.load(Ordering::Relaxed);
+        let mut warnings = vec![];
+
+        let input_meta = ctx
+            .get_token_meta(params.input_mint)
+            .await
+            .unwrap_or_else(|e| {
+                warnings.push(format!("No token info for {}: {}", params.input_mint, e));
+                TokenMeta::default()
+            });
+        let output_meta = ctx
+            .get_token_meta(params.output_mint)
+            .await
+            .unwrap_or_else(|e| {
+                warnings.push(format!("No token info for {}: {}", params.output_mint, e));
+                TokenMeta::default()
+            });
+
+        for meta in [&input_meta, &output_meta] {
+            if let Some(program) = meta.transfer_hook_program {
+                warnings.push(format!(
+                    "Mint {} has a transfer hook ({}), swaps need its extra accounts",
+                    meta.mint, program
+                ));
+            }
+        }
+
+        let s = self.saros_dlmm.read().await;
+        let pool_params = |amount: u64| QuoteParams {
+            amount,
+            input_mint: params.input_mint,
+            output_mint: params.output_mint,
+            swap_mode: params.swap_mode,
+        };
+
+        let (mut quote, fees) = match params.swap_mode {
+            SwapMode::ExactIn => {
+                let input_fee = input_meta.transfer_fee(epoch, params.amount);
+                let mut quote = s.quote(&pool_params(params.amount - input_fee))?;
+                let output_fee = output_meta.transfer_fee(epoch, quote.out_amount);
+
+                quote.in_amount = params.amount;
+                quote.out_amount -= output_fee;
+                (quote, TransferFees::new(input_fee, output_fee))
+            }
+            SwapMode::ExactOut => {
+                let gross_out = output_meta.pre_fee_amount(epoch, params.amount);
+                let mut quote = s.quote(&pool_params(gross_out))?;
+                let gross_in = input_meta.pre_fee_amount(epoch, quote.in_amount);
+
+                let fees = TransferFees::new(gross_in - quote.in_amount, gross_out - params.amount);
+                quote.in_amount = gross_in;
+                quote.out_amount = params.amount;
+                (quote, fees)
+            }
+        };
+
+        let swap_for_y = is_swap_for_y(params.input_mint, s.pair.token_mint_x);
+
+        // Bins from another slot would not match the quote, leave them out
+        let slot = self.slot.load(Ordering::Relaxed);
+        let pool_state = ctx
+            .pool_states
+            .get_cached(&s.key)
+            .await
+            .filter(|cached| cached.slot == slot);
+        if pool_state.is_none() {
+            warnings.push(format!(
+                "No bins read at slot {}, bin details omitted",
+                slot
+            ));
+        }
+
+        let mut response = build_quote_response(
+            &s,
+            pool_state.as_ref().map(|cached| cached.value.as_ref()),
+            &quote,
+            swap_for_y,
+            fees,
+        );
+        response.warnings = warnings;
+        response.context_slot = slot;
+
+        Ok(response)
+    }
 }
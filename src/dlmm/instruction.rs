@@ -0,0 +1,303 @@
+use anchor_lang::{prelude::AccountMeta, solana_program::hash::hash, AnchorSerialize};
+use anyhow::{bail, Result};
+use saros_dlmm::SarosDlmm;
+use saros_sdk::{
+    instruction::{build_swap_instruction_data, BuildSwapInstructionDataParams},
+    math::swap_manager::SwapType,
+    utils::helper::is_swap_for_y,
+};
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey};
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+
+use crate::{
+    dlmm::BinLiquidityDistribution,
+    state::{
+        bin_array_key, hook_bin_array_key, hook_position_key, LiquidityPosition, BIN_ARRAY_SIZE,
+    },
+};
+
+pub const MEMO_PROGRAM_ID: Pubkey =
+    Pubkey::from_str_const("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr");
+
+/// Build a Saros swap instruction for `user`, including hook remaining accounts
+pub fn build_swap_instruction(
+    client: &SarosDlmm,
+    hook_program_id: Pubkey,
+    user: Pubkey,
+    source_mint: Pubkey,
+    amount: u64,
+    other_amount_threshold: u64,
+) -> Result<Instruction> {
+    let user_token_vault_x = get_associated_token_address_with_program_id(
+        &user,
+        &client.pair.token_mint_x,
+        &client.token_program[0],
+    );
+
+    let user_token_vault_y = get_associated_token_address_with_program_id(
+        &user,
+        &client.pair.token_mint_y,
+        &client.token_program[1],
+    );
+
+    let is_swap_for_y = is_swap_for_y(source_mint, client.pair.token_mint_x);
+    let swap_mode = if is_swap_for_y {
+        if source_mint == client.pair.token_mint_x {
+            SwapType::ExactIn
+        } else {
+            SwapType::ExactOut
+        }
+    } else {
+        if source_mint == client.pair.token_mint_x {
+            SwapType::ExactOut
+        } else {
+            SwapType::ExactIn
+        }
+    };
+
+    let bin_for_swap = client.compute_bin_array_swap()?;
+
+    let swap_instruction_data = build_swap_instruction_data(BuildSwapInstructionDataParams {
+        amount,
+        other_amount_threshold,
+        swap_for_y: is_swap_for_y,
+        swap_mode,
+    })?;
+
+    let mut account_metas = Vec::new();
+
+    {
+        account_metas.push(AccountMeta::new(client.key, false));
+        account_metas.push(AccountMeta::new_readonly(client.pair.token_mint_x, false));
+        account_metas.push(AccountMeta::new_readonly(client.pair.token_mint_y, false));
+        account_metas.push(AccountMeta::new(bin_for_swap.bin_array_keys[0], false));
+        account_metas.push(AccountMeta::new(bin_for_swap.bin_array_keys[1], false));
+        account_metas.push(AccountMeta::new(client.token_vault[0], false));
+        account_metas.push(AccountMeta::new(client.token_vault[1], false));
+        account_metas.push(AccountMeta::new(user_token_vault_x, false));
+        account_metas.push(AccountMeta::new(user_token_vault_y, false));
+        account_metas.push(AccountMeta::new_readonly(user, true));
+        account_metas.push(AccountMeta::new_readonly(client.token_program[0], false));
+        account_metas.push(AccountMeta::new_readonly(client.token_program[1], false));
+        account_metas.push(AccountMeta::new_readonly(MEMO_PROGRAM_ID, false));
+    }
+
+    // If pair does not have hook, hook should be pair key (dummy)
+    account_metas.push(AccountMeta::new(client.hook, false));
+    account_metas.push(AccountMeta::new_readonly(hook_program_id, false));
+    // This expect as the last of swap instruction
+    account_metas.push(AccountMeta::new_readonly(client.event_authority, false));
+    account_metas.push(AccountMeta::new_readonly(client.program_id, false));
+
+    // Remaining accounts for hook CPI call
+    if client.hook != client.key {
+        let [hook_bin_array_lower, hook_bin_array_upper] =
+            hook_bin_array_keys(client, hook_program_id);
+
+        account_metas.push(AccountMeta::new(hook_bin_array_lower, false));
+        account_metas.push(AccountMeta::new(hook_bin_array_upper, false));
+    }
+
+    Ok(Instruction {
+        program_id: client.program_id,
+        accounts: account_metas,
+        data: swap_instruction_data,
+    })
+}
+
+/// Hook bin array PDAs around the pair's active bin array
+pub fn hook_bin_array_keys(client: &SarosDlmm, hook_program_id: Pubkey) -> [Pubkey; 2] {
+    let bin_array_index = client.pair.bin_array_index();
+    [
+        hook_bin_array_key(&client.hook, bin_array_index, &hook_program_id),
+        hook_bin_array_key(&client.hook, bin_array_index + 1, &hook_program_id),
+    ]
+}
+
+/// Build a Saros `increase_position` instruction depositing `amounts` (X, Y)
+/// into an existing position, spread by `distribution` around the active bin.
+/// Account order follows the liquidity book program IDL.
+pub fn build_add_liquidity_instruction(
+    client: &SarosDlmm,
+    hook_program_id: Pubkey,
+    user: Pubkey,
+    position_key: Pubkey,
+    position: &LiquidityPosition,
+    amounts: [u64; 2],
+    distribution: &[BinLiquidityDistribution],
+) -> Result<Instruction> {
+    if position.pair != client.key {
+        bail!(
+            "Position {} belongs to pair {}, not {}",
+            position_key,
+            position.pair,
+            client.key
+        );
+    }
+    check_position_range(
+        client.pair.active_id,
+        position.lower_bin_id,
+        position.upper_bin_id,
+        distribution,
+    )?;
+
+    let user_token_vault_x = get_associated_token_address_with_program_id(
+        &user,
+        &client.pair.token_mint_x,
+        &client.token_program[0],
+    );
+    let user_token_vault_y = get_associated_token_address_with_program_id(
+        &user,
+        &client.pair.token_mint_y,
+        &client.token_program[1],
+    );
+    let position_token_account = get_associated_token_address_with_program_id(
+        &user,
+        &position.position_mint,
+        &spl_token_2022::ID,
+    );
+
+    // A position spans at most two bin arrays, from the one holding its lower bin
+    let bin_array_index = position.lower_bin_id.max(0) as u32 / BIN_ARRAY_SIZE;
+    let [bin_array_lower, bin_array_upper] = [bin_array_index, bin_array_index + 1]
+        .map(|index| bin_array_key(&client.key, index, &client.program_id));
+
+    let mut account_metas = vec![
+        AccountMeta::new(client.key, false),
+        AccountMeta::new(position_key, false),
+        AccountMeta::new(bin_array_lower, false),
+        AccountMeta::new(bin_array_upper, false),
+        AccountMeta::new(client.token_vault[0], false),
+        AccountMeta::new(client.token_vault[1], false),
+        AccountMeta::new(user_token_vault_x, false),
+        AccountMeta::new(user_token_vault_y, false),
+        AccountMeta::new_readonly(position_token_account, false),
+        AccountMeta::new_readonly(client.pair.token_mint_x, false),
+        AccountMeta::new_readonly(client.pair.token_mint_y, false),
+        AccountMeta::new_readonly(user, true),
+        AccountMeta::new_readonly(client.token_program[0], false),
+        AccountMeta::new_readonly(client.token_program[1], false),
+        AccountMeta::new_readonly(spl_token_2022::ID, false),
+        AccountMeta::new_readonly(MEMO_PROGRAM_ID, false),
+        // If pair does not have hook, hook should be pair key (dummy)
+        AccountMeta::new(client.hook, false),
+        AccountMeta::new_readonly(hook_program_id, false),
+        AccountMeta::new_readonly(client.event_authority, false),
+        AccountMeta::new_readonly(client.program_id, false),
+    ];
+
+    // Remaining accounts for the hook to settle the position's rewards
+    if client.hook != client.key {
+        for index in [bin_array_index, bin_array_index + 1] {
+            account_metas.push(AccountMeta::new(
+                hook_bin_array_key(&client.hook, index, &hook_program_id),
+                false,
+            ));
+        }
+        account_metas.push(AccountMeta::new(
+            hook_position_key(&client.hook, &position_key, &hook_program_id),
+            false,
+        ));
+    }
+
+    Ok(Instruction {
+        program_id: client.program_id,
+        accounts: account_metas,
+        data: add_liquidity_data(amounts[0], amounts[1], distribution)?,
+    })
+}
+
+/// Anchor sighash of a liquidity book instruction
+pub fn instruction_discriminator(name: &str) -> [u8; 8] {
+    let mut discriminator = [0u8; 8];
+    discriminator.copy_from_slice(&hash(format!("global:{}", name).as_bytes()).to_bytes()[..8]);
+    discriminator
+}
+
+/// `increase_position` arguments: amounts, then the per-bin distribution
+fn add_liquidity_data(
+    amount_x: u64,
+    amount_y: u64,
+    distribution: &[BinLiquidityDistribution],
+) -> Result<Vec<u8>> {
+    let mut data = instruction_discriminator("increase_position").to_vec();
+    amount_x.serialize(&mut data)?;
+    amount_y.serialize(&mut data)?;
+    distribution.serialize(&mut data)?;
+    Ok(data)
+}
+
+/// Every bin of `distribution` must fall inside the position
+fn check_position_range(
+    active_id: u32,
+    lower_bin_id: i32,
+    upper_bin_id: i32,
+    distribution: &[BinLiquidityDistribution],
+) -> Result<()> {
+    if distribution.is_empty() {
+        bail!("Distribution has no bins");
+    }
+
+    let range = lower_bin_id as i64..=upper_bin_id as i64;
+    for bin in distribution {
+        let bin_id = active_id as i64 + bin.relative_bin_id as i64;
+        if !range.contains(&bin_id) {
+            bail!(
+                "Bin {} is outside the position's range [{}, {}]",
+                bin_id,
+                range.start(),
+                range.end()
+            );
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ACTIVE_ID: u32 = 8_388_608;
+
+    fn bin(
+        relative_bin_id: i32,
+        distribution_x: u16,
+        distribution_y: u16,
+    ) -> BinLiquidityDistribution {
+        BinLiquidityDistribution {
+            relative_bin_id,
+            distribution_x,
+            distribution_y,
+        }
+    }
+
+    #[test]
+    fn add_liquidity_data_layout() {
+        let data =
+            add_liquidity_data(1_000, 2_000, &[bin(-1, 0, 10_000), bin(1, 10_000, 0)]).unwrap();
+
+        assert_eq!(data[..8], instruction_discriminator("increase_position"));
+        assert_eq!(data[8..16], 1_000u64.to_le_bytes());
+        assert_eq!(data[16..24], 2_000u64.to_le_bytes());
+        // Borsh vec: u32 length, then i32 + u16 + u16 per bin
+        assert_eq!(data[24..28], 2u32.to_le_bytes());
+        assert_eq!(data[28..32], (-1i32).to_le_bytes());
+        assert_eq!(data[32..34], 0u16.to_le_bytes());
+        assert_eq!(data[34..36], 10_000u16.to_le_bytes());
+        assert_eq!(data.len(), 28 + 2 * 8);
+    }
+
+    #[test]
+    fn distribution_must_fit_the_position() {
+        let lower = ACTIVE_ID as i32 - 2;
+        let upper = ACTIVE_ID as i32 + 2;
+
+        assert!(
+            check_position_range(ACTIVE_ID, lower, upper, &[bin(-2, 0, 1), bin(2, 1, 0)]).is_ok()
+        );
+        assert!(check_position_range(ACTIVE_ID, lower, upper, &[bin(3, 1, 0)]).is_err());
+        assert!(check_position_range(ACTIVE_ID, lower, upper, &[bin(-3, 0, 1)]).is_err());
+        assert!(check_position_range(ACTIVE_ID, lower, upper, &[]).is_err());
+    }
+}
@@ -1,3 +1,13 @@
 mod dlmm_client;
+mod instruction;
+mod quote;
+mod router;
+mod strategy;
+mod transaction;
 
 pub use dlmm_client::*;
+pub use instruction::*;
+pub use quote::*;
+pub use router::*;
+pub use strategy::*;
+pub use transaction::*;
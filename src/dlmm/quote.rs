@@ -0,0 +1,275 @@
+use jupiter_amm_interface::Quote;
+use saros_dlmm::SarosDlmm;
+
+use crate::state::{PoolState, QuoteResponse};
+
+/// Bin id at which the price is exactly 1 (2^23).
+pub const ID_CENTER: u32 = 8_388_608;
+
+/// Price of token Y per token X (raw units) for a given bin.
+pub fn get_price_from_id(bin_step: u8, bin_id: u32) -> f64 {
+    let base = 1.0 + bin_step as f64 / 10_000.0;
+    base.powi(bin_id as i32 - ID_CENTER as i32)
+}
+
+/// Bin whose price (Y per X, raw units) is nearest to `price`
+pub fn get_id_from_price(bin_step: u8, price: f64) -> u32 {
+    let base = 1.0 + bin_step as f64 / 10_000.0;
+    let offset = (price.ln() / base.ln()).round() as i64;
+    (ID_CENTER as i64)
+        .saturating_add(offset)
+        .clamp(0, u32::MAX as i64) as u32
+}
+
+/// Token-2022 transfer fees withheld on the way into and out of the pool
+#[derive(Default, Clone, Copy)]
+pub struct TransferFees {
+    pub input: u64,
+    pub output: u64,
+}
+
+impl TransferFees {
+    pub fn new(input: u64, output: u64) -> Self {
+        TransferFees { input, output }
+    }
+}
+
+pub struct SwapPath {
+    pub end_active_id: u32,
+    pub bins_crossed: u32,
+}
+
+/// Walk the loaded bins from the active id in the swap direction, consuming
+/// the output reserves until `out_amount` is covered.
+pub fn walk_bins(
+    pool_state: &PoolState,
+    active_id: u32,
+    swap_for_y: bool,
+    out_amount: u64,
+) -> SwapPath {
+    walk_reserves(
+        |bin_id| {
+            pool_state.bin(bin_id).map(|bin| {
+                if swap_for_y {
+                    bin.reserve_y
+                } else {
+                    bin.reserve_x
+                }
+            })
+        },
+        active_id,
+        swap_for_y,
+        out_amount,
+    )
+}
+
+/// `walk_bins` over the output reserve of each bin, `None` past the loaded bins
+fn walk_reserves(
+    reserve_of: impl Fn(u32) -> Option<u64>,
+    active_id: u32,
+    swap_for_y: bool,
+    out_amount: u64,
+) -> SwapPath {
+    let mut remaining = out_amount;
+    let mut bin_id = active_id;
+
+    while remaining > 0 {
+        let Some(reserve) = reserve_of(bin_id) else {
+            break;
+        };
+
+        if remaining <= reserve {
+            break;
+        }
+        remaining -= reserve;
+
+        let next_id = if swap_for_y {
+            bin_id.checked_sub(1)
+        } else {
+            bin_id.checked_add(1)
+        };
+        match next_id {
+            Some(id) if reserve_of(id).is_some() => bin_id = id,
+            _ => break,
+        }
+    }
+
+    SwapPath {
+        end_active_id: bin_id,
+        bins_crossed: bin_id.abs_diff(active_id),
+    }
+}
+
+/// Enrich a quote with spot/execution price, price impact and how far the
+/// trade walks the book. `quote` holds what the user sends and receives, so
+/// the execution price includes transfer fees.
+pub fn build_quote_response(
+    saros_dlmm: &SarosDlmm,
+    pool_state: Option<&PoolState>,
+    quote: &Quote,
+    swap_for_y: bool,
+    transfer_fees: TransferFees,
+) -> QuoteResponse {
+    let active_id = saros_dlmm.pair.active_id;
+    let spot_price = get_price_from_id(saros_dlmm.pair.bin_step, active_id);
+
+    let execution_price =
+        execution_price(spot_price, swap_for_y, quote.in_amount, quote.out_amount);
+    let price_impact_pct = price_impact_pct(spot_price, execution_price, swap_for_y);
+
+    let path = match pool_state {
+        Some(pool_state) => walk_bins(
+            pool_state,
+            active_id,
+            swap_for_y,
+            quote.out_amount + transfer_fees.output,
+        ),
+        None => SwapPath {
+            end_active_id: active_id,
+            bins_crossed: 0,
+        },
+    };
+
+    QuoteResponse {
+        in_amount: quote.in_amount,
+        out_amount: quote.out_amount,
+        fee_amount: quote.fee_amount,
+        fee_mint: quote.fee_mint,
+        spot_price,
+        execution_price,
+        price_impact_pct,
+        bins_crossed: path.bins_crossed,
+        end_active_id: path.end_active_id,
+        input_transfer_fee: transfer_fees.input,
+        output_transfer_fee: transfer_fees.output,
+        warnings: vec![],
+    }
+}
+
+/// Y per X paid for the trade, so spot and execution prices are comparable
+fn execution_price(spot_price: f64, swap_for_y: bool, in_amount: u64, out_amount: u64) -> f64 {
+    match (swap_for_y, in_amount, out_amount) {
+        (_, 0, _) | (_, _, 0) => spot_price,
+        (true, in_amount, out_amount) => out_amount as f64 / in_amount as f64,
+        (false, in_amount, out_amount) => in_amount as f64 / out_amount as f64,
+    }
+}
+
+/// How much worse than spot the trade executes, never negative
+fn price_impact_pct(spot_price: f64, execution_price: f64, swap_for_y: bool) -> f64 {
+    if spot_price <= 0.0 {
+        return 0.0;
+    }
+
+    let diff = if swap_for_y {
+        spot_price - execution_price
+    } else {
+        execution_price - spot_price
+    };
+    (diff / spot_price * 100.0).max(0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual / expected - 1.0).abs() < 1e-9,
+            "{} != {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn price_is_one_at_the_center_bin() {
+        for bin_step in [1, 10, 25, 100, 255] {
+            assert_eq!(get_price_from_id(bin_step, ID_CENTER), 1.0);
+            assert_eq!(get_id_from_price(bin_step, 1.0), ID_CENTER);
+        }
+    }
+
+    #[test]
+    fn price_compounds_by_bin_step() {
+        assert_close(get_price_from_id(10, ID_CENTER + 1), 1.001);
+        assert_close(get_price_from_id(10, ID_CENTER - 1), 1.0 / 1.001);
+        assert_close(get_price_from_id(25, ID_CENTER + 100), 1.0025f64.powi(100));
+        assert_close(get_price_from_id(100, ID_CENTER - 500), 1.01f64.powi(-500));
+    }
+
+    #[test]
+    fn id_from_price_round_trips() {
+        for bin_step in [1, 10, 25, 100, 255] {
+            for offset in [-5_000i32, -777, -1, 0, 1, 42, 5_000] {
+                let bin_id = ID_CENTER.saturating_add_signed(offset);
+                let price = get_price_from_id(bin_step, bin_id);
+                assert_eq!(get_id_from_price(bin_step, price), bin_id);
+                // Nearest bin, not the one below
+                assert_eq!(get_id_from_price(bin_step, price * 1.00001), bin_id);
+                assert_eq!(get_id_from_price(bin_step, price / 1.00001), bin_id);
+            }
+        }
+    }
+
+    #[test]
+    fn id_from_price_clamps_extreme_prices() {
+        assert_eq!(get_id_from_price(1, 0.0), 0);
+        assert_eq!(get_id_from_price(1, f64::INFINITY), u32::MAX);
+    }
+
+    #[test]
+    fn execution_price_is_y_per_x() {
+        // Selling 100 X for 190 Y, or buying 100 X with 210 Y
+        assert_close(execution_price(2.0, true, 100, 190), 1.9);
+        assert_close(execution_price(2.0, false, 210, 100), 2.1);
+        // Nothing traded, nothing to divide
+        assert_eq!(execution_price(2.0, true, 0, 190), 2.0);
+        assert_eq!(execution_price(2.0, false, 210, 0), 2.0);
+    }
+
+    #[test]
+    fn price_impact_counts_only_adverse_moves() {
+        assert_close(price_impact_pct(2.0, 1.9, true), 5.0);
+        assert_close(price_impact_pct(2.0, 2.1, false), 5.0);
+        // Better than spot is no impact
+        assert_eq!(price_impact_pct(2.0, 2.1, true), 0.0);
+        assert_eq!(price_impact_pct(2.0, 1.9, false), 0.0);
+        assert_eq!(price_impact_pct(0.0, 1.9, true), 0.0);
+    }
+
+    /// Bins 100 to 104 loaded, 10 units of output each
+    fn reserve_of(bin_id: u32) -> Option<u64> {
+        (100..=104).contains(&bin_id).then_some(10)
+    }
+
+    fn walk(active_id: u32, swap_for_y: bool, out_amount: u64) -> (u32, u32) {
+        let path = walk_reserves(reserve_of, active_id, swap_for_y, out_amount);
+        (path.end_active_id, path.bins_crossed)
+    }
+
+    #[test]
+    fn walk_stops_in_the_bin_covering_the_output() {
+        assert_eq!(walk(100, false, 0), (100, 0));
+        assert_eq!(walk(100, false, 10), (100, 0));
+        assert_eq!(walk(100, false, 11), (101, 1));
+        assert_eq!(walk(100, false, 35), (103, 3));
+
+        // Swapping for Y moves down
+        assert_eq!(walk(104, true, 20), (103, 1));
+        assert_eq!(walk(104, true, 25), (102, 2));
+    }
+
+    #[test]
+    fn walk_stops_at_the_last_loaded_bin() {
+        assert_eq!(walk(100, false, 1_000), (104, 4));
+        assert_eq!(walk(104, true, 1_000), (100, 4));
+        // Nothing loaded at the active id
+        assert_eq!(walk(99, false, 1_000), (99, 0));
+
+        let at_zero = walk_reserves(|_| Some(1), 0, true, 1_000);
+        assert_eq!((at_zero.end_active_id, at_zero.bins_crossed), (0, 0));
+        let at_max = walk_reserves(|_| Some(1), u32::MAX, false, 1_000);
+        assert_eq!((at_max.end_active_id, at_max.bins_crossed), (u32::MAX, 0));
+    }
+}
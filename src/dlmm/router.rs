@@ -0,0 +1,253 @@
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use jupiter_amm_interface::{QuoteParams, SwapMode};
+use solana_sdk::{
+    compute_budget::ComputeBudgetInstruction, instruction::Instruction, pubkey::Pubkey,
+};
+use spl_associated_token_account::instruction::create_associated_token_account_idempotent;
+use tracing::warn;
+
+use crate::{
+    app::AppContext,
+    dlmm::{build_swap_instruction, build_v0_transaction, DLMMClient, V0Transaction},
+    state::QuoteResponse,
+};
+
+/// A pair the router may hop through, refreshed once per routing request.
+struct RoutablePair {
+    key: Pubkey,
+    mints: [Pubkey; 2],
+    client: Arc<DLMMClient>,
+}
+
+impl RoutablePair {
+    fn other_mint(&self, mint: Pubkey) -> Option<Pubkey> {
+        match self.mints {
+            [x, y] if x == mint => Some(y),
+            [x, y] if y == mint => Some(x),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct RouteHop {
+    pub pair: Pubkey,
+    pub input_mint: Pubkey,
+    pub output_mint: Pubkey,
+    pub quote: QuoteResponse,
+    client: Arc<DLMMClient>,
+}
+
+pub struct Route {
+    pub in_amount: u64,
+    pub out_amount: u64,
+    pub hops: Vec<RouteHop>,
+}
+
+/// Find the best 1- or 2-hop ExactIn route from `input_mint` to `output_mint`
+/// through the given Saros pairs.
+pub async fn find_best_route(
+    ctx: &AppContext,
+    pairs: &[Pubkey],
+    input_mint: Pubkey,
+    output_mint: Pubkey,
+    amount: u64,
+) -> Result<Route> {
+    let mut routable = Vec::with_capacity(pairs.len());
+    for pair_key in pairs {
+        if routable.iter().any(|p: &RoutablePair| p.key == *pair_key) {
+            continue;
+        }
+
+        let client = match ctx.get_or_spawn_client(*pair_key).await {
+            Ok(client) => client,
+            Err(e) => {
+                warn!("⚠️ Skipping pair {} for routing: {}", pair_key, e);
+                continue;
+            }
+        };
+        if let Err(e) = client.refresh(ctx).await {
+            warn!("⚠️ Skipping pair {} for routing: {}", pair_key, e);
+            continue;
+        }
+
+        let mints = {
+            let saros_dlmm = client.saros_dlmm.read().await;
+            [saros_dlmm.pair.token_mint_x, saros_dlmm.pair.token_mint_y]
+        };
+
+        routable.push(RoutablePair {
+            key: *pair_key,
+            mints,
+            client,
+        });
+    }
+
+    let mut best: Option<Route> = None;
+
+    // 1 hop: direct pairs
+    for pair in routable.iter() {
+        if pair.other_mint(input_mint) != Some(output_mint) {
+            continue;
+        }
+
+        if let Ok(hop) = quote_hop(ctx, pair, input_mint, output_mint, amount).await {
+            let route = Route {
+                in_amount: amount,
+                out_amount: hop.quote.out_amount,
+                hops: vec![hop],
+            };
+            best = pick_best(best, route);
+        }
+    }
+
+    // 2 hops: input -> middle -> output
+    for first in routable.iter() {
+        let Some(middle_mint) = first.other_mint(input_mint) else {
+            continue;
+        };
+        if middle_mint == output_mint {
+            continue;
+        }
+
+        let Ok(first_hop) = quote_hop(ctx, first, input_mint, middle_mint, amount).await else {
+            continue;
+        };
+
+        for second in routable.iter() {
+            if second.key == first.key || second.other_mint(middle_mint) != Some(output_mint) {
+                continue;
+            }
+
+            let middle_amount = first_hop.quote.out_amount;
+            if let Ok(second_hop) =
+                quote_hop(ctx, second, middle_mint, output_mint, middle_amount).await
+            {
+                let route = Route {
+                    in_amount: amount,
+                    out_amount: second_hop.quote.out_amount,
+                    hops: vec![first_hop.clone(), second_hop],
+                };
+                best = pick_best(best, route);
+            }
+        }
+    }
+
+    best.ok_or_else(|| anyhow!("No route found from {} to {}", input_mint, output_mint))
+}
+
+/// Chain the swap instructions for a route, each hop spending what the
+/// previous hop guarantees after slippage.
+pub async fn build_route_instructions(
+    ctx: &AppContext,
+    route: &Route,
+    user: Pubkey,
+    slippage_bps: u16,
+) -> Result<Vec<Instruction>> {
+    let mut instructions = Vec::new();
+    let mut amount_in = route.in_amount;
+
+    for hop in route.hops.iter() {
+        let quote = hop
+            .client
+            .quote(
+                ctx,
+                &QuoteParams {
+                    amount: amount_in,
+                    input_mint: hop.input_mint,
+                    output_mint: hop.output_mint,
+                    swap_mode: SwapMode::ExactIn,
+                },
+            )
+            .await?;
+        let min_out_amount = apply_slippage(quote.out_amount, slippage_bps);
+
+        let saros_dlmm = hop.client.saros_dlmm.read().await;
+
+        // Make sure the receiving token account exists before the swap lands in it
+        let output_index = if hop.output_mint == saros_dlmm.pair.token_mint_x {
+            0
+        } else {
+            1
+        };
+        instructions.push(create_associated_token_account_idempotent(
+            &user,
+            &user,
+            &hop.output_mint,
+            &saros_dlmm.token_program[output_index],
+        ));
+
+        instructions.push(build_swap_instruction(
+            &saros_dlmm,
+            ctx.config.hook_program_id,
+            user,
+            hop.input_mint,
+            amount_in,
+            min_out_amount,
+        )?);
+
+        amount_in = min_out_amount;
+    }
+
+    Ok(instructions)
+}
+
+/// The route's swaps behind a compute budget, compiled into a v0 transaction
+/// for `user` to sign. Returns the instructions alongside it.
+pub async fn build_route_transaction(
+    ctx: &AppContext,
+    route: &Route,
+    user: Pubkey,
+    slippage_bps: u16,
+) -> Result<(Vec<Instruction>, V0Transaction)> {
+    let mut instructions = vec![
+        ComputeBudgetInstruction::set_compute_unit_limit(1_400_000),
+        ComputeBudgetInstruction::set_compute_unit_price(1),
+    ];
+    instructions.extend(build_route_instructions(ctx, route, user, slippage_bps).await?);
+
+    let transaction = build_v0_transaction(ctx, user, &instructions).await?;
+    Ok((instructions, transaction))
+}
+
+pub fn apply_slippage(amount: u64, slippage_bps: u16) -> u64 {
+    (amount as u128 * (10_000 - slippage_bps.min(10_000)) as u128 / 10_000) as u64
+}
+
+async fn quote_hop(
+    ctx: &AppContext,
+    pair: &RoutablePair,
+    input_mint: Pubkey,
+    output_mint: Pubkey,
+    amount: u64,
+) -> Result<RouteHop> {
+    let quote = pair
+        .client
+        .quote(
+            ctx,
+            &QuoteParams {
+                amount,
+                input_mint,
+                output_mint,
+                swap_mode: SwapMode::ExactIn,
+            },
+        )
+        .await?;
+
+    Ok(RouteHop {
+        pair: pair.key,
+        input_mint,
+        output_mint,
+        quote,
+        client: pair.client.clone(),
+    })
+}
+
+fn pick_best(best: Option<Route>, candidate: Route) -> Option<Route> {
+    match best {
+        Some(best) if best.out_amount >= candidate.out_amount => Some(best),
+        _ => Some(candidate),
+    }
+}
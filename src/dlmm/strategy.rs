@@ -0,0 +1,418 @@
+use anchor_lang::AnchorSerialize;
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::dlmm::{get_id_from_price, get_price_from_id};
+
+/// Each side's distribution sums to this
+pub const BASIS_POINT_MAX: u16 = 10_000;
+/// Bins a single position can hold
+pub const MAX_POSITION_BINS: u32 = 64;
+
+/// How liquidity is weighted across the range
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StrategyShape {
+    /// Same weight in every bin
+    #[default]
+    #[serde(rename = "spot", alias = "uniform")]
+    Spot,
+    /// Concentrated around the active bin, falling off like a normal curve
+    #[serde(rename = "curve", alias = "normal")]
+    Curve,
+    /// Thin at the active bin, growing towards the edges of the range
+    #[serde(rename = "bid_ask", alias = "inverse")]
+    BidAsk,
+}
+
+impl StrategyShape {
+    /// Weight of the bin `offset` bins from the active one, for a range
+    /// reaching `spread` bins out on its wider side
+    fn weight(&self, offset: i32, spread: u32) -> f64 {
+        let distance = offset.unsigned_abs() as f64;
+        match self {
+            StrategyShape::Spot => 1.0,
+            StrategyShape::Curve => {
+                let sigma = (spread as f64 / 2.0).max(1.0);
+                (-(distance / sigma).powi(2) / 2.0).exp()
+            }
+            StrategyShape::BidAsk => distance + 1.0,
+        }
+    }
+}
+
+/// Which tokens the deposit brings
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DepositSide {
+    #[default]
+    #[serde(rename = "both")]
+    Both,
+    /// X only, into the active bin and above
+    #[serde(rename = "x", alias = "x_only")]
+    X,
+    /// Y only, into the active bin and below
+    #[serde(rename = "y", alias = "y_only")]
+    Y,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct StrategyParams {
+    pub shape: StrategyShape,
+    pub side: DepositSide,
+    /// First bin of the range relative to the active id, inclusive
+    pub min_bin_offset: i32,
+    /// Last bin of the range relative to the active id, inclusive
+    pub max_bin_offset: i32,
+}
+
+impl StrategyParams {
+    /// A range bounded by raw prices (Y per X) instead of offsets
+    pub fn offsets_from_prices(
+        bin_step: u8,
+        active_id: u32,
+        lower_price: f64,
+        upper_price: f64,
+    ) -> Result<(i32, i32)> {
+        if !(lower_price > 0.0 && upper_price >= lower_price) {
+            bail!("Invalid price range [{}, {}]", lower_price, upper_price);
+        }
+
+        let offset = |price: f64| get_id_from_price(bin_step, price) as i64 - active_id as i64;
+        Ok((
+            offset(lower_price).clamp(i32::MIN as i64, i32::MAX as i64) as i32,
+            offset(upper_price).clamp(i32::MIN as i64, i32::MAX as i64) as i32,
+        ))
+    }
+}
+
+/// One bin of an add-liquidity distribution, in the program's argument layout
+#[derive(Debug, Clone, Copy, Serialize, AnchorSerialize)]
+#[borsh(crate = "anchor_lang::prelude::borsh")]
+pub struct BinLiquidityDistribution {
+    pub relative_bin_id: i32,
+    /// Share of the X deposit, basis points
+    pub distribution_x: u16,
+    /// Share of the Y deposit, basis points
+    pub distribution_y: u16,
+}
+
+/// A bin's share of a deposit, as raw amounts
+#[derive(Debug, Clone, Serialize)]
+pub struct BinDeposit {
+    pub bin_id: u32,
+    /// Y per X in raw units
+    pub price: f64,
+    pub amount_x: u64,
+    pub amount_y: u64,
+}
+
+/// Per-bin weights for a deposit around `active_id`. Bins above the active
+/// one take X only and bins below take Y only, as the pool requires.
+pub fn generate_distribution(
+    params: &StrategyParams,
+    active_id: u32,
+) -> Result<Vec<BinLiquidityDistribution>> {
+    let StrategyParams {
+        shape,
+        side,
+        min_bin_offset,
+        max_bin_offset,
+    } = *params;
+
+    if min_bin_offset > max_bin_offset {
+        bail!(
+            "Range start {} is after its end {}",
+            min_bin_offset,
+            max_bin_offset
+        );
+    }
+    let bins = min_bin_offset.abs_diff(max_bin_offset) + 1;
+    if bins > MAX_POSITION_BINS {
+        bail!(
+            "Range spans {} bins, a position holds at most {}",
+            bins,
+            MAX_POSITION_BINS
+        );
+    }
+    if (active_id as i64 + min_bin_offset as i64) < 0
+        || (active_id as i64 + max_bin_offset as i64) > u32::MAX as i64
+    {
+        bail!("Range falls outside the valid bin ids");
+    }
+
+    let offsets: Vec<i32> = (min_bin_offset..=max_bin_offset).collect();
+    let spread = min_bin_offset
+        .unsigned_abs()
+        .max(max_bin_offset.unsigned_abs());
+
+    let side_weights = |takes: fn(i32) -> bool, enabled: bool| -> Vec<f64> {
+        offsets
+            .iter()
+            .map(|offset| {
+                if enabled && takes(*offset) {
+                    shape.weight(*offset, spread)
+                } else {
+                    0.0
+                }
+            })
+            .collect()
+    };
+    let distribution_x =
+        to_basis_points(&side_weights(|offset| offset >= 0, side != DepositSide::Y));
+    let distribution_y =
+        to_basis_points(&side_weights(|offset| offset <= 0, side != DepositSide::X));
+
+    if distribution_x
+        .iter()
+        .chain(distribution_y.iter())
+        .all(|bps| *bps == 0)
+    {
+        bail!(
+            "Range [{}, {}] has no bins for a {:?} deposit",
+            min_bin_offset,
+            max_bin_offset,
+            side
+        );
+    }
+
+    Ok(offsets
+        .into_iter()
+        .zip(distribution_x.into_iter().zip(distribution_y))
+        .map(
+            |(relative_bin_id, (distribution_x, distribution_y))| BinLiquidityDistribution {
+                relative_bin_id,
+                distribution_x,
+                distribution_y,
+            },
+        )
+        .collect())
+}
+
+/// Split `amount_x` and `amount_y` across the bins of `distribution`
+pub fn preview_deposit(
+    distribution: &[BinLiquidityDistribution],
+    active_id: u32,
+    bin_step: u8,
+    amount_x: u64,
+    amount_y: u64,
+) -> Vec<BinDeposit> {
+    let share =
+        |amount: u64, bps: u16| (amount as u128 * bps as u128 / BASIS_POINT_MAX as u128) as u64;
+
+    distribution
+        .iter()
+        .map(|bin| {
+            let bin_id = active_id.saturating_add_signed(bin.relative_bin_id);
+            BinDeposit {
+                bin_id,
+                price: get_price_from_id(bin_step, bin_id),
+                amount_x: share(amount_x, bin.distribution_x),
+                amount_y: share(amount_y, bin.distribution_y),
+            }
+        })
+        .collect()
+}
+
+/// Scale weights to basis points summing to exactly `BASIS_POINT_MAX`,
+/// handing rounding leftovers to the largest remainders. All zero stays zero.
+fn to_basis_points(weights: &[f64]) -> Vec<u16> {
+    let total: f64 = weights.iter().sum();
+    if total <= 0.0 {
+        return vec![0; weights.len()];
+    }
+
+    let exact: Vec<f64> = weights
+        .iter()
+        .map(|weight| weight / total * BASIS_POINT_MAX as f64)
+        .collect();
+    let mut bps: Vec<u16> = exact.iter().map(|share| share.floor() as u16).collect();
+
+    let assigned: u16 = bps.iter().sum();
+    let mut by_remainder: Vec<usize> = (0..exact.len()).collect();
+    by_remainder.sort_by(|a, b| {
+        (exact[*b] - exact[*b].floor()).total_cmp(&(exact[*a] - exact[*a].floor()))
+    });
+    for index in by_remainder
+        .into_iter()
+        .take(BASIS_POINT_MAX.saturating_sub(assigned) as usize)
+    {
+        bps[index] += 1;
+    }
+
+    bps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ACTIVE_ID: u32 = 8_388_608;
+
+    fn params(shape: StrategyShape, side: DepositSide, min: i32, max: i32) -> StrategyParams {
+        StrategyParams {
+            shape,
+            side,
+            min_bin_offset: min,
+            max_bin_offset: max,
+        }
+    }
+
+    fn sums(distribution: &[BinLiquidityDistribution]) -> (u32, u32) {
+        distribution.iter().fold((0, 0), |(x, y), bin| {
+            (x + bin.distribution_x as u32, y + bin.distribution_y as u32)
+        })
+    }
+
+    fn x_at(distribution: &[BinLiquidityDistribution], offset: i32) -> u16 {
+        distribution
+            .iter()
+            .find(|bin| bin.relative_bin_id == offset)
+            .unwrap()
+            .distribution_x
+    }
+
+    #[test]
+    fn basis_points_sum_to_max() {
+        for weights in [
+            vec![1.0; 3],
+            vec![1.0; 7],
+            vec![1.0; 64],
+            vec![0.1, 0.2, 0.7],
+            vec![1e-9, 1.0, 1e9],
+            vec![0.0, 2.0, 0.0, 3.0],
+        ] {
+            let bps = to_basis_points(&weights);
+            assert_eq!(bps.len(), weights.len());
+            assert_eq!(bps.iter().map(|bps| *bps as u32).sum::<u32>(), 10_000);
+        }
+
+        // Leftovers go to the largest remainders, zero weights stay empty
+        assert_eq!(to_basis_points(&[1.0; 3]), [3334, 3333, 3333]);
+        assert_eq!(to_basis_points(&[0.0, 1.0, 0.0, 1.0]), [0, 5000, 0, 5000]);
+        assert_eq!(to_basis_points(&[0.0; 4]), [0; 4]);
+        assert!(to_basis_points(&[]).is_empty());
+    }
+
+    #[test]
+    fn two_sided_ranges_split_at_the_active_bin() {
+        for shape in [
+            StrategyShape::Spot,
+            StrategyShape::Curve,
+            StrategyShape::BidAsk,
+        ] {
+            let distribution =
+                generate_distribution(&params(shape, DepositSide::Both, -5, 3), ACTIVE_ID).unwrap();
+
+            assert_eq!(distribution.len(), 9);
+            assert_eq!(sums(&distribution), (10_000, 10_000));
+            for bin in &distribution {
+                assert_eq!(bin.distribution_x > 0, bin.relative_bin_id >= 0);
+                assert_eq!(bin.distribution_y > 0, bin.relative_bin_id <= 0);
+            }
+        }
+    }
+
+    #[test]
+    fn shapes_weight_bins_by_distance() {
+        let spot = generate_distribution(
+            &params(StrategyShape::Spot, DepositSide::X, 0, 3),
+            ACTIVE_ID,
+        )
+        .unwrap();
+        assert!(spot.iter().all(|bin| bin.distribution_x == 2500));
+
+        let curve = generate_distribution(
+            &params(StrategyShape::Curve, DepositSide::Both, -4, 4),
+            ACTIVE_ID,
+        )
+        .unwrap();
+        assert!(x_at(&curve, 0) > x_at(&curve, 2));
+        assert!(x_at(&curve, 2) > x_at(&curve, 4));
+
+        let bid_ask = generate_distribution(
+            &params(StrategyShape::BidAsk, DepositSide::Both, -4, 4),
+            ACTIVE_ID,
+        )
+        .unwrap();
+        assert!(x_at(&bid_ask, 0) < x_at(&bid_ask, 2));
+        assert!(x_at(&bid_ask, 2) < x_at(&bid_ask, 4));
+    }
+
+    #[test]
+    fn one_sided_deposits_use_one_token() {
+        let x_only = generate_distribution(
+            &params(StrategyShape::Spot, DepositSide::X, -3, 3),
+            ACTIVE_ID,
+        )
+        .unwrap();
+        assert_eq!(sums(&x_only), (10_000, 0));
+        assert!(x_only
+            .iter()
+            .all(|bin| (bin.distribution_x > 0) == (bin.relative_bin_id >= 0)));
+
+        let y_only = generate_distribution(
+            &params(StrategyShape::Curve, DepositSide::Y, -3, 3),
+            ACTIVE_ID,
+        )
+        .unwrap();
+        assert_eq!(sums(&y_only), (0, 10_000));
+        assert!(y_only
+            .iter()
+            .all(|bin| (bin.distribution_y > 0) == (bin.relative_bin_id <= 0)));
+
+        // A range above the active bin can only take X
+        let above = generate_distribution(
+            &params(StrategyShape::Spot, DepositSide::Both, 2, 5),
+            ACTIVE_ID,
+        )
+        .unwrap();
+        assert_eq!(sums(&above), (10_000, 0));
+        assert!(generate_distribution(
+            &params(StrategyShape::Spot, DepositSide::Y, 2, 5),
+            ACTIVE_ID
+        )
+        .is_err());
+        assert!(generate_distribution(
+            &params(StrategyShape::Spot, DepositSide::X, -5, -1),
+            ACTIVE_ID
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn rejects_invalid_ranges() {
+        let spot = |min, max, active_id| {
+            generate_distribution(
+                &params(StrategyShape::Spot, DepositSide::Both, min, max),
+                active_id,
+            )
+        };
+
+        assert!(spot(3, -3, ACTIVE_ID).is_err());
+        assert!(spot(0, MAX_POSITION_BINS as i32, ACTIVE_ID).is_err());
+        assert!(spot(0, MAX_POSITION_BINS as i32 - 1, ACTIVE_ID).is_ok());
+        assert!(spot(-1, 1, 0).is_err());
+        assert!(spot(-1, 1, u32::MAX).is_err());
+
+        assert!(StrategyParams::offsets_from_prices(10, ACTIVE_ID, 2.0, 1.0).is_err());
+        assert!(StrategyParams::offsets_from_prices(10, ACTIVE_ID, 0.0, 1.0).is_err());
+    }
+
+    #[test]
+    fn preview_splits_amounts_without_overspending() {
+        let distribution = generate_distribution(
+            &params(StrategyShape::Curve, DepositSide::Both, -6, 6),
+            ACTIVE_ID,
+        )
+        .unwrap();
+        let deposits = preview_deposit(&distribution, ACTIVE_ID, 10, 1_000_003, 999_999);
+
+        assert_eq!(deposits.len(), distribution.len());
+        assert_eq!(deposits[6].bin_id, ACTIVE_ID);
+        assert_eq!(deposits[0].bin_id, ACTIVE_ID - 6);
+        let spent_x: u64 = deposits.iter().map(|bin| bin.amount_x).sum();
+        let spent_y: u64 = deposits.iter().map(|bin| bin.amount_y).sum();
+        // Each bin rounds down, losing less than one unit
+        assert!(spent_x <= 1_000_003 && 1_000_003 - spent_x < deposits.len() as u64);
+        assert!(spent_y <= 999_999 && 999_999 - spent_y < deposits.len() as u64);
+    }
+}
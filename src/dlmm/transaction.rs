@@ -0,0 +1,201 @@
+use std::collections::HashSet;
+
+use anyhow::{Context, Result};
+use saros_dlmm::SarosDlmm;
+use solana_sdk::{
+    address_lookup_table::{
+        instruction::{create_lookup_table, extend_lookup_table},
+        state::AddressLookupTable,
+        AddressLookupTableAccount,
+    },
+    commitment_config::CommitmentConfig,
+    instruction::Instruction,
+    message::{v0, VersionedMessage},
+    pubkey::Pubkey,
+    signature::{Keypair, Signature},
+    signer::Signer,
+    transaction::{Transaction, VersionedTransaction},
+};
+use tracing::info;
+
+use crate::{app::AppContext, dlmm::MEMO_PROGRAM_ID};
+
+/// An extend instruction must fit in one transaction alongside its signatures
+const MAX_EXTEND_ADDRESSES: usize = 20;
+
+/// An unsigned v0 transaction with every account key it loads, in balance order
+pub struct V0Transaction {
+    pub transaction: VersionedTransaction,
+    pub account_keys: Vec<Pubkey>,
+}
+
+/// Compile `instructions` into a v0 transaction using the configured lookup
+/// tables, leaving the signatures for the wallet.
+pub async fn build_v0_transaction(
+    ctx: &AppContext,
+    payer: Pubkey,
+    instructions: &[Instruction],
+) -> Result<V0Transaction> {
+    let lookup_tables = ctx.get_lookup_tables().await;
+
+    let client = ctx.rpc_client.clone();
+    let blockhash = ctx
+        .config
+        .retry
+        .run_blocking("blockhash", move || Ok(client.get_latest_blockhash()?))
+        .await?;
+
+    let message = v0::Message::try_compile(&payer, instructions, &lookup_tables, blockhash)?;
+
+    let mut account_keys = message.account_keys.clone();
+    let mut readonly = vec![];
+    for lookup in message.address_table_lookups.iter() {
+        let table = lookup_tables
+            .iter()
+            .find(|table| table.key == lookup.account_key)
+            .context("Compiled message uses an unknown lookup table")?;
+        let address = |index: &u8| table.addresses[*index as usize];
+        account_keys.extend(lookup.writable_indexes.iter().map(address));
+        readonly.extend(lookup.readonly_indexes.iter().map(address));
+    }
+    account_keys.extend(readonly);
+
+    let transaction = VersionedTransaction {
+        signatures: vec![Signature::default(); message.header.num_required_signatures as usize],
+        message: VersionedMessage::V0(message),
+    };
+
+    Ok(V0Transaction {
+        transaction,
+        account_keys,
+    })
+}
+
+/// Accounts every swap on a pair references, worth keeping in a lookup table
+pub fn pair_lookup_addresses(client: &SarosDlmm, hook_program_id: Pubkey) -> Vec<Pubkey> {
+    let mut addresses = vec![
+        client.key,
+        client.program_id,
+        client.event_authority,
+        client.pair.token_mint_x,
+        client.pair.token_mint_y,
+        client.token_vault[0],
+        client.token_vault[1],
+        client.token_program[0],
+        client.token_program[1],
+        client.hook,
+        hook_program_id,
+        MEMO_PROGRAM_ID,
+    ];
+
+    let mut seen = HashSet::new();
+    addresses.retain(|address| seen.insert(*address));
+    addresses
+}
+
+pub fn fetch_lookup_table(
+    client: &solana_client::rpc_client::RpcClient,
+    key: Pubkey,
+) -> Result<AddressLookupTableAccount> {
+    let account = client.get_account(&key)?;
+    let table = AddressLookupTable::deserialize(&account.data)
+        .with_context(|| format!("Invalid lookup table {}", key))?;
+
+    Ok(AddressLookupTableAccount {
+        key,
+        addresses: table.addresses.to_vec(),
+    })
+}
+
+/// Create a lookup table for a pair's static accounts, or add the missing
+/// ones to an existing table, returning the table address.
+pub async fn create_or_extend_lookup_table(
+    ctx: &AppContext,
+    pair: Pubkey,
+    authority: &Keypair,
+    table: Option<Pubkey>,
+) -> Result<Pubkey> {
+    let dlmm_client = ctx.get_or_spawn_client(pair).await?;
+    let addresses = {
+        let saros_dlmm = dlmm_client.saros_dlmm.read().await;
+        pair_lookup_addresses(&saros_dlmm, ctx.config.hook_program_id)
+    };
+
+    let client = ctx.rpc_client.clone();
+    let (table, existing) = match table {
+        Some(table) => {
+            let table = ctx
+                .config
+                .retry
+                .run_blocking("lookup table", move || fetch_lookup_table(&client, table))
+                .await?;
+            (table.key, table.addresses)
+        }
+        None => {
+            let recent_slot = ctx
+                .config
+                .retry
+                .run_blocking("finalized slot", move || {
+                    Ok(client.get_slot_with_commitment(CommitmentConfig::finalized())?)
+                })
+                .await?;
+            let (instruction, table) =
+                create_lookup_table(authority.pubkey(), authority.pubkey(), recent_slot);
+            let signature = send_instructions(ctx, authority, &[instruction]).await?;
+            info!("🗂️ Created lookup table {} ({})", table, signature);
+            (table, vec![])
+        }
+    };
+
+    let missing: Vec<Pubkey> = addresses
+        .into_iter()
+        .filter(|address| !existing.contains(address))
+        .collect();
+
+    for chunk in missing.chunks(MAX_EXTEND_ADDRESSES) {
+        let instruction = extend_lookup_table(
+            table,
+            authority.pubkey(),
+            Some(authority.pubkey()),
+            chunk.to_vec(),
+        );
+        let signature = send_instructions(ctx, authority, &[instruction]).await?;
+        info!(
+            "🗂️ Extended lookup table {} with {} addresses ({})",
+            table,
+            chunk.len(),
+            signature
+        );
+    }
+
+    Ok(table)
+}
+
+/// Sign once, then retry sending that same transaction, so a retry can never
+/// land the instructions twice
+async fn send_instructions(
+    ctx: &AppContext,
+    signer: &Keypair,
+    instructions: &[Instruction],
+) -> Result<Signature> {
+    let client = ctx.rpc_client.clone();
+    let blockhash = ctx
+        .config
+        .retry
+        .run_blocking("blockhash", move || Ok(client.get_latest_blockhash()?))
+        .await?;
+    let transaction = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&signer.pubkey()),
+        &[signer],
+        blockhash,
+    );
+
+    let client = ctx.rpc_client.clone();
+    ctx.config
+        .retry
+        .run_blocking("send transaction", move || {
+            Ok(client.send_and_confirm_transaction(&transaction)?)
+        })
+        .await
+}
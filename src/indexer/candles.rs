@@ -0,0 +1,96 @@
+use serde::Serialize;
+
+use crate::dlmm::get_price_from_id;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CandleInterval {
+    M1,
+    M5,
+    H1,
+    D1,
+}
+
+impl CandleInterval {
+    pub const ALL: [CandleInterval; 4] = [
+        CandleInterval::M1,
+        CandleInterval::M5,
+        CandleInterval::H1,
+        CandleInterval::D1,
+    ];
+
+    pub fn parse(interval: &str) -> Option<CandleInterval> {
+        CandleInterval::ALL
+            .into_iter()
+            .find(|candidate| candidate.as_str() == interval)
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CandleInterval::M1 => "1m",
+            CandleInterval::M5 => "5m",
+            CandleInterval::H1 => "1h",
+            CandleInterval::D1 => "1d",
+        }
+    }
+
+    pub fn seconds(&self) -> i64 {
+        match self {
+            CandleInterval::M1 => 60,
+            CandleInterval::M5 => 300,
+            CandleInterval::H1 => 3_600,
+            CandleInterval::D1 => 86_400,
+        }
+    }
+
+    /// Start of the candle containing `time` (unix seconds)
+    pub fn open_time(&self, time: i64) -> i64 {
+        time - time.rem_euclid(self.seconds())
+    }
+}
+
+/// A stored candle. Prices are kept as bin ids, which only need the pair's
+/// bin step and decimals to become prices, and order the same way.
+#[derive(Debug, Clone)]
+pub struct BinCandle {
+    pub open_time: i64,
+    pub open_bin: u32,
+    pub high_bin: u32,
+    pub low_bin: u32,
+    pub close_bin: u32,
+    /// X traded in either direction, raw units
+    pub volume_x: u64,
+    /// Y traded in either direction, raw units
+    pub volume_y: u64,
+    pub trades: u64,
+}
+
+/// A candle in UI prices (Y per X) and UI volumes
+#[derive(Debug, Clone, Serialize)]
+pub struct Candle {
+    pub open_time: i64,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub volume_x: f64,
+    pub volume_y: f64,
+    pub trades: u64,
+}
+
+impl Candle {
+    pub fn from_bins(candle: &BinCandle, bin_step: u8, decimals_x: u8, decimals_y: u8) -> Candle {
+        let scale_price = 10f64.powi(decimals_x as i32 - decimals_y as i32);
+        let price = |bin_id: u32| get_price_from_id(bin_step, bin_id) * scale_price;
+
+        Candle {
+            open_time: candle.open_time,
+            open: price(candle.open_bin),
+            high: price(candle.high_bin),
+            low: price(candle.low_bin),
+            close: price(candle.close_bin),
+            volume_x: candle.volume_x as f64 / 10f64.powi(decimals_x as i32),
+            volume_y: candle.volume_y as f64 / 10f64.powi(decimals_y as i32),
+            trades: candle.trades,
+        }
+    }
+}
@@ -0,0 +1,504 @@
+use std::{
+    collections::BTreeSet,
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use anyhow::{anyhow, Result};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::Serialize;
+use tracing::info;
+
+use crate::{
+    analytics::SwapRecord,
+    indexer::{BinCandle, CandleInterval, IndexedTransaction, LiquidityRecord},
+};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS transactions (
+    signature TEXT PRIMARY KEY,
+    pair TEXT NOT NULL,
+    slot INTEGER NOT NULL,
+    block_time INTEGER NOT NULL,
+    failed INTEGER NOT NULL,
+    instructions TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS swaps (
+    signature TEXT NOT NULL,
+    event_index INTEGER NOT NULL,
+    pair TEXT NOT NULL,
+    slot INTEGER NOT NULL,
+    block_time INTEGER NOT NULL,
+    swap_for_y INTEGER NOT NULL,
+    bin_id INTEGER NOT NULL,
+    amount_in INTEGER NOT NULL,
+    amount_out INTEGER NOT NULL,
+    fee INTEGER NOT NULL,
+    protocol_fee INTEGER NOT NULL,
+    PRIMARY KEY (signature, event_index)
+);
+CREATE INDEX IF NOT EXISTS swaps_by_pair ON swaps (pair, slot DESC, signature DESC, event_index DESC);
+CREATE INDEX IF NOT EXISTS swaps_by_time ON swaps (pair, block_time);
+CREATE TABLE IF NOT EXISTS liquidity (
+    signature TEXT NOT NULL,
+    event_index INTEGER NOT NULL,
+    pair TEXT NOT NULL,
+    position TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    slot INTEGER NOT NULL,
+    block_time INTEGER NOT NULL,
+    bin_ids TEXT NOT NULL,
+    amounts_x TEXT NOT NULL,
+    amounts_y TEXT NOT NULL,
+    PRIMARY KEY (signature, event_index)
+);
+CREATE INDEX IF NOT EXISTS liquidity_by_position ON liquidity (position, slot);
+CREATE TABLE IF NOT EXISTS candles (
+    pair TEXT NOT NULL,
+    interval TEXT NOT NULL,
+    open_time INTEGER NOT NULL,
+    open_bin INTEGER NOT NULL,
+    high_bin INTEGER NOT NULL,
+    low_bin INTEGER NOT NULL,
+    close_bin INTEGER NOT NULL,
+    volume_x INTEGER NOT NULL,
+    volume_y INTEGER NOT NULL,
+    trades INTEGER NOT NULL,
+    PRIMARY KEY (pair, interval, open_time)
+);
+CREATE TABLE IF NOT EXISTS cursors (
+    pair TEXT PRIMARY KEY,
+    newest_signature TEXT,
+    oldest_signature TEXT,
+    oldest_block_time INTEGER,
+    backfill_complete INTEGER NOT NULL DEFAULT 0
+);
+";
+
+/// How far a pair has been indexed, so a restart picks up where it stopped
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct IndexCursor {
+    /// Newest signature indexed, the next forward pass stops here
+    pub newest_signature: Option<String>,
+    /// Oldest signature indexed, backfill continues before it
+    pub oldest_signature: Option<String>,
+    pub oldest_block_time: Option<i64>,
+    pub backfill_complete: bool,
+}
+
+/// Position in `/api/pair/trades`, newest first
+#[derive(Debug, Clone)]
+pub struct TradeCursor {
+    pub slot: u64,
+    pub signature: String,
+    pub event_index: u32,
+}
+
+impl TradeCursor {
+    pub fn parse(cursor: &str) -> Option<TradeCursor> {
+        let mut parts = cursor.splitn(3, ':');
+        Some(TradeCursor {
+            slot: parts.next()?.parse().ok()?,
+            signature: parts.next()?.to_string(),
+            event_index: parts.next()?.parse().ok()?,
+        })
+    }
+
+    pub fn of(swap: &SwapRecord) -> String {
+        format!("{}:{}:{}", swap.slot, swap.signature, swap.event_index)
+    }
+}
+
+/// SQLite store for indexed transactions, swaps, liquidity events and candles
+pub struct IndexDb {
+    conn: Mutex<Connection>,
+}
+
+impl IndexDb {
+    pub fn open(path: &Path) -> Result<IndexDb> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+
+        let conn = Connection::open(path)?;
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        conn.execute_batch(SCHEMA)?;
+
+        let db = IndexDb {
+            conn: Mutex::new(conn),
+        };
+        db.rebuild_missing_candles()?;
+        Ok(db)
+    }
+
+    /// Build candles for swaps indexed before candles were stored
+    fn rebuild_missing_candles(&self) -> Result<()> {
+        let mut conn = self.conn()?;
+        let has_candles: bool =
+            conn.query_row("SELECT EXISTS (SELECT 1 FROM candles)", [], |row| {
+                row.get(0)
+            })?;
+        if has_candles {
+            return Ok(());
+        }
+
+        let tx = conn.transaction()?;
+        let swaps: Vec<(String, i64)> = tx
+            .prepare("SELECT DISTINCT pair, block_time FROM swaps")?
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<rusqlite::Result<_>>()?;
+        if swaps.is_empty() {
+            return Ok(());
+        }
+
+        for interval in CandleInterval::ALL {
+            let buckets: BTreeSet<(&str, i64)> = swaps
+                .iter()
+                .map(|(pair, block_time)| (pair.as_str(), interval.open_time(*block_time)))
+                .collect();
+            for (pair, open_time) in buckets {
+                refresh_candle(&tx, pair, interval, open_time)?;
+            }
+        }
+
+        tx.commit()?;
+        info!(
+            "🕯️ Built candles for {} indexed swap timestamps",
+            swaps.len()
+        );
+        Ok(())
+    }
+
+    /// Run `query` on a blocking thread, SQLite calls must not stall the
+    /// async workers
+    pub async fn run<T, F>(self: &Arc<Self>, query: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&IndexDb) -> Result<T> + Send + 'static,
+    {
+        let db = self.clone();
+        tokio::task::spawn_blocking(move || query(&db)).await?
+    }
+
+    fn conn(&self) -> Result<std::sync::MutexGuard<'_, Connection>> {
+        self.conn
+            .lock()
+            .map_err(|_| anyhow!("Index database lock poisoned"))
+    }
+
+    /// Store a transaction with its events, replacing any earlier copy
+    pub fn insert(&self, pair: &str, transaction: &IndexedTransaction) -> Result<()> {
+        let mut conn = self.conn()?;
+        let tx = conn.transaction()?;
+
+        tx.execute(
+            "INSERT OR REPLACE INTO transactions (signature, pair, slot, block_time, failed, instructions)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                transaction.signature,
+                pair,
+                transaction.slot,
+                transaction.block_time,
+                transaction.failed,
+                transaction.instructions.join(","),
+            ],
+        )?;
+
+        for swap in transaction.swaps.iter() {
+            tx.execute(
+                "INSERT OR REPLACE INTO swaps (signature, event_index, pair, slot, block_time, swap_for_y,
+                     bin_id, amount_in, amount_out, fee, protocol_fee)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+                params![
+                    swap.signature,
+                    swap.event_index,
+                    swap.pair,
+                    swap.slot,
+                    swap.block_time,
+                    swap.swap_for_y,
+                    swap.bin_id,
+                    swap.amount_in,
+                    swap.amount_out,
+                    swap.fee,
+                    swap.protocol_fee,
+                ],
+            )?;
+        }
+
+        for liquidity in transaction.liquidity.iter() {
+            tx.execute(
+                "INSERT OR REPLACE INTO liquidity (signature, event_index, pair, position, kind, slot,
+                     block_time, bin_ids, amounts_x, amounts_y)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+                params![
+                    liquidity.signature,
+                    liquidity.event_index,
+                    liquidity.pair,
+                    liquidity.position,
+                    liquidity.kind,
+                    liquidity.slot,
+                    liquidity.block_time,
+                    serde_json::to_string(&liquidity.bin_ids)?,
+                    serde_json::to_string(&liquidity.amounts_x)?,
+                    serde_json::to_string(&liquidity.amounts_y)?,
+                ],
+            )?;
+        }
+
+        if !transaction.swaps.is_empty() {
+            for interval in CandleInterval::ALL {
+                let open_time = interval.open_time(transaction.block_time);
+                refresh_candle(&tx, pair, interval, open_time)?;
+            }
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Candles of `pair` opening within `[from, to]`, oldest first
+    pub fn candles(
+        &self,
+        pair: &str,
+        interval: CandleInterval,
+        from: i64,
+        to: i64,
+        limit: usize,
+    ) -> Result<Vec<BinCandle>> {
+        let conn = self.conn()?;
+        let mut statement = conn.prepare(
+            "SELECT open_time, open_bin, high_bin, low_bin, close_bin, volume_x, volume_y, trades
+             FROM candles
+             WHERE pair = ?1 AND interval = ?2 AND open_time >= ?3 AND open_time <= ?4
+             ORDER BY open_time
+             LIMIT ?5",
+        )?;
+        let rows = statement.query_map(
+            params![pair, interval.as_str(), from, to, limit as i64],
+            |row| {
+                Ok(BinCandle {
+                    open_time: row.get(0)?,
+                    open_bin: row.get(1)?,
+                    high_bin: row.get(2)?,
+                    low_bin: row.get(3)?,
+                    close_bin: row.get(4)?,
+                    volume_x: row.get(5)?,
+                    volume_y: row.get(6)?,
+                    trades: row.get(7)?,
+                })
+            },
+        )?;
+
+        Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+    }
+
+    /// Liquidity events of a position, oldest first
+    pub fn position_liquidity(&self, position: &str) -> Result<Vec<LiquidityRecord>> {
+        let conn = self.conn()?;
+        let mut statement = conn.prepare(
+            "SELECT signature, event_index, slot, block_time, pair, position, kind,
+                    bin_ids, amounts_x, amounts_y
+             FROM liquidity
+             WHERE position = ?1
+             ORDER BY slot, signature, event_index",
+        )?;
+        let rows = statement.query_map(params![position], |row| {
+            Ok((
+                LiquidityRecord {
+                    signature: row.get(0)?,
+                    event_index: row.get(1)?,
+                    slot: row.get(2)?,
+                    block_time: row.get(3)?,
+                    pair: row.get(4)?,
+                    position: row.get(5)?,
+                    kind: row.get(6)?,
+                    bin_ids: vec![],
+                    amounts_x: vec![],
+                    amounts_y: vec![],
+                },
+                row.get::<_, String>(7)?,
+                row.get::<_, String>(8)?,
+                row.get::<_, String>(9)?,
+            ))
+        })?;
+
+        rows.map(|row| -> Result<LiquidityRecord> {
+            let (mut record, bin_ids, amounts_x, amounts_y) = row?;
+            record.bin_ids = serde_json::from_str(&bin_ids)?;
+            record.amounts_x = serde_json::from_str(&amounts_x)?;
+            record.amounts_y = serde_json::from_str(&amounts_y)?;
+            Ok(record)
+        })
+        .collect()
+    }
+
+    /// Bin of the last swap at or before `time`, the pair's price then
+    pub fn bin_at(&self, pair: &str, time: i64) -> Result<Option<u32>> {
+        Ok(self
+            .conn()?
+            .query_row(
+                "SELECT bin_id FROM swaps
+                 WHERE pair = ?1 AND block_time <= ?2
+                 ORDER BY slot DESC, signature DESC, event_index DESC
+                 LIMIT 1",
+                params![pair, time],
+                |row| row.get(0),
+            )
+            .optional()?)
+    }
+
+    /// Swaps of `pair` in bins `[lower_bin_id, upper_bin_id]` at or after `since`
+    pub fn swaps_in_bins_since(
+        &self,
+        pair: &str,
+        lower_bin_id: u32,
+        upper_bin_id: u32,
+        since: i64,
+    ) -> Result<Vec<SwapRecord>> {
+        let conn = self.conn()?;
+        let mut statement = conn.prepare(
+            "SELECT signature, event_index, slot, block_time, pair, swap_for_y, bin_id,
+                    amount_in, amount_out, fee, protocol_fee
+             FROM swaps
+             WHERE pair = ?1 AND block_time >= ?2 AND bin_id >= ?3 AND bin_id <= ?4
+             ORDER BY slot, signature, event_index",
+        )?;
+        let rows = statement.query_map(
+            params![pair, since, lower_bin_id, upper_bin_id],
+            swap_from_row,
+        )?;
+
+        Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+    }
+
+    pub fn cursor(&self, pair: &str) -> Result<IndexCursor> {
+        let cursor = self
+            .conn()?
+            .query_row(
+                "SELECT newest_signature, oldest_signature, oldest_block_time, backfill_complete
+                 FROM cursors WHERE pair = ?1",
+                params![pair],
+                |row| {
+                    Ok(IndexCursor {
+                        newest_signature: row.get(0)?,
+                        oldest_signature: row.get(1)?,
+                        oldest_block_time: row.get(2)?,
+                        backfill_complete: row.get(3)?,
+                    })
+                },
+            )
+            .optional()?;
+
+        Ok(cursor.unwrap_or_default())
+    }
+
+    pub fn save_cursor(&self, pair: &str, cursor: &IndexCursor) -> Result<()> {
+        self.conn()?.execute(
+            "INSERT OR REPLACE INTO cursors
+                 (pair, newest_signature, oldest_signature, oldest_block_time, backfill_complete)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                pair,
+                cursor.newest_signature,
+                cursor.oldest_signature,
+                cursor.oldest_block_time,
+                cursor.backfill_complete,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// A page of swaps, newest first, strictly older than `before`
+    pub fn trades(
+        &self,
+        pair: &str,
+        before: Option<&TradeCursor>,
+        limit: usize,
+    ) -> Result<Vec<SwapRecord>> {
+        let conn = self.conn()?;
+        let (slot, signature, event_index) = match before {
+            Some(cursor) => (
+                cursor.slot as i64,
+                cursor.signature.as_str(),
+                cursor.event_index as i64,
+            ),
+            None => (i64::MAX, "", 0),
+        };
+
+        let mut statement = conn.prepare(
+            "SELECT signature, event_index, slot, block_time, pair, swap_for_y, bin_id,
+                    amount_in, amount_out, fee, protocol_fee
+             FROM swaps
+             WHERE pair = ?1 AND (slot, signature, event_index) < (?2, ?3, ?4)
+             ORDER BY slot DESC, signature DESC, event_index DESC
+             LIMIT ?5",
+        )?;
+        let rows = statement.query_map(
+            params![pair, slot, signature, event_index, limit as i64],
+            swap_from_row,
+        )?;
+
+        Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+    }
+
+    /// Every swap of `pair` at or after `since` (unix seconds), oldest first
+    pub fn swaps_since(&self, pair: &str, since: i64) -> Result<Vec<SwapRecord>> {
+        let conn = self.conn()?;
+        let mut statement = conn.prepare(
+            "SELECT signature, event_index, slot, block_time, pair, swap_for_y, bin_id,
+                    amount_in, amount_out, fee, protocol_fee
+             FROM swaps
+             WHERE pair = ?1 AND block_time >= ?2
+             ORDER BY slot, signature, event_index",
+        )?;
+        let rows = statement.query_map(params![pair, since], swap_from_row)?;
+
+        Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+    }
+}
+
+/// Recompute one candle from the swaps in its window. Backfill inserts swaps
+/// out of order, so open and close always come from the stored swaps.
+fn refresh_candle(
+    conn: &Connection,
+    pair: &str,
+    interval: CandleInterval,
+    open_time: i64,
+) -> Result<()> {
+    let close_time = open_time + interval.seconds();
+    conn.execute(
+        "INSERT OR REPLACE INTO candles
+             (pair, interval, open_time, open_bin, high_bin, low_bin, close_bin, volume_x, volume_y, trades)
+         SELECT ?1, ?2, ?3,
+             (SELECT bin_id FROM swaps WHERE pair = ?1 AND block_time >= ?3 AND block_time < ?4
+                 ORDER BY slot, signature, event_index LIMIT 1),
+             MAX(bin_id),
+             MIN(bin_id),
+             (SELECT bin_id FROM swaps WHERE pair = ?1 AND block_time >= ?3 AND block_time < ?4
+                 ORDER BY slot DESC, signature DESC, event_index DESC LIMIT 1),
+             SUM(CASE WHEN swap_for_y THEN amount_in ELSE amount_out END),
+             SUM(CASE WHEN swap_for_y THEN amount_out ELSE amount_in END),
+             COUNT(*)
+         FROM swaps
+         WHERE pair = ?1 AND block_time >= ?3 AND block_time < ?4
+         HAVING COUNT(*) > 0",
+        params![pair, interval.as_str(), open_time, close_time],
+    )?;
+    Ok(())
+}
+
+fn swap_from_row(row: &Row) -> rusqlite::Result<SwapRecord> {
+    Ok(SwapRecord {
+        signature: row.get(0)?,
+        event_index: row.get(1)?,
+        slot: row.get(2)?,
+        block_time: row.get(3)?,
+        pair: row.get(4)?,
+        swap_for_y: row.get(5)?,
+        bin_id: row.get(6)?,
+        amount_in: row.get(7)?,
+        amount_out: row.get(8)?,
+        fee: row.get(9)?,
+        protocol_fee: row.get(10)?,
+    })
+}
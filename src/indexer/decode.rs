@@ -0,0 +1,187 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::{
+    analytics::{event_pair, transaction_events, SwapRecord},
+    dlmm::instruction_discriminator,
+    tx::EVENT_IX_TAG_LE,
+};
+
+/// Liquidity book instructions the indexer names, by Anchor method name
+const DLMM_INSTRUCTIONS: &[&str] = &[
+    "swap",
+    "create_position",
+    "increase_position",
+    "decrease_position",
+    "close_position",
+    "claim_fee",
+    "initialize_pair",
+    "initialize_bin_array",
+];
+
+/// One add/remove liquidity or fee claim event of a pair
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LiquidityRecord {
+    pub signature: String,
+    pub event_index: u32,
+    pub slot: u64,
+    pub block_time: i64,
+    pub pair: String,
+    pub position: String,
+    /// `add_liquidity`, `remove_liquidity` or `collect_fees`
+    pub kind: String,
+    pub bin_ids: Vec<u32>,
+    pub amounts_x: Vec<u64>,
+    pub amounts_y: Vec<u64>,
+}
+
+/// Everything the indexer keeps from one transaction touching a pair
+#[derive(Debug, Clone)]
+pub struct IndexedTransaction {
+    pub signature: String,
+    pub slot: u64,
+    pub block_time: i64,
+    pub failed: bool,
+    /// DLMM instructions called, top level and CPI, in order
+    pub instructions: Vec<String>,
+    pub swaps: Vec<SwapRecord>,
+    pub liquidity: Vec<LiquidityRecord>,
+}
+
+impl IndexedTransaction {
+    /// Decode a `getTransaction` result (json encoding). Pure, so recorded
+    /// fixtures decode exactly like live transactions.
+    pub fn decode(
+        signature: &str,
+        result: &Value,
+        pair: &Pubkey,
+        program_id: &Pubkey,
+    ) -> IndexedTransaction {
+        let slot = result["slot"].as_u64().unwrap_or_default();
+        let block_time = result["blockTime"].as_i64().unwrap_or_default();
+        let failed = !result["meta"]["err"].is_null();
+
+        let mut indexed = IndexedTransaction {
+            signature: signature.to_string(),
+            slot,
+            block_time,
+            failed,
+            instructions: instruction_names(result, program_id),
+            swaps: vec![],
+            liquidity: vec![],
+        };
+        if failed {
+            return indexed;
+        }
+
+        let events = transaction_events(result, program_id).events;
+        indexed.swaps = SwapRecord::from_events(signature, slot, block_time, &events, pair);
+        indexed.liquidity = events
+            .iter()
+            .enumerate()
+            .filter(|(_, event)| event_pair(event) == Some(*pair))
+            .filter_map(|(event_index, event)| {
+                let kind = event["type"].as_str()?;
+                let (bin_ids, amounts_x, amounts_y) = match kind {
+                    "add_liquidity" | "remove_liquidity" => (
+                        u32_array(&event["bin_ids"]),
+                        u64_array(&event["amounts_x"]),
+                        u64_array(&event["amounts_y"]),
+                    ),
+                    "collect_fees" => (
+                        vec![],
+                        vec![event["fee_x"].as_u64().unwrap_or_default()],
+                        vec![event["fee_y"].as_u64().unwrap_or_default()],
+                    ),
+                    _ => return None,
+                };
+
+                Some(LiquidityRecord {
+                    signature: signature.to_string(),
+                    event_index: event_index as u32,
+                    slot,
+                    block_time,
+                    pair: pair.to_string(),
+                    position: event["position"].as_str().unwrap_or_default().to_string(),
+                    kind: kind.to_string(),
+                    bin_ids,
+                    amounts_x,
+                    amounts_y,
+                })
+            })
+            .collect();
+
+        indexed
+    }
+}
+
+/// Name every instruction invoking `program_id`, matching Anchor sighashes
+fn instruction_names(result: &Value, program_id: &Pubkey) -> Vec<String> {
+    // Static keys first, then keys loaded from lookup tables
+    let account_keys: Vec<&str> = [
+        &result["transaction"]["message"]["accountKeys"],
+        &result["meta"]["loadedAddresses"]["writable"],
+        &result["meta"]["loadedAddresses"]["readonly"],
+    ]
+    .into_iter()
+    .filter_map(Value::as_array)
+    .flatten()
+    .filter_map(Value::as_str)
+    .collect();
+    let program_id = program_id.to_string();
+
+    let top_level = result["transaction"]["message"]["instructions"]
+        .as_array()
+        .into_iter()
+        .flatten();
+    let inner = result["meta"]["innerInstructions"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .flat_map(|inner| inner["instructions"].as_array().into_iter().flatten());
+
+    top_level
+        .chain(inner)
+        .filter(|instruction| {
+            instruction["programIdIndex"]
+                .as_u64()
+                .and_then(|index| account_keys.get(index as usize))
+                .is_some_and(|key| *key == program_id)
+        })
+        .filter_map(|instruction| {
+            let data = bs58::decode(instruction["data"].as_str()?)
+                .into_vec()
+                .ok()?;
+            let discriminator = data.get(..8)?;
+            // Self-CPI event logs are not instructions of their own
+            if discriminator == EVENT_IX_TAG_LE {
+                return None;
+            }
+
+            let name = DLMM_INSTRUCTIONS
+                .iter()
+                .find(|name| instruction_discriminator(name) == discriminator)
+                .map_or("unknown", |name| *name);
+            Some(name.to_string())
+        })
+        .collect()
+}
+
+fn u32_array(value: &Value) -> Vec<u32> {
+    value
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|value| value.as_u64().map(|value| value as u32))
+        .collect()
+}
+
+fn u64_array(value: &Value) -> Vec<u64> {
+    value
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_u64)
+        .collect()
+}
@@ -0,0 +1,11 @@
+mod candles;
+mod db;
+mod decode;
+mod source;
+mod worker;
+
+pub use candles::*;
+pub use db::*;
+pub use decode::*;
+pub use source::*;
+pub use worker::*;
@@ -0,0 +1,166 @@
+use std::{fs, path::PathBuf};
+
+use anyhow::{Context, Result};
+use serde_json::{json, Value};
+use solana_client::client_error::reqwest;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::{analytics::fetch_transaction, tx::rpc_call};
+
+/// One entry of `getSignaturesForAddress`, newest first
+#[derive(Debug, Clone)]
+pub struct SignatureInfo {
+    pub signature: String,
+    pub slot: u64,
+    pub block_time: Option<i64>,
+}
+
+/// Where the indexer reads signatures and transactions from
+pub trait TransactionSource {
+    /// Signatures of `address` older than `before` and newer than `until`,
+    /// newest first
+    async fn signatures(
+        &self,
+        address: &Pubkey,
+        before: Option<&str>,
+        until: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<SignatureInfo>>;
+
+    /// A `getTransaction` result (json encoding), `None` when not found
+    async fn transaction(&self, signature: &str) -> Result<Option<Value>>;
+}
+
+pub struct RpcSource {
+    http: reqwest::Client,
+    url: String,
+}
+
+impl RpcSource {
+    pub fn new(url: String) -> RpcSource {
+        RpcSource {
+            http: reqwest::Client::new(),
+            url,
+        }
+    }
+}
+
+impl TransactionSource for RpcSource {
+    async fn signatures(
+        &self,
+        address: &Pubkey,
+        before: Option<&str>,
+        until: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<SignatureInfo>> {
+        let mut config = json!({ "limit": limit, "commitment": "finalized" });
+        if let Some(before) = before {
+            config["before"] = json!(before);
+        }
+        if let Some(until) = until {
+            config["until"] = json!(until);
+        }
+
+        let result = rpc_call(
+            &self.http,
+            &self.url,
+            "getSignaturesForAddress",
+            json!([address.to_string(), config]),
+        )
+        .await?;
+
+        Ok(result
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| {
+                Some(SignatureInfo {
+                    signature: entry["signature"].as_str()?.to_string(),
+                    slot: entry["slot"].as_u64()?,
+                    block_time: entry["blockTime"].as_i64(),
+                })
+            })
+            .collect())
+    }
+
+    async fn transaction(&self, signature: &str) -> Result<Option<Value>> {
+        let result = fetch_transaction(&self.http, &self.url, signature).await?;
+        Ok((!result.is_null()).then_some(result))
+    }
+}
+
+/// Recorded `getTransaction` results, one `<signature>.json` per file, served
+/// as if every transaction touched the requested address
+pub struct FixtureSource {
+    transactions: Vec<(SignatureInfo, Value)>,
+}
+
+impl FixtureSource {
+    pub fn load(dir: impl Into<PathBuf>) -> Result<FixtureSource> {
+        let dir = dir.into();
+        let mut transactions = vec![];
+
+        let entries =
+            fs::read_dir(&dir).with_context(|| format!("Cannot read {}", dir.display()))?;
+        for entry in entries {
+            let path = entry?.path();
+            if path.extension().is_none_or(|extension| extension != "json") {
+                continue;
+            }
+
+            let signature = path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .context("Fixture file name is not a signature")?
+                .to_string();
+            let result: Value = serde_json::from_slice(&fs::read(&path)?)
+                .with_context(|| format!("Invalid fixture {}", path.display()))?;
+
+            let info = SignatureInfo {
+                signature,
+                slot: result["slot"].as_u64().unwrap_or_default(),
+                block_time: result["blockTime"].as_i64(),
+            };
+            transactions.push((info, result));
+        }
+
+        // Newest first, like getSignaturesForAddress
+        transactions.sort_by(|(a, _), (b, _)| (b.slot, &b.signature).cmp(&(a.slot, &a.signature)));
+        Ok(FixtureSource { transactions })
+    }
+}
+
+impl TransactionSource for FixtureSource {
+    async fn signatures(
+        &self,
+        _address: &Pubkey,
+        before: Option<&str>,
+        until: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<SignatureInfo>> {
+        let start = match before {
+            Some(before) => self
+                .transactions
+                .iter()
+                .position(|(info, _)| info.signature == before)
+                .map_or(self.transactions.len(), |index| index + 1),
+            None => 0,
+        };
+
+        Ok(self.transactions[start..]
+            .iter()
+            .map(|(info, _)| info)
+            .take_while(|info| until != Some(info.signature.as_str()))
+            .take(limit)
+            .cloned()
+            .collect())
+    }
+
+    async fn transaction(&self, signature: &str) -> Result<Option<Value>> {
+        Ok(self
+            .transactions
+            .iter()
+            .find(|(info, _)| info.signature == signature)
+            .map(|(_, result)| result.clone()))
+    }
+}
@@ -0,0 +1,319 @@
+use std::{
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::Result;
+use solana_sdk::pubkey::Pubkey;
+use tracing::{info, warn};
+
+use crate::indexer::{
+    IndexCursor, IndexDb, IndexedTransaction, RpcSource, SignatureInfo, TransactionSource,
+};
+
+/// Signatures requested per page
+const PAGE_SIZE: usize = 100;
+/// Pages of history walked per pass until backfill completes
+const BACKFILL_PAGES_PER_PASS: usize = 5;
+
+/// Counts from one indexing pass over a pair
+#[derive(Debug, Default)]
+pub struct IndexReport {
+    pub transactions: usize,
+    pub swaps: usize,
+    pub liquidity: usize,
+}
+
+/// Walks a pair's signatures into the index: forward from the newest indexed
+/// signature, then a few backfill pages further into history per pass.
+pub struct Indexer<S> {
+    db: Arc<IndexDb>,
+    source: S,
+    program_id: Pubkey,
+    /// Backfill stops at transactions older than this, `None` walks all history
+    backfill: Option<Duration>,
+}
+
+impl<S: TransactionSource> Indexer<S> {
+    pub fn new(
+        db: Arc<IndexDb>,
+        source: S,
+        program_id: Pubkey,
+        backfill: Option<Duration>,
+    ) -> Indexer<S> {
+        Indexer {
+            db,
+            source,
+            program_id,
+            backfill,
+        }
+    }
+
+    pub async fn index_pair(&self, pair: &Pubkey) -> Result<IndexReport> {
+        let key = pair.to_string();
+        let mut cursor = {
+            let key = key.clone();
+            self.db.run(move |db| db.cursor(&key)).await?
+        };
+        let mut report = IndexReport::default();
+
+        // Forward: everything newer than the last pass, stored oldest first so
+        // an interrupted pass resumes from the last stored signature
+        let mut pending: Vec<SignatureInfo> = vec![];
+        loop {
+            let before = pending.last().map(|info| info.signature.as_str());
+            let page = self
+                .source
+                .signatures(pair, before, cursor.newest_signature.as_deref(), PAGE_SIZE)
+                .await?;
+            let done = page.len() < PAGE_SIZE || cursor.newest_signature.is_none();
+            pending.extend(page);
+            if done {
+                break;
+            }
+        }
+
+        if cursor.oldest_signature.is_none() {
+            if let Some(oldest) = pending.last() {
+                cursor.oldest_signature = Some(oldest.signature.clone());
+                cursor.oldest_block_time = oldest.block_time;
+            }
+        }
+
+        for info in pending.iter().rev() {
+            self.index_transaction(pair, info, &mut report).await?;
+            cursor.newest_signature = Some(info.signature.clone());
+            self.save_cursor(&key, &cursor).await?;
+        }
+
+        // Backfill: a few pages further back than anything indexed so far, so
+        // a newly watched pair catches up on history over its first passes
+        let backfill_until = self.backfill.and_then(|backfill| {
+            let now = SystemTime::now().duration_since(UNIX_EPOCH).ok()?;
+            Some(now.saturating_sub(backfill).as_secs() as i64)
+        });
+        for _ in 0..BACKFILL_PAGES_PER_PASS {
+            let Some(oldest) = cursor.oldest_signature.clone() else {
+                break;
+            };
+            if cursor.backfill_complete {
+                break;
+            }
+
+            let page = self
+                .source
+                .signatures(pair, Some(&oldest), None, PAGE_SIZE)
+                .await?;
+            if page.len() < PAGE_SIZE {
+                cursor.backfill_complete = true;
+            }
+
+            for info in page.iter() {
+                let expired = backfill_until
+                    .zip(info.block_time)
+                    .is_some_and(|(until, block_time)| block_time < until);
+                if expired {
+                    cursor.backfill_complete = true;
+                    break;
+                }
+
+                self.index_transaction(pair, info, &mut report).await?;
+                cursor.oldest_signature = Some(info.signature.clone());
+                cursor.oldest_block_time = info.block_time;
+                self.save_cursor(&key, &cursor).await?;
+            }
+            self.save_cursor(&key, &cursor).await?;
+        }
+
+        Ok(report)
+    }
+
+    async fn index_transaction(
+        &self,
+        pair: &Pubkey,
+        info: &SignatureInfo,
+        report: &mut IndexReport,
+    ) -> Result<()> {
+        let Some(result) = self.source.transaction(&info.signature).await? else {
+            warn!("⚠️ Transaction {} not found, skipping", info.signature);
+            return Ok(());
+        };
+
+        let transaction =
+            IndexedTransaction::decode(&info.signature, &result, pair, &self.program_id);
+        report.transactions += 1;
+        report.swaps += transaction.swaps.len();
+        report.liquidity += transaction.liquidity.len();
+
+        let pair = pair.to_string();
+        self.db.run(move |db| db.insert(&pair, &transaction)).await
+    }
+
+    async fn save_cursor(&self, key: &str, cursor: &IndexCursor) -> Result<()> {
+        let key = key.to_string();
+        let cursor = cursor.clone();
+        self.db.run(move |db| db.save_cursor(&key, &cursor)).await
+    }
+}
+
+impl Indexer<RpcSource> {
+    /// Index every pair in `pairs` each `interval`
+    pub fn spawn(self, pairs: Vec<Pubkey>, interval: Duration) {
+        if pairs.is_empty() {
+            return;
+        }
+
+        info!("🗃️ Indexing {} pairs every {:?}", pairs.len(), interval);
+
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                for pair in pairs.iter() {
+                    match self.index_pair(pair).await {
+                        Ok(report) if report.transactions > 0 => info!(
+                            "🗃️ Indexed {} transactions for {} ({} swaps, {} liquidity events)",
+                            report.transactions, pair, report.swaps, report.liquidity
+                        ),
+                        Ok(_) => {}
+                        Err(e) => warn!("⚠️ Failed to index {}: {}", pair, e),
+                    }
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+    use crate::indexer::FixtureSource;
+
+    const PROGRAM_ID: Pubkey =
+        Pubkey::from_str_const("1qbkdrr3z4ryLA7pZykqxvxWPoeifcVKo6ZG9CfkvVE");
+    const PAIR: Pubkey = Pubkey::from_str_const("5qTdeoe4C7p1yoBLnNstZGiAUYj57uxKVuJewxSwF1rM");
+
+    /// Oldest to newest: a deposit, a swap emitted by CPI, then a logged swap
+    /// next to events forged by another program
+    const DEPOSIT: &str =
+        "5viE6tdTyzJod81b5XHuMm7dKkRQV2vp4wLAFzuL3VNtY3fX6DEMWEKEohabhp7HWYZ6eTfzEW7hs3AXDXT7pYrc";
+    const SWAP_CPI: &str =
+        "2eJ9CRDA82AvRpZ19hVuFDvP3nVhTpS4PViHWYiJntiywvKhPNdgfrmFfeSHVNopK4vTwb2FRtKCufbrkLPiPews";
+    const SWAP_LOG: &str =
+        "i2FJj24neqU7npq2UphA8MHcmExzFJZEVx1UodSuEGmJEsKiSB47G7BXTTysHoWTieXDFgRKxNDxh4G8RQoxxGc";
+
+    fn indexer(backfill: Option<Duration>) -> Indexer<FixtureSource> {
+        let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/indexer");
+        Indexer::new(
+            Arc::new(IndexDb::open(Path::new(":memory:")).unwrap()),
+            FixtureSource::load(fixtures).unwrap(),
+            PROGRAM_ID,
+            backfill,
+        )
+    }
+
+    #[tokio::test]
+    async fn indexes_fixture_transactions() {
+        let indexer = indexer(None);
+        let report = indexer.index_pair(&PAIR).await.unwrap();
+
+        assert_eq!(report.transactions, 3);
+        assert_eq!(report.swaps, 2);
+        assert_eq!(report.liquidity, 1);
+
+        let pair = PAIR.to_string();
+        let trades = indexer.db.trades(&pair, None, 10).unwrap();
+        let signatures: Vec<&str> = trades.iter().map(|swap| swap.signature.as_str()).collect();
+        assert_eq!(signatures, [SWAP_LOG, SWAP_CPI]);
+        // The forged events of the other program are not counted
+        assert!(!trades[0].swap_for_y);
+        assert_eq!(trades[0].amount_in, 2_000_000);
+        assert_eq!(trades[0].fee, 2_000);
+        assert!(trades[1].swap_for_y);
+        assert_eq!(trades[1].amount_out, 995_000);
+        assert_eq!(trades[1].protocol_fee, 600);
+
+        let deposits = indexer
+            .db
+            .position_liquidity("75NqraLLofiLUpNQg2wUPG6CJpxcaGEZUwuNrnAgvcTP")
+            .unwrap();
+        assert_eq!(deposits.len(), 1);
+        assert_eq!(deposits[0].signature, DEPOSIT);
+        assert_eq!(deposits[0].kind, "add_liquidity");
+        assert_eq!(deposits[0].bin_ids, [8388607, 8388608, 8388609]);
+
+        let cursor = indexer.db.cursor(&pair).unwrap();
+        assert_eq!(cursor.newest_signature.as_deref(), Some(SWAP_LOG));
+        assert_eq!(cursor.oldest_signature.as_deref(), Some(DEPOSIT));
+        assert!(cursor.backfill_complete);
+    }
+
+    #[tokio::test]
+    async fn resumes_from_saved_cursor() {
+        let indexer = indexer(None);
+        let pair = PAIR.to_string();
+        indexer
+            .db
+            .save_cursor(
+                &pair,
+                &IndexCursor {
+                    newest_signature: Some(SWAP_CPI.to_string()),
+                    oldest_signature: Some(DEPOSIT.to_string()),
+                    oldest_block_time: Some(1_700_000_000),
+                    backfill_complete: true,
+                },
+            )
+            .unwrap();
+
+        let report = indexer.index_pair(&PAIR).await.unwrap();
+        assert_eq!(report.transactions, 1);
+        assert_eq!(report.swaps, 1);
+        assert_eq!(
+            indexer
+                .db
+                .cursor(&pair)
+                .unwrap()
+                .newest_signature
+                .as_deref(),
+            Some(SWAP_LOG)
+        );
+
+        // Nothing new on the next pass
+        let report = indexer.index_pair(&PAIR).await.unwrap();
+        assert_eq!(report.transactions, 0);
+    }
+
+    #[tokio::test]
+    async fn backfill_stops_at_window() {
+        // Reach back to the CPI swap, with a minute to spare, but not the
+        // deposit ten minutes before it
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let indexer = indexer(Some(Duration::from_secs(now - 1_700_000_600 + 60)));
+        let pair = PAIR.to_string();
+        indexer
+            .db
+            .save_cursor(
+                &pair,
+                &IndexCursor {
+                    newest_signature: Some(SWAP_LOG.to_string()),
+                    oldest_signature: Some(SWAP_LOG.to_string()),
+                    oldest_block_time: Some(1_700_001_200),
+                    backfill_complete: false,
+                },
+            )
+            .unwrap();
+
+        let report = indexer.index_pair(&PAIR).await.unwrap();
+        assert_eq!(report.transactions, 1);
+        assert_eq!(report.liquidity, 0);
+
+        let cursor = indexer.db.cursor(&pair).unwrap();
+        assert_eq!(cursor.oldest_signature.as_deref(), Some(SWAP_CPI));
+        assert!(cursor.backfill_complete);
+    }
+}
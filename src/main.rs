@@ -1,15 +1,23 @@
+mod analytics;
 mod app;
+mod cache;
 mod cli;
 mod dlmm;
+mod indexer;
+mod retry;
 mod state;
+mod store;
+mod tx;
+mod warmer;
 mod web;
 
-use std::time::Duration;
+use std::{str::FromStr, time::Duration};
 
+use base64::{engine::general_purpose, Engine as _};
 use clap::Parser;
-use cli::{Cli, Commands};
-use solana_sdk::pubkey::Pubkey;
-use tracing::info;
+use cli::{Cli, Commands, PairsCommands};
+use solana_sdk::{pubkey::Pubkey, signature::read_keypair_file};
+use tracing::{info, warn};
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
@@ -23,53 +31,463 @@ async fn main() -> anyhow::Result<()> {
     match cli.command {
         Commands::Start { web } => {
             info!("🚀 Starting Saros DLMM Interface...");
-            let mut config = app::AppConfig::default();
+            let config = load_config();
 
-            if let Ok(rpc_url) = dotenv::var("RPC_URL") {
-                info!("Using RPC URL from .env: {}", rpc_url);
-                config.rpc_url = rpc_url;
+            if web {
+                web::start_web_server(config).await?;
+            } else {
+                info!("Running in CLI-only mode...");
             }
+        }
+        Commands::Route {
+            input_mint,
+            output_mint,
+            amount,
+            pairs,
+            signer,
+            slippage_bps,
+        } => {
+            let ctx = app::AppContext::new(load_config());
+            let pairs = pairs
+                .iter()
+                .map(|pair| Pubkey::from_str(pair))
+                .collect::<Result<Vec<_>, _>>()?;
+
+            let route = dlmm::find_best_route(
+                &ctx,
+                &pairs,
+                Pubkey::from_str(&input_mint)?,
+                Pubkey::from_str(&output_mint)?,
+                amount,
+            )
+            .await?;
 
-            if let Ok(lb_program_id) = dotenv::var("LB_PROGRAM_ID") {
+            info!(
+                "💱 Best route: in_amount={}, out_amount={}, hops={}",
+                route.in_amount,
+                route.out_amount,
+                route.hops.len()
+            );
+            for hop in route.hops.iter() {
                 info!(
-                    "Using Liquidity Bootstrapping Program ID from .env: {}",
-                    lb_program_id
+                    "  ↪ pair={} {} -> {} in={} out={} impact={:.4}%",
+                    hop.pair,
+                    hop.input_mint,
+                    hop.output_mint,
+                    hop.quote.in_amount,
+                    hop.quote.out_amount,
+                    hop.quote.price_impact_pct
                 );
-                config.lb_program_id = Pubkey::from_str_const(&lb_program_id);
             }
 
-            if let Ok(hook_program_id) = dotenv::var("HOOK_PROGRAM_ID") {
-                info!("Using Hook Program ID from .env: {}", hook_program_id);
-                config.hook_program_id = Pubkey::from_str_const(&hook_program_id);
+            if let Some(signer) = signer {
+                let user = Pubkey::from_str(&signer)?;
+                let (_, tx) =
+                    dlmm::build_route_transaction(&ctx, &route, user, slippage_bps).await?;
+                info!(
+                    "🧾 Unsigned transaction (min_out_amount={}): {}",
+                    dlmm::apply_slippage(route.out_amount, slippage_bps),
+                    general_purpose::STANDARD.encode(bincode::serialize(&tx.transaction)?)
+                );
             }
+        }
+        Commands::Pair { address } => {
+            let ctx = app::AppContext::new(load_config());
+            let stats = ctx.get_pair_stats(Pubkey::from_str(&address)?).await?;
+            let usd = |value: Option<f64>| {
+                value
+                    .map(|value| format!("${:.2}", value))
+                    .unwrap_or_else(|| "n/a".to_string())
+            };
 
-            if let Ok(pool_ttl_secs) = dotenv::var("POOL_CACHE_TTL_SECS") {
-                if let Ok(pool_ttl) = pool_ttl_secs.parse::<u64>() {
-                    info!("Using Pool Cache TTL from .env: {} seconds", pool_ttl);
-                    config.cache_ttl.pool_ttl = Duration::from_secs(pool_ttl);
-                }
+            info!(
+                "📈 Pair {} active_id={} price={:.8}",
+                stats.pair, stats.active_id, stats.price
+            );
+            info!(
+                "  reserves x={} y={} tvl={}",
+                stats.reserve_x,
+                stats.reserve_y,
+                usd(stats.tvl_usd)
+            );
+            info!(
+                "  24h swaps={} volume={} fees={} apr={}",
+                stats.swaps_24h,
+                usd(stats.volume_24h_usd),
+                usd(stats.fees_24h_usd),
+                stats
+                    .fee_apr_pct
+                    .map(|apr| format!("{:.2}%", apr))
+                    .unwrap_or_else(|| "n/a".to_string())
+            );
+            for range in stats.ranges.iter() {
+                info!(
+                    "  ±{} bins [{}..{}] liquidity={} fees={} apr={}",
+                    range.range,
+                    range.lower_bin_id,
+                    range.upper_bin_id,
+                    usd(range.liquidity_usd),
+                    usd(range.fees_24h_usd),
+                    range
+                        .fee_apr_pct
+                        .map(|apr| format!("{:.2}%", apr))
+                        .unwrap_or_else(|| "n/a".to_string())
+                );
             }
-
-            if let Ok(token_ttl_secs) = dotenv::var("TOKEN_CACHE_TTL_SECS") {
-                if let Ok(token_ttl) = token_ttl_secs.parse::<u64>() {
-                    info!("Using Token Cache TTL from .env: {} seconds", token_ttl);
-                    config.cache_ttl.token_ttl = Duration::from_secs(token_ttl);
-                }
+            if !stats.history_complete {
+                warn!("Swap history was truncated, 24h volume is a lower bound");
             }
+        }
+        Commands::Pnl {
+            position,
+            deposit_x,
+            deposit_y,
+            deposit_price,
+        } => {
+            let ctx = app::AppContext::new(load_config());
+            let input = analytics::DepositInput {
+                deposit_x,
+                deposit_y,
+                deposit_price,
+            };
+            let pnl =
+                analytics::compute_position_pnl(&ctx, Pubkey::from_str(&position)?, &input).await?;
+
+            info!(
+                "📊 Position {} on {} bins [{}..{}]",
+                pnl.position, pnl.pair, pnl.lower_bin_id, pnl.upper_bin_id
+            );
+            info!(
+                "  price={:.8} deposit_price={:.8} ({})",
+                pnl.price, pnl.deposit_price, pnl.deposit_source
+            );
+            info!(
+                "  holding x={} y={}, deposited x={} y={}",
+                pnl.amount_x, pnl.amount_y, pnl.deposit_x, pnl.deposit_y
+            );
+            let values = &pnl.values;
+            info!(
+                "  value={:.6} hodl={:.6} deposit={:.6} (in Y)",
+                values.current_value, values.hodl_value, values.deposit_value
+            );
+            info!(
+                "  impermanent_loss={:.6} ({:.2}%) fees={:.6}, unclaimed x={} y={} (estimated)",
+                values.impermanent_loss,
+                values.impermanent_loss_pct,
+                values.fees_value,
+                pnl.fees_unclaimed_estimate_x,
+                pnl.fees_unclaimed_estimate_y
+            );
+            info!(
+                "  net_pnl={:.6} ({:.2}%) usd={}",
+                values.net_pnl,
+                values.net_pnl_pct,
+                pnl.net_pnl_usd
+                    .map(|value| format!("${:.2}", value))
+                    .unwrap_or_else(|| "n/a".to_string())
+            );
+        }
+        Commands::Index { pair, fixtures } => {
+            let config = load_config();
+            let program_id = config.lb_program_id;
+            let backfill = config.index_backfill;
+            let ctx = app::AppContext::new(config);
+            let db = ctx
+                .index_db
+                .clone()
+                .ok_or_else(|| anyhow::anyhow!("INDEX_DB_PATH is disabled"))?;
+            let pair = Pubkey::from_str(&pair)?;
 
-            if let Ok(bin_ttl_secs) = dotenv::var("BIN_CACHE_TTL_SECS") {
-                if let Ok(bin_ttl) = bin_ttl_secs.parse::<u64>() {
-                    info!("Using Bin Cache TTL from .env: {} seconds", bin_ttl);
-                    config.cache_ttl.bin_ttl = Duration::from_secs(bin_ttl);
+            let report = match fixtures {
+                Some(dir) => {
+                    let source = indexer::FixtureSource::load(dir)?;
+                    indexer::Indexer::new(db, source, program_id, backfill)
+                        .index_pair(&pair)
+                        .await?
                 }
+                None => {
+                    let source = indexer::RpcSource::new(ctx.rpc_client.url());
+                    indexer::Indexer::new(db, source, program_id, backfill)
+                        .index_pair(&pair)
+                        .await?
+                }
+            };
+
+            info!(
+                "🗃️ Indexed {} transactions for {} ({} swaps, {} liquidity events)",
+                report.transactions, pair, report.swaps, report.liquidity
+            );
+        }
+        Commands::Pairs {
+            command:
+                PairsCommands::Search {
+                    mint,
+                    symbol,
+                    bin_step,
+                },
+        } => {
+            let ctx = app::AppContext::new(load_config());
+            let query = state::PairSearch {
+                mint: mint.as_deref().map(Pubkey::from_str).transpose()?,
+                symbol,
+                bin_step,
+            };
+
+            let count = ctx.refresh_pair_index().await?;
+            info!("📇 Indexed {} pairs", count);
+
+            for entry in ctx.pair_index.read().await.search(&query) {
+                info!(
+                    "  {} {}/{} bin_step={} active_id={}",
+                    entry.pair,
+                    entry.symbol_x.as_deref().unwrap_or("?"),
+                    entry.symbol_y.as_deref().unwrap_or("?"),
+                    entry.bin_step,
+                    entry.active_id
+                );
             }
-            if web {
-                web::start_web_server(config).await?;
-            } else {
-                info!("Running in CLI-only mode...");
-            }
+        }
+        Commands::Lut {
+            pair,
+            keypair,
+            table,
+        } => {
+            let ctx = app::AppContext::new(load_config());
+            let authority = read_keypair_file(&keypair)
+                .map_err(|e| anyhow::anyhow!("Failed to read keypair {}: {}", keypair, e))?;
+
+            let table = dlmm::create_or_extend_lookup_table(
+                &ctx,
+                Pubkey::from_str(&pair)?,
+                &authority,
+                table.as_deref().map(Pubkey::from_str).transpose()?,
+            )
+            .await?;
+
+            info!("🗂️ Lookup table for {}: {}", pair, table);
         }
     }
 
     Ok(())
 }
+
+/// Build the app config from defaults overridden by `.env`
+fn load_config() -> app::AppConfig {
+    let mut config = app::AppConfig::default();
+
+    if let Ok(rpc_url) = dotenv::var("RPC_URL") {
+        info!("Using RPC URL from .env: {}", rpc_url);
+        config.rpc_url = rpc_url;
+    }
+
+    if let Ok(max_attempts) = dotenv::var("RPC_MAX_ATTEMPTS") {
+        if let Ok(max_attempts) = max_attempts.parse::<u32>() {
+            info!("Using RPC max attempts from .env: {}", max_attempts);
+            config.retry.max_attempts = max_attempts;
+        }
+    }
+
+    if let Ok(backoff_ms) = dotenv::var("RPC_BACKOFF_MS") {
+        if let Ok(backoff_ms) = backoff_ms.parse::<u64>() {
+            info!("Using RPC backoff from .env: {} ms", backoff_ms);
+            config.retry.base_delay = Duration::from_millis(backoff_ms);
+        }
+    }
+
+    if let Ok(max_backoff_ms) = dotenv::var("RPC_MAX_BACKOFF_MS") {
+        if let Ok(max_backoff_ms) = max_backoff_ms.parse::<u64>() {
+            info!("Using RPC max backoff from .env: {} ms", max_backoff_ms);
+            config.retry.max_delay = Duration::from_millis(max_backoff_ms);
+        }
+    }
+
+    if let Ok(timeout_ms) = dotenv::var("RPC_TIMEOUT_MS") {
+        if let Ok(timeout_ms) = timeout_ms.parse::<u64>() {
+            info!("Using RPC timeout from .env: {} ms", timeout_ms);
+            config.retry.timeout = Duration::from_millis(timeout_ms);
+        }
+    }
+
+    if let Ok(send_attempts) = dotenv::var("SEND_ATTEMPTS") {
+        if let Ok(send_attempts) = send_attempts.parse::<u32>() {
+            info!("Using send attempts from .env: {}", send_attempts);
+            config.send.attempts = send_attempts;
+        }
+    }
+
+    if let Ok(max_retries) = dotenv::var("SEND_MAX_RETRIES") {
+        if let Ok(max_retries) = max_retries.parse::<usize>() {
+            info!("Using send maxRetries from .env: {}", max_retries);
+            config.send.max_retries = Some(max_retries);
+        }
+    }
+
+    if let Ok(skip_preflight) = dotenv::var("SEND_SKIP_PREFLIGHT") {
+        if let Ok(skip_preflight) = skip_preflight.parse::<bool>() {
+            info!("Using send skipPreflight from .env: {}", skip_preflight);
+            config.send.skip_preflight = skip_preflight;
+        }
+    }
+
+    if let Ok(max_trackers) = dotenv::var("MAX_TX_TRACKERS") {
+        if let Ok(max_trackers) = max_trackers.parse::<usize>() {
+            info!(
+                "Using transaction tracker limit from .env: {}",
+                max_trackers
+            );
+            config.send.max_trackers = max_trackers;
+        }
+    }
+
+    if let Ok(lb_program_id) = dotenv::var("LB_PROGRAM_ID") {
+        info!(
+            "Using Liquidity Bootstrapping Program ID from .env: {}",
+            lb_program_id
+        );
+        config.lb_program_id = Pubkey::from_str_const(&lb_program_id);
+    }
+
+    if let Ok(hook_program_id) = dotenv::var("HOOK_PROGRAM_ID") {
+        info!("Using Hook Program ID from .env: {}", hook_program_id);
+        config.hook_program_id = Pubkey::from_str_const(&hook_program_id);
+    }
+
+    if let Ok(pool_ttl_secs) = dotenv::var("POOL_CACHE_TTL_SECS") {
+        if let Ok(pool_ttl) = pool_ttl_secs.parse::<u64>() {
+            info!("Using Pool Cache TTL from .env: {} seconds", pool_ttl);
+            config.cache_ttl.pool_ttl = Duration::from_secs(pool_ttl);
+        }
+    }
+
+    if let Ok(token_ttl_secs) = dotenv::var("TOKEN_CACHE_TTL_SECS") {
+        if let Ok(token_ttl) = token_ttl_secs.parse::<u64>() {
+            info!("Using Token Cache TTL from .env: {} seconds", token_ttl);
+            config.cache_ttl.token_ttl = Duration::from_secs(token_ttl);
+        }
+    }
+
+    if let Ok(bin_ttl_secs) = dotenv::var("BIN_CACHE_TTL_SECS") {
+        if let Ok(bin_ttl) = bin_ttl_secs.parse::<u64>() {
+            info!("Using Bin Cache TTL from .env: {} seconds", bin_ttl);
+            config.cache_ttl.bin_ttl = Duration::from_secs(bin_ttl);
+        }
+    }
+
+    if let Ok(token_list_path) = dotenv::var("TOKEN_LIST_PATH") {
+        info!("Using token list from .env: {}", token_list_path);
+        config.token_list_path = Some(token_list_path);
+    }
+
+    if let Ok(cache_dir) = dotenv::var("CACHE_DIR") {
+        info!("Using Cache directory from .env: {}", cache_dir);
+        config.cache_dir = (!cache_dir.is_empty()).then_some(cache_dir);
+    }
+
+    if let Ok(watchlist) = dotenv::var("WATCHLIST") {
+        config.watchlist = watchlist
+            .split(',')
+            .map(str::trim)
+            .filter(|pair| !pair.is_empty())
+            .filter_map(|pair| match Pubkey::from_str(pair) {
+                Ok(pair) => Some(pair),
+                Err(e) => {
+                    warn!("Ignoring invalid watchlist pair {}: {}", pair, e);
+                    None
+                }
+            })
+            .collect();
+        info!(
+            "Using Watchlist from .env: {} pairs",
+            config.watchlist.len()
+        );
+    }
+
+    if let Ok(lookup_tables) = dotenv::var("LOOKUP_TABLES") {
+        config.lookup_tables = lookup_tables
+            .split(',')
+            .map(str::trim)
+            .filter(|table| !table.is_empty())
+            .filter_map(|table| match Pubkey::from_str(table) {
+                Ok(table) => Some(table),
+                Err(e) => {
+                    warn!("Ignoring invalid lookup table {}: {}", table, e);
+                    None
+                }
+            })
+            .collect();
+        info!(
+            "Using Lookup tables from .env: {} tables",
+            config.lookup_tables.len()
+        );
+    }
+
+    if let Ok(stats_ttl_secs) = dotenv::var("STATS_CACHE_TTL_SECS") {
+        if let Ok(stats_ttl) = stats_ttl_secs.parse::<u64>() {
+            info!("Using Stats Cache TTL from .env: {} seconds", stats_ttl);
+            config.cache_ttl.stats_ttl = Duration::from_secs(stats_ttl);
+        }
+    }
+
+    if let Ok(admin_token) = dotenv::var("ADMIN_TOKEN") {
+        info!("Using Admin token from .env");
+        config.admin_token = (!admin_token.is_empty()).then_some(admin_token);
+    }
+
+    if let Ok(price_api_url) = dotenv::var("PRICE_API_URL") {
+        info!("Using Price API from .env: {}", price_api_url);
+        config.price_api_url = (!price_api_url.is_empty()).then_some(price_api_url);
+    }
+
+    if let Ok(max_signatures) = dotenv::var("STATS_MAX_SIGNATURES") {
+        if let Ok(max_signatures) = max_signatures.parse::<usize>() {
+            info!("Using Stats signature limit from .env: {}", max_signatures);
+            config.stats_max_signatures = max_signatures;
+        }
+    }
+
+    if let Ok(index_db_path) = dotenv::var("INDEX_DB_PATH") {
+        info!("Using Index database from .env: {}", index_db_path);
+        config.index_db_path = (!index_db_path.is_empty()).then_some(index_db_path);
+    }
+
+    if let Ok(index_interval_secs) = dotenv::var("INDEX_INTERVAL_SECS") {
+        if let Ok(index_interval) = index_interval_secs.parse::<u64>() {
+            info!("Using Index interval from .env: {} seconds", index_interval);
+            config.index_interval = Duration::from_secs(index_interval);
+        }
+    }
+
+    if let Ok(backfill_days) = dotenv::var("INDEX_BACKFILL_DAYS") {
+        if let Ok(backfill_days) = backfill_days.parse::<u64>() {
+            info!("Using Index backfill from .env: {} days", backfill_days);
+            config.index_backfill =
+                (backfill_days > 0).then(|| Duration::from_secs(backfill_days * 86_400));
+        }
+    }
+
+    if let Ok(divergence_bps) = dotenv::var("QUOTE_DIVERGENCE_BPS") {
+        if let Ok(divergence_bps) = divergence_bps.parse::<u64>() {
+            info!("Using Quote divergence from .env: {} bps", divergence_bps);
+            config.quote_divergence_bps = divergence_bps;
+        }
+    }
+
+    if let Ok(cache_capacity) = dotenv::var("CACHE_MAX_ENTRIES") {
+        if let Ok(cache_capacity) = cache_capacity.parse::<usize>() {
+            info!("Using Cache capacity from .env: {} entries", cache_capacity);
+            config.cache_capacity = cache_capacity;
+        }
+    }
+
+    if let Ok(pair_index_secs) = dotenv::var("PAIR_INDEX_REFRESH_SECS") {
+        if let Ok(pair_index_ttl) = pair_index_secs.parse::<u64>() {
+            info!(
+                "Using Pair Index refresh from .env: {} seconds",
+                pair_index_ttl
+            );
+            config.cache_ttl.pair_index_ttl = Duration::from_secs(pair_index_ttl);
+        }
+    }
+
+    config
+}
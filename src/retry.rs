@@ -0,0 +1,335 @@
+use std::{
+    collections::hash_map::RandomState,
+    future::Future,
+    hash::{BuildHasher, Hasher},
+    time::Duration,
+};
+
+use anyhow::Result;
+use solana_client::{
+    client_error::{reqwest, ClientError, ClientErrorKind},
+    rpc_custom_error::{
+        JSON_RPC_SERVER_ERROR_BLOCK_NOT_AVAILABLE,
+        JSON_RPC_SERVER_ERROR_MIN_CONTEXT_SLOT_NOT_REACHED, JSON_RPC_SERVER_ERROR_NODE_UNHEALTHY,
+    },
+    rpc_request::{RpcError, RpcResponseErrorData},
+};
+use tokio::time::error::Elapsed;
+use tracing::warn;
+
+/// How RPC reads are retried: exponential backoff with full jitter, a timeout
+/// per attempt, and only errors that can succeed on a second try are retried.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    pub timeout: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(200),
+            max_delay: Duration::from_secs(2),
+            timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Run `op` until it succeeds, fails with a permanent error or runs out of attempts
+    pub async fn run<T, F, Fut>(&self, name: &str, mut op: F) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let max_attempts = self.max_attempts.max(1);
+        let mut attempt = 1;
+
+        loop {
+            let result = match tokio::time::timeout(self.timeout, op()).await {
+                Ok(result) => result,
+                Err(elapsed) => Err(anyhow::Error::new(elapsed)
+                    .context(format!("{} timed out after {:?}", name, self.timeout))),
+            };
+
+            match result {
+                Ok(value) => return Ok(value),
+                Err(e) if attempt < max_attempts && is_transient(&e) => {
+                    let delay = self.backoff(attempt);
+                    warn!(
+                        "🔁 {} failed (attempt {}/{}), retrying in {:?}: {}",
+                        name, attempt, max_attempts, delay, e
+                    );
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Run a blocking RPC call on the blocking pool, with retries
+    pub async fn run_blocking<T, F>(&self, name: &str, op: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: Fn() -> Result<T> + Clone + Send + 'static,
+    {
+        self.run(name, || {
+            let op = op.clone();
+            async move { tokio::task::spawn_blocking(op).await? }
+        })
+        .await
+    }
+
+    /// Full jitter: a random delay up to the capped exponential backoff
+    fn backoff(&self, attempt: u32) -> Duration {
+        let exponential = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(attempt - 1))
+            .min(self.max_delay);
+        let jitter = RandomState::new().build_hasher().finish() as f64 / u64::MAX as f64;
+
+        exponential.mul_f64(jitter)
+    }
+}
+
+/// Network failures, timeouts, rate limits and lagging nodes are worth
+/// retrying; anything else (bad input, missing accounts) will fail again.
+pub fn is_transient(error: &anyhow::Error) -> bool {
+    if error.chain().any(|cause| cause.is::<Elapsed>()) {
+        return true;
+    }
+    // Raw JSON-RPC calls surface reqwest errors directly
+    if let Some(e) = error
+        .chain()
+        .find_map(|cause| cause.downcast_ref::<reqwest::Error>())
+    {
+        return is_transient_http(e);
+    }
+
+    let Some(client_error) = error
+        .chain()
+        .find_map(|cause| cause.downcast_ref::<ClientError>())
+    else {
+        return false;
+    };
+
+    match client_error.kind() {
+        ClientErrorKind::Io(_) => true,
+        ClientErrorKind::Reqwest(e) => is_transient_http(e),
+        ClientErrorKind::RpcError(RpcError::RpcResponseError { code, data, .. }) => {
+            matches!(
+                *code,
+                JSON_RPC_SERVER_ERROR_MIN_CONTEXT_SLOT_NOT_REACHED
+                    | JSON_RPC_SERVER_ERROR_NODE_UNHEALTHY
+                    | JSON_RPC_SERVER_ERROR_BLOCK_NOT_AVAILABLE
+            ) || matches!(data, RpcResponseErrorData::NodeUnhealthy { .. })
+        }
+        ClientErrorKind::RpcError(RpcError::RpcRequestError(_)) => true,
+        _ => false,
+    }
+}
+
+fn is_transient_http(e: &reqwest::Error) -> bool {
+    e.is_timeout()
+        || e.is_connect()
+        || e.status()
+            .is_some_and(|status| status.is_server_error() || status.as_u16() == 429)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    };
+
+    use axum::{http::StatusCode, routing::get, Router};
+
+    use super::*;
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(5),
+            timeout: Duration::from_millis(200),
+        }
+    }
+
+    fn rpc_error(code: i64, data: RpcResponseErrorData) -> anyhow::Error {
+        ClientError::from(RpcError::RpcResponseError {
+            code,
+            message: "rpc error".to_string(),
+            data,
+        })
+        .into()
+    }
+
+    /// Fail `failures` times with `error`, then succeed, counting calls
+    async fn flaky(
+        policy: &RetryPolicy,
+        failures: u32,
+        error: fn() -> anyhow::Error,
+    ) -> (Result<u32>, u32) {
+        let calls = AtomicU32::new(0);
+        let result = policy
+            .run("flaky", || async move {
+                let call = calls.fetch_add(1, Ordering::SeqCst) + 1;
+                if call <= failures {
+                    Err(error())
+                } else {
+                    Ok(call)
+                }
+            })
+            .await;
+        (result, calls.load(Ordering::SeqCst))
+    }
+
+    fn lagging_node() -> anyhow::Error {
+        rpc_error(
+            JSON_RPC_SERVER_ERROR_MIN_CONTEXT_SLOT_NOT_REACHED,
+            RpcResponseErrorData::Empty,
+        )
+    }
+
+    fn bad_request() -> anyhow::Error {
+        rpc_error(-32602, RpcResponseErrorData::Empty)
+    }
+
+    #[test]
+    fn classifies_rpc_errors() {
+        assert!(is_transient(&lagging_node()));
+        assert!(is_transient(&rpc_error(
+            JSON_RPC_SERVER_ERROR_NODE_UNHEALTHY,
+            RpcResponseErrorData::Empty
+        )));
+        assert!(is_transient(&rpc_error(
+            -32000,
+            RpcResponseErrorData::NodeUnhealthy {
+                num_slots_behind: Some(10)
+            }
+        )));
+        assert!(is_transient(
+            &ClientError::from(RpcError::RpcRequestError("reset".to_string())).into()
+        ));
+        assert!(is_transient(
+            &ClientError::from(std::io::Error::other("connection reset")).into()
+        ));
+        // Found anywhere in the chain
+        assert!(is_transient(&lagging_node().context("pair state")));
+
+        assert!(!is_transient(&bad_request()));
+        assert!(!is_transient(
+            &ClientError::from(ClientErrorKind::Custom("bad".to_string())).into()
+        ));
+        assert!(!is_transient(&anyhow::anyhow!("Pair account not found")));
+    }
+
+    #[tokio::test]
+    async fn classifies_http_errors() {
+        let app = Router::new()
+            .route("/limited", get(|| async { StatusCode::TOO_MANY_REQUESTS }))
+            .route("/down", get(|| async { StatusCode::BAD_GATEWAY }))
+            .route("/missing", get(|| async { StatusCode::NOT_FOUND }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let http = reqwest::Client::new();
+        let status_error = |path: &'static str| {
+            let request = http.get(format!("{}{}", url, path));
+            async move {
+                anyhow::Error::new(
+                    request
+                        .send()
+                        .await
+                        .unwrap()
+                        .error_for_status()
+                        .unwrap_err(),
+                )
+            }
+        };
+
+        assert!(is_transient(&status_error("/limited").await));
+        assert!(is_transient(&status_error("/down").await));
+        assert!(!is_transient(&status_error("/missing").await));
+
+        // Nothing listens on port 1
+        let refused = http.get("http://127.0.0.1:1").send().await.unwrap_err();
+        assert!(is_transient(&refused.into()));
+    }
+
+    #[tokio::test]
+    async fn retries_transient_errors_only() {
+        let (result, calls) = flaky(&policy(), 2, lagging_node).await;
+        assert_eq!((result.unwrap(), calls), (3, 3));
+
+        let (result, calls) = flaky(&policy(), 5, lagging_node).await;
+        assert!(result.is_err());
+        assert_eq!(calls, 3);
+
+        let (result, calls) = flaky(&policy(), 1, bad_request).await;
+        assert!(result.is_err());
+        assert_eq!(calls, 1);
+
+        let single = RetryPolicy {
+            max_attempts: 0,
+            ..policy()
+        };
+        assert_eq!(flaky(&single, 5, lagging_node).await.1, 1);
+    }
+
+    #[tokio::test]
+    async fn retries_timed_out_attempts() {
+        let calls = Arc::new(AtomicU32::new(0));
+        let counted = calls.clone();
+        let result = policy()
+            .run("slow", || {
+                let calls = counted.clone();
+                async move {
+                    if calls.fetch_add(1, Ordering::SeqCst) == 0 {
+                        tokio::time::sleep(Duration::from_secs(5)).await;
+                    }
+                    Ok(())
+                }
+            })
+            .await;
+
+        assert!(result.is_ok());
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        let elapsed = tokio::time::timeout(Duration::ZERO, std::future::pending::<()>())
+            .await
+            .unwrap_err();
+        assert!(is_transient(&anyhow::Error::new(elapsed).context("slow")));
+    }
+
+    #[tokio::test]
+    async fn run_blocking_returns_the_value() {
+        let value = policy()
+            .run_blocking("blocking", || Ok(21 * 2))
+            .await
+            .unwrap();
+        assert_eq!(value, 42);
+    }
+
+    #[test]
+    fn backoff_stays_under_the_cap() {
+        let policy = RetryPolicy {
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(1_000),
+            ..RetryPolicy::default()
+        };
+
+        for _ in 0..100 {
+            assert!(policy.backoff(1) <= Duration::from_millis(100));
+            assert!(policy.backoff(3) <= Duration::from_millis(400));
+            assert!(policy.backoff(10) <= Duration::from_millis(1_000));
+            assert!(policy.backoff(u32::MAX) <= Duration::from_millis(1_000));
+        }
+    }
+}
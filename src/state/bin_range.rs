@@ -0,0 +1,112 @@
+use std::{collections::HashMap, sync::Arc};
+
+use anyhow::Result;
+use saros_sdk::state::{bin_array::BinArray, pair::Pair};
+use serde::{Deserialize, Serialize};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{program_pack::Pack, pubkey::Pubkey};
+
+use crate::{dlmm::get_price_from_id, state::BIN_ARRAY_SIZE};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BinLiquidity {
+    pub bin_id: u32,
+    pub price: f64,
+    pub amount_x: u64,
+    pub amount_y: u64,
+    /// X available from the active bin up to and including this bin
+    pub cumulative_x: u64,
+    /// Y available from the active bin down to and including this bin
+    pub cumulative_y: u64,
+}
+
+pub struct BinRange {
+    pub active_id: u32,
+    pub bin_step: u8,
+    pub bins: Vec<BinLiquidity>,
+}
+
+impl BinRange {
+    /// Read `range` bins on each side of the active id, across as many
+    /// `BinArray` accounts as that spans.
+    pub fn fetch(client: Arc<RpcClient>, pair_key: Pubkey, range: u32) -> Result<BinRange> {
+        let pair_account = client.get_account(&pair_key)?;
+        let pair = Pair::unpack(&pair_account.data)?;
+
+        let lower_id = pair.active_id.saturating_sub(range);
+        let upper_id = pair.active_id.saturating_add(range);
+
+        let indexes: Vec<u32> = (lower_id / BIN_ARRAY_SIZE..=upper_id / BIN_ARRAY_SIZE).collect();
+        let keys: Vec<Pubkey> = indexes
+            .iter()
+            .map(|index| bin_array_key(&pair_key, *index, &pair_account.owner))
+            .collect();
+
+        // Uninitialized bin arrays simply hold no liquidity
+        let bin_arrays: HashMap<u32, BinArray> = client
+            .get_multiple_accounts(&keys)?
+            .into_iter()
+            .zip(indexes)
+            .filter_map(|(account, index)| {
+                let bin_array = BinArray::unpack(&account?.data).ok()?;
+                Some((index, bin_array))
+            })
+            .collect();
+
+        let mut bins: Vec<BinLiquidity> = (lower_id..=upper_id)
+            .map(|bin_id| {
+                let (amount_x, amount_y) = bin_arrays
+                    .get(&(bin_id / BIN_ARRAY_SIZE))
+                    .map(|bin_array| {
+                        let bin = &bin_array.bins[(bin_id % BIN_ARRAY_SIZE) as usize];
+                        (bin.reserve_x, bin.reserve_y)
+                    })
+                    .unwrap_or_default();
+
+                BinLiquidity {
+                    bin_id,
+                    price: get_price_from_id(pair.bin_step, bin_id),
+                    amount_x,
+                    amount_y,
+                    cumulative_x: 0,
+                    cumulative_y: 0,
+                }
+            })
+            .collect();
+
+        // Depth accumulates outward from the active bin on each side
+        let mut cumulative_x = 0u64;
+        for bin in bins.iter_mut().filter(|bin| bin.bin_id >= pair.active_id) {
+            cumulative_x = cumulative_x.saturating_add(bin.amount_x);
+            bin.cumulative_x = cumulative_x;
+        }
+
+        let mut cumulative_y = 0u64;
+        for bin in bins
+            .iter_mut()
+            .rev()
+            .filter(|bin| bin.bin_id <= pair.active_id)
+        {
+            cumulative_y = cumulative_y.saturating_add(bin.amount_y);
+            bin.cumulative_y = cumulative_y;
+        }
+
+        Ok(BinRange {
+            active_id: pair.active_id,
+            bin_step: pair.bin_step,
+            bins,
+        })
+    }
+}
+
+pub fn bin_array_key(pair_key: &Pubkey, index: u32, program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            b"bin_array".as_ref(),
+            pair_key.as_ref(),
+            index.to_le_bytes().as_ref(),
+        ],
+        program_id,
+    )
+    .0
+}
@@ -0,0 +1,350 @@
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anchor_lang::AnchorDeserialize;
+use anyhow::{Context, Result};
+use serde::Serialize;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::state::BIN_ARRAY_SIZE;
+
+/// Bins tracked by a single position account
+const POSITION_BIN_COUNT: usize = 64;
+/// Hook accumulators are X64 fixed point
+const REWARD_PER_SHARE_SCALE: f64 = 18_446_744_073_709_551_616.0;
+
+// Layouts follow the rewarder hook and liquidity book program IDLs
+
+#[derive(Debug, Clone, AnchorDeserialize)]
+#[borsh(crate = "anchor_lang::prelude::borsh")]
+pub struct HookState {
+    _bump: [u8; 1],
+    pub authority: Pubkey,
+    pub pair: Pubkey,
+    pub reward_token_mint: Pubkey,
+    pub hook_reserve: Pubkey,
+    pub rewards_per_second: u64,
+    pub end_time: i64,
+    pub last_update: i64,
+    /// Rewarded bins start at `active_id + delta_bin_a`
+    pub delta_bin_a: i32,
+    /// Rewarded bins end at `active_id + delta_bin_b`
+    pub delta_bin_b: i32,
+    pub total_unclaimed_rewards: u64,
+}
+
+#[derive(Debug, Clone, Copy, AnchorDeserialize)]
+#[borsh(crate = "anchor_lang::prelude::borsh")]
+pub struct HookBin {
+    pub accumulated_rewards_per_share: u128,
+}
+
+#[derive(Debug, Clone, AnchorDeserialize)]
+#[borsh(crate = "anchor_lang::prelude::borsh")]
+pub struct HookBinArray {
+    pub hook: Pubkey,
+    pub index: u32,
+    pub bins: [HookBin; BIN_ARRAY_SIZE as usize],
+}
+
+#[derive(Debug, Clone, AnchorDeserialize)]
+#[borsh(crate = "anchor_lang::prelude::borsh")]
+pub struct HookPosition {
+    pub user_accrued_rewards_per_share: [u128; POSITION_BIN_COUNT],
+    pub pending_rewards: u64,
+    _bump: [u8; 1],
+}
+
+#[derive(Debug, Clone, AnchorDeserialize)]
+#[borsh(crate = "anchor_lang::prelude::borsh")]
+pub struct LiquidityPosition {
+    pub pair: Pubkey,
+    pub position_mint: Pubkey,
+    pub liquidity_shares: [u128; POSITION_BIN_COUNT],
+    pub lower_bin_id: i32,
+    pub upper_bin_id: i32,
+    _space: [u8; 8],
+}
+
+/// Skip the 8 byte Anchor discriminator and decode the account body
+pub fn decode_account<T: AnchorDeserialize>(data: &[u8], name: &str) -> Result<T> {
+    let mut body = data
+        .get(8..)
+        .with_context(|| format!("{} account is too short", name))?;
+    T::deserialize(&mut body).with_context(|| format!("Failed to decode {} account", name))
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HookBinReward {
+    pub bin_id: u32,
+    /// Raw X64 accumulator, as a string since it exceeds JSON number precision
+    pub accumulated_rewards_per_share: String,
+    pub rewards_per_share: f64,
+}
+
+/// A pair's hook with the reward accumulators of its rewarded bins
+pub struct HookRewards {
+    pub hook_key: Pubkey,
+    /// Active bin the rewarded range was read around
+    pub active_id: u32,
+    pub hook: HookState,
+    pub bins: Vec<HookBinReward>,
+}
+
+impl HookRewards {
+    /// Read the hook and the hook bin arrays covering its rewarded range
+    /// around `active_id`.
+    pub fn fetch(
+        client: Arc<RpcClient>,
+        hook_key: Pubkey,
+        hook_program_id: Pubkey,
+        active_id: u32,
+    ) -> Result<HookRewards> {
+        let hook_account = client.get_account(&hook_key)?;
+        let hook: HookState = decode_account(&hook_account.data, "hook")?;
+
+        let lower_id = active_id.saturating_add_signed(hook.delta_bin_a);
+        let upper_id = active_id
+            .saturating_add_signed(hook.delta_bin_b)
+            .max(lower_id);
+        let bin_arrays =
+            fetch_hook_bin_arrays(&client, &hook_key, &hook_program_id, lower_id, upper_id)?;
+
+        let bins = (lower_id..=upper_id)
+            .filter_map(|bin_id| {
+                let accumulated = hook_bin(&bin_arrays, bin_id)?.accumulated_rewards_per_share;
+                Some(HookBinReward {
+                    bin_id,
+                    accumulated_rewards_per_share: accumulated.to_string(),
+                    rewards_per_share: accumulated as f64 / REWARD_PER_SHARE_SCALE,
+                })
+            })
+            .collect();
+
+        Ok(HookRewards {
+            hook_key,
+            active_id,
+            hook,
+            bins,
+        })
+    }
+
+    /// Whether the hook still emits rewards
+    pub fn is_active(&self) -> bool {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|now| now.as_secs() as i64)
+            .unwrap_or_default();
+        self.hook.rewards_per_second > 0 && now < self.hook.end_time
+    }
+
+    /// Emission per second shared by each rewarded bin
+    pub fn rewards_per_second_per_bin(&self) -> f64 {
+        let bin_count = (self.hook.delta_bin_b - self.hook.delta_bin_a + 1).max(1);
+        self.hook.rewards_per_second as f64 / bin_count as f64
+    }
+}
+
+/// Rewards a position has accrued on its hook as of the hook's last update
+#[derive(Debug, Clone, Serialize)]
+pub struct PositionRewards {
+    pub position: String,
+    pub pair: String,
+    pub position_mint: String,
+    pub hook: String,
+    pub reward_mint: String,
+    pub lower_bin_id: i32,
+    pub upper_bin_id: i32,
+    pub pending_rewards: u64,
+    pub accrued_rewards: u64,
+    pub total_rewards: u64,
+    pub hook_last_update: i64,
+}
+
+impl PositionRewards {
+    /// Pending rewards plus each bin's accumulator growth since the position
+    /// last synced, weighted by its liquidity shares.
+    pub fn fetch(
+        client: Arc<RpcClient>,
+        position_key: Pubkey,
+        hook_key: Pubkey,
+        hook_program_id: Pubkey,
+    ) -> Result<PositionRewards> {
+        let position_account = client.get_account(&position_key)?;
+        let position: LiquidityPosition = decode_account(&position_account.data, "position")?;
+
+        let hook_account = client.get_account(&hook_key)?;
+        let hook: HookState = decode_account(&hook_account.data, "hook")?;
+
+        let hook_position_key = hook_position_key(&hook_key, &position_key, &hook_program_id);
+        let hook_position_account = client
+            .get_account(&hook_position_key)
+            .context("Position is not registered with the hook")?;
+        let hook_position: HookPosition =
+            decode_account(&hook_position_account.data, "hook position")?;
+
+        let lower_id = position.lower_bin_id.max(0) as u32;
+        let upper_id = position.upper_bin_id.max(position.lower_bin_id).max(0) as u32;
+        let bin_arrays =
+            fetch_hook_bin_arrays(&client, &hook_key, &hook_program_id, lower_id, upper_id)?;
+
+        let accrued: u128 = (lower_id..=upper_id)
+            .zip(position.liquidity_shares.iter())
+            .zip(hook_position.user_accrued_rewards_per_share.iter())
+            .filter_map(|((bin_id, shares), user_accrued)| {
+                let accumulated = hook_bin(&bin_arrays, bin_id)?.accumulated_rewards_per_share;
+                let growth = accumulated.saturating_sub(*user_accrued);
+                Some(mul_shr_64(*shares, growth))
+            })
+            .sum();
+        let accrued_rewards = u64::try_from(accrued).unwrap_or(u64::MAX);
+
+        Ok(PositionRewards {
+            position: position_key.to_string(),
+            pair: position.pair.to_string(),
+            position_mint: position.position_mint.to_string(),
+            hook: hook_key.to_string(),
+            reward_mint: hook.reward_token_mint.to_string(),
+            lower_bin_id: position.lower_bin_id,
+            upper_bin_id: position.upper_bin_id,
+            pending_rewards: hook_position.pending_rewards,
+            accrued_rewards,
+            total_rewards: hook_position
+                .pending_rewards
+                .saturating_add(accrued_rewards),
+            hook_last_update: hook.last_update,
+        })
+    }
+}
+
+/// Read the pair this position belongs to, to find its hook
+pub fn fetch_position_pair(client: &RpcClient, position_key: Pubkey) -> Result<Pubkey> {
+    Ok(fetch_liquidity_position(client, position_key)?.pair)
+}
+
+pub fn fetch_liquidity_position(
+    client: &RpcClient,
+    position_key: Pubkey,
+) -> Result<LiquidityPosition> {
+    let account = client.get_account(&position_key)?;
+    decode_account(&account.data, "position")
+}
+
+pub fn hook_bin_array_key(hook_key: &Pubkey, index: u32, hook_program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            b"bin_array".as_ref(),
+            hook_key.as_ref(),
+            index.to_le_bytes().as_ref(),
+        ],
+        hook_program_id,
+    )
+    .0
+}
+
+pub fn hook_position_key(
+    hook_key: &Pubkey,
+    position_key: &Pubkey,
+    hook_program_id: &Pubkey,
+) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            b"position".as_ref(),
+            hook_key.as_ref(),
+            position_key.as_ref(),
+        ],
+        hook_program_id,
+    )
+    .0
+}
+
+/// Uninitialized hook bin arrays have accrued nothing and are skipped
+fn fetch_hook_bin_arrays(
+    client: &RpcClient,
+    hook_key: &Pubkey,
+    hook_program_id: &Pubkey,
+    lower_id: u32,
+    upper_id: u32,
+) -> Result<HashMap<u32, HookBinArray>> {
+    let indexes: Vec<u32> = (lower_id / BIN_ARRAY_SIZE..=upper_id / BIN_ARRAY_SIZE).collect();
+    let keys: Vec<Pubkey> = indexes
+        .iter()
+        .map(|index| hook_bin_array_key(hook_key, *index, hook_program_id))
+        .collect();
+
+    Ok(client
+        .get_multiple_accounts(&keys)?
+        .into_iter()
+        .zip(indexes)
+        .filter_map(|(account, index)| {
+            let bin_array: HookBinArray = decode_account(&account?.data, "hook bin array").ok()?;
+            (bin_array.hook == *hook_key && bin_array.index == index).then_some((index, bin_array))
+        })
+        .collect())
+}
+
+fn hook_bin(bin_arrays: &HashMap<u32, HookBinArray>, bin_id: u32) -> Option<&HookBin> {
+    bin_arrays
+        .get(&(bin_id / BIN_ARRAY_SIZE))
+        .map(|bin_array| &bin_array.bins[(bin_id % BIN_ARRAY_SIZE) as usize])
+}
+
+/// `(a * b) >> 64` without overflowing the intermediate product
+fn mul_shr_64(a: u128, b: u128) -> u128 {
+    let (a_hi, a_lo) = (a >> 64, a & u64::MAX as u128);
+    let (b_hi, b_lo) = (b >> 64, b & u64::MAX as u128);
+
+    let lo = (a_lo * b_lo) >> 64;
+    let mid = a_hi
+        .saturating_mul(b_lo)
+        .saturating_add(a_lo.saturating_mul(b_hi))
+        .saturating_add(lo);
+    let hi = a_hi.saturating_mul(b_hi);
+
+    hi.saturating_mul(1 << 64).saturating_add(mid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ONE: u128 = 1 << 64;
+
+    #[test]
+    fn mul_shr_64_matches_the_exact_product() {
+        let values = [
+            0,
+            1,
+            3,
+            1 << 63,
+            u64::MAX as u128,
+            ONE,
+            3 * ONE + 7,
+            1 << 100,
+        ];
+        for a in values {
+            for b in values {
+                if let Some(product) = a.checked_mul(b) {
+                    assert_eq!(mul_shr_64(a, b), product >> 64, "{} * {}", a, b);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn mul_shr_64_handles_wide_products() {
+        // X64 fixed point: multiplying by one is the identity
+        assert_eq!(mul_shr_64(ONE, u128::MAX), u128::MAX);
+        assert_eq!(mul_shr_64(u128::MAX, ONE), u128::MAX);
+        assert_eq!(mul_shr_64(1 << 100, 1 << 90), 1 << 126);
+        // Rounds down
+        assert_eq!(mul_shr_64(3, 1 << 63), 1);
+        // Saturates when the result does not fit
+        assert_eq!(mul_shr_64(u128::MAX, u128::MAX), u128::MAX);
+        assert_eq!(mul_shr_64(1 << 127, 1 << 65), u128::MAX);
+    }
+}
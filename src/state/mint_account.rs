@@ -1,8 +1,9 @@
-use std::sync::Arc;
-
+use anyhow::Result;
 use serde::{Deserialize, Serialize};
-use solana_client::rpc_client::RpcClient;
 use solana_sdk::{account::Account, pubkey::Pubkey};
+
+use super::SlotAccounts;
+
 #[derive(Clone, Deserialize, Serialize)]
 pub struct MintAccount {
     pub key: Pubkey,
@@ -10,14 +11,10 @@ pub struct MintAccount {
 }
 
 impl MintAccount {
-    pub fn fetch(client: Arc<RpcClient>, mint_key: Pubkey) -> Self {
-        let account = client
-            .get_account(&mint_key)
-            .expect("Failed to get mint account");
-
-        MintAccount {
+    pub fn from_accounts(mint_key: Pubkey, accounts: &SlotAccounts) -> Result<Self> {
+        Ok(MintAccount {
             key: mint_key,
-            account,
-        }
+            account: accounts.get(&mint_key)?.clone(),
+        })
     }
 }
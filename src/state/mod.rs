@@ -1,79 +1,214 @@
+mod bin_range;
+mod hook;
 mod mint_account;
+mod off_chain_meta;
 mod pair_account;
+mod pair_index;
+mod pair_static;
 mod pool_state;
+mod position;
+mod slot_accounts;
+mod token_list;
 mod token_meta;
 mod types;
 
 use std::sync::Arc;
 
-use anyhow::Result;
+use anyhow::{bail, Context, Result};
+pub use bin_range::*;
+pub use hook::*;
 use jupiter_amm_interface::KeyedAccount;
 pub use mint_account::*;
+pub use off_chain_meta::*;
+pub use pair_index::*;
+pub use pair_static::*;
 pub use pool_state::*;
+pub use position::*;
+use saros_sdk::state::pair::Pair;
+pub use slot_accounts::*;
 use solana_client::rpc_client::RpcClient;
-use solana_sdk::pubkey::Pubkey;
+use solana_sdk::{program_pack::Pack, pubkey::Pubkey};
+pub use token_list::*;
 pub use token_meta::*;
 pub use types::*;
 
+use crate::retry::RetryPolicy;
+
 pub struct State {
+    /// Slot every account below was read at
+    pub slot: u64,
+    pub pair_account: KeyedAccount,
+    /// `None` while a bin array next to the active bin is uninitialized
     pub pool_state: Option<PoolState>,
     pub mint_accounts: Vec<MintAccount>,
+    /// Every account read, enough to load the pair's AMM without reading again
+    pub accounts: AccountMap,
 }
 
-pub trait Fetch {
-    fn fetch(client: Arc<RpcClient>, key: Pubkey) -> Result<Self>
-    where
-        Self: Sized;
-}
-
-impl Fetch for KeyedAccount {
-    fn fetch(client: Arc<RpcClient>, pair_key: Pubkey) -> Result<Self> {
-        pair_account::fetch(client, pair_key)
-    }
-}
+/// The pair decides which bin arrays to read, so a pair that moved to another
+/// bin array between reads needs another round
+const MAX_STATE_ROUNDS: usize = 3;
 
 impl State {
-    pub async fn generate_state_async(client: Arc<RpcClient>, pair_account: KeyedAccount) -> Self {
-        tokio::task::spawn_blocking(move || {
-            // ---- run in a separate thread, safe with runtime ----
-            let state = PoolState::fetch(client.clone(), pair_account);
-            let mint_x_account = MintAccount::fetch(client.clone(), state.mint_x);
-            let mint_y_account = MintAccount::fetch(client.clone(), state.mint_y);
-
-            State {
-                pool_state: Some(state),
-                mint_accounts: vec![mint_x_account, mint_y_account],
+    /// Read a pair with its active bin arrays and mints from a single slot
+    pub fn fetch(
+        client: &RpcClient,
+        pair_key: Pubkey,
+        min_context_slot: Option<u64>,
+    ) -> Result<State> {
+        let mut keys = vec![pair_key];
+        let mut min_context_slot = min_context_slot;
+
+        for _ in 0..MAX_STATE_ROUNDS {
+            let accounts = SlotAccounts::fetch(client, &keys, min_context_slot)?;
+            let account = accounts.get(&pair_key)?.clone();
+            let pair = Pair::unpack(&account.data).context("Failed to unpack pair account")?;
+
+            let bin_array_keys = PoolState::bin_array_keys(&pair_key, &account.owner, &pair);
+            let state_keys = [
+                vec![pair_key],
+                bin_array_keys.to_vec(),
+                vec![pair.token_mint_x, pair.token_mint_y],
+            ]
+            .concat();
+
+            if state_keys != keys {
+                keys = state_keys;
+                min_context_slot = Some(accounts.slot);
+                continue;
             }
-        })
-        .await
-        .expect("spawn_blocking failed")
+
+            let pool_state = PoolState::from_accounts(&pair, bin_array_keys, &accounts)?;
+            let mint_accounts = vec![
+                MintAccount::from_accounts(pair.token_mint_x, &accounts)?,
+                MintAccount::from_accounts(pair.token_mint_y, &accounts)?,
+            ];
+
+            return Ok(State {
+                slot: accounts.slot,
+                pool_state,
+                mint_accounts,
+                pair_account: KeyedAccount {
+                    key: pair_key,
+                    account,
+                    params: None,
+                },
+                accounts: accounts.into_map(),
+            });
+        }
+
+        bail!("Pair {} kept moving bin arrays while being read", pair_key)
+    }
+
+    pub async fn generate_state_async(
+        client: Arc<RpcClient>,
+        pair_key: Pubkey,
+        min_context_slot: Option<u64>,
+        retry: &RetryPolicy,
+    ) -> Result<State> {
+        retry
+            .run_blocking("pair state", move || {
+                // ---- run in a separate thread, safe with runtime ----
+                State::fetch(&client, pair_key, min_context_slot)
+            })
+            .await
     }
 
-    pub async fn generate_keyed_account(
+    pub async fn generate_bin_range(
         client: Arc<RpcClient>,
         pair_key: Pubkey,
-    ) -> Result<KeyedAccount> {
-        let pair_account = tokio::task::spawn_blocking(move || {
-            // ---- run in a separate thread, safe with runtime ----
-            KeyedAccount::fetch(client.clone(), pair_key)
-        })
-        .await
-        .expect("spawn_blocking failed")?;
-
-        Ok(pair_account)
+        range: u32,
+        retry: &RetryPolicy,
+    ) -> Result<BinRange> {
+        retry
+            .run_blocking("bin range", move || {
+                // ---- run in a separate thread, safe with runtime ----
+                BinRange::fetch(client.clone(), pair_key, range)
+            })
+            .await
+    }
+
+    pub async fn generate_hook_rewards(
+        client: Arc<RpcClient>,
+        hook_key: Pubkey,
+        hook_program_id: Pubkey,
+        active_id: u32,
+        retry: &RetryPolicy,
+    ) -> Result<HookRewards> {
+        retry
+            .run_blocking("hook rewards", move || {
+                // ---- run in a separate thread, safe with runtime ----
+                HookRewards::fetch(client.clone(), hook_key, hook_program_id, active_id)
+            })
+            .await
+    }
+
+    pub async fn generate_position_rewards(
+        client: Arc<RpcClient>,
+        position_key: Pubkey,
+        hook_key: Pubkey,
+        hook_program_id: Pubkey,
+        retry: &RetryPolicy,
+    ) -> Result<PositionRewards> {
+        retry
+            .run_blocking("position rewards", move || {
+                // ---- run in a separate thread, safe with runtime ----
+                PositionRewards::fetch(client.clone(), position_key, hook_key, hook_program_id)
+            })
+            .await
+    }
+
+    pub async fn generate_position_state(
+        client: Arc<RpcClient>,
+        position_key: Pubkey,
+        retry: &RetryPolicy,
+    ) -> Result<PositionState> {
+        retry
+            .run_blocking("position", move || {
+                // ---- run in a separate thread, safe with runtime ----
+                PositionState::fetch(client.clone(), position_key)
+            })
+            .await
+    }
+
+    pub async fn generate_pair_index(
+        client: Arc<RpcClient>,
+        program_id: Pubkey,
+        retry: &RetryPolicy,
+    ) -> Result<PairIndex> {
+        retry
+            .run_blocking("pair index", move || {
+                // ---- run in a separate thread, safe with runtime ----
+                PairIndex::fetch(client.clone(), program_id)
+            })
+            .await
     }
 
     pub async fn generate_token_state(
         client: Arc<RpcClient>,
         mint_key: Pubkey,
+        token_list: Arc<TokenList>,
+        retry: &RetryPolicy,
     ) -> Result<TokenMeta> {
-        let token_state = tokio::task::spawn_blocking(move || {
-            // ---- run in a separate thread, safe with runtime ----
-            TokenMeta::fetch(client.clone(), mint_key)
-        })
-        .await
-        .expect("spawn_blocking failed")?;
-
-        Ok(token_state)
+        retry
+            .run_blocking("token metadata", move || {
+                // ---- run in a separate thread, safe with runtime ----
+                TokenMeta::fetch(client.clone(), mint_key, token_list.clone())
+            })
+            .await
+    }
+
+    pub async fn generate_token_states(
+        client: Arc<RpcClient>,
+        mint_keys: Vec<Pubkey>,
+        token_list: Arc<TokenList>,
+        retry: &RetryPolicy,
+    ) -> Result<Vec<TokenMeta>> {
+        retry
+            .run_blocking("token metadata batch", move || {
+                // ---- run in a separate thread, safe with runtime ----
+                TokenMeta::fetch_many(&client, &mint_keys, &token_list)
+            })
+            .await
     }
 }
@@ -0,0 +1,166 @@
+use std::{
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    sync::Arc,
+    time::Duration,
+};
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use solana_client::client_error::reqwest::{
+    self,
+    dns::{Addrs, Name, Resolve, Resolving},
+    redirect, Url,
+};
+
+/// Gateway `ipfs://` URIs are fetched through
+const IPFS_GATEWAY: &str = "https://ipfs.io/ipfs/";
+/// Gateway `ar://` URIs are fetched through
+const ARWEAVE_GATEWAY: &str = "https://arweave.net/";
+const MAX_REDIRECTS: usize = 5;
+
+/// Fields we care about from the off-chain JSON a token's `uri` points at
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OffChainMeta {
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub symbol: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub image: Option<String>,
+}
+
+impl OffChainMeta {
+    /// HTTP client for token URIs. The URI is picked by the token creator, so
+    /// only https to public addresses is allowed, redirects included.
+    pub fn http_client() -> Result<reqwest::Client> {
+        let redirects = redirect::Policy::custom(|attempt| {
+            if attempt.previous().len() >= MAX_REDIRECTS {
+                attempt.error("too many redirects")
+            } else if let Err(e) = check_url(attempt.url()) {
+                attempt.error(e.to_string())
+            } else {
+                attempt.follow()
+            }
+        });
+
+        Ok(reqwest::Client::builder()
+            .timeout(Duration::from_secs(5))
+            .redirect(redirects)
+            .dns_resolver(Arc::new(PublicResolver))
+            .build()?)
+    }
+
+    pub async fn fetch(http: &reqwest::Client, uri: &str) -> Result<OffChainMeta> {
+        let url = resolve_uri(uri)?;
+
+        let meta = http
+            .get(url)
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<OffChainMeta>()
+            .await?;
+
+        Ok(meta)
+    }
+}
+
+/// Map `ipfs://` and `ar://` to their gateways and reject anything that is
+/// not https to a public host
+fn resolve_uri(uri: &str) -> Result<Url> {
+    let uri = uri.trim();
+    let url = if let Some(path) = uri.strip_prefix("ipfs://") {
+        format!("{}{}", IPFS_GATEWAY, path.trim_start_matches("ipfs/"))
+    } else if let Some(id) = uri.strip_prefix("ar://") {
+        format!("{}{}", ARWEAVE_GATEWAY, id)
+    } else {
+        uri.to_string()
+    };
+
+    let url = Url::parse(&url)?;
+    check_url(&url)?;
+    Ok(url)
+}
+
+fn check_url(url: &Url) -> Result<()> {
+    if url.scheme() != "https" {
+        bail!("Only https URIs are fetched, got {}", url.scheme());
+    }
+
+    let Some(host) = url.host_str() else {
+        bail!("URI has no host");
+    };
+    // Domains are checked by `PublicResolver` once resolved
+    let literal = host.trim_start_matches('[').trim_end_matches(']');
+    if let Ok(ip) = literal.parse::<IpAddr>() {
+        if !is_public(ip) {
+            bail!("URI points at non-public address {}", ip);
+        }
+    }
+
+    Ok(())
+}
+
+/// System DNS, dropping private, loopback and other non-public addresses so a
+/// domain cannot point the server at its own network
+struct PublicResolver;
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            let addrs: Vec<SocketAddr> = tokio::net::lookup_host((name.as_str(), 0))
+                .await?
+                .filter(|addr| is_public(addr.ip()))
+                .collect();
+            if addrs.is_empty() {
+                return Err(format!("{} has no public address", name.as_str()).into());
+            }
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_public_v4(ip),
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(mapped) => is_public_v4(mapped),
+            None => is_public_v6(ip),
+        },
+    }
+}
+
+fn is_public_v4(ip: Ipv4Addr) -> bool {
+    let [a, b, c, _] = ip.octets();
+    !(ip.is_private()
+        || ip.is_loopback()
+        || ip.is_link_local()
+        || ip.is_unspecified()
+        || ip.is_broadcast()
+        || ip.is_documentation()
+        || ip.is_multicast()
+        || a == 0
+        // Carrier-grade NAT 100.64.0.0/10
+        || (a == 100 && (64..128).contains(&b))
+        // IETF protocol assignments 192.0.0.0/24
+        || (a == 192 && b == 0 && c == 0)
+        // Benchmarking 198.18.0.0/15
+        || (a == 198 && (18..20).contains(&b))
+        // Reserved 240.0.0.0/4
+        || a >= 240)
+}
+
+fn is_public_v6(ip: Ipv6Addr) -> bool {
+    let first = ip.segments()[0];
+    !(ip.is_loopback()
+        || ip.is_unspecified()
+        || ip.is_multicast()
+        // Unique local fc00::/7
+        || (first & 0xfe00) == 0xfc00
+        // Link local fe80::/10
+        || (first & 0xffc0) == 0xfe80
+        // Documentation 2001:db8::/32
+        || (first == 0x2001 && ip.segments()[1] == 0x0db8))
+}
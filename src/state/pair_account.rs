@@ -45,13 +45,3 @@ impl PairAccount {
         }
     }
 }
-
-pub fn fetch(client: Arc<RpcClient>, pair_key: Pubkey) -> Result<KeyedAccount> {
-    let account = client.get_account(&pair_key)?;
-
-    Ok(KeyedAccount {
-        key: pair_key,
-        account,
-        params: None,
-    })
-}
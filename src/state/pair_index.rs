@@ -0,0 +1,215 @@
+use std::sync::Arc;
+
+use anchor_lang::solana_program::hash::hash;
+use anyhow::Result;
+use saros_sdk::state::pair::Pair;
+use serde::{Deserialize, Serialize};
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::{
+    rpc_client::RpcClient,
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+    rpc_filter::{Memcmp, RpcFilterType},
+};
+use solana_sdk::{program_pack::Pack, pubkey::Pubkey};
+use tracing::warn;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PairIndexEntry {
+    pub pair: Pubkey,
+    pub mint_x: Pubkey,
+    pub mint_y: Pubkey,
+    pub bin_step: u8,
+    pub active_id: u32,
+    pub symbol_x: Option<String>,
+    pub symbol_y: Option<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct PairSearch {
+    pub mint: Option<Pubkey>,
+    pub symbol: Option<String>,
+    pub bin_step: Option<u8>,
+}
+
+#[derive(Default)]
+pub struct PairIndex {
+    pub entries: Vec<PairIndexEntry>,
+}
+
+impl PairIndex {
+    /// Anchor account discriminator of the `Pair` account
+    pub fn discriminator() -> [u8; 8] {
+        let mut discriminator = [0u8; 8];
+        discriminator.copy_from_slice(&hash(b"account:Pair").to_bytes()[..8]);
+        discriminator
+    }
+
+    /// Load every `Pair` account owned by the liquidity book program
+    pub fn fetch(client: Arc<RpcClient>, program_id: Pubkey) -> Result<PairIndex> {
+        let config = RpcProgramAccountsConfig {
+            filters: Some(vec![
+                RpcFilterType::DataSize(Pair::LEN as u64),
+                RpcFilterType::Memcmp(Memcmp::new_raw_bytes(0, Self::discriminator().to_vec())),
+            ]),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let accounts = client.get_program_accounts_with_config(&program_id, config)?;
+
+        let entries = accounts
+            .into_iter()
+            .filter_map(|(key, account)| match Pair::unpack(&account.data) {
+                Ok(pair) => Some(PairIndexEntry {
+                    pair: key,
+                    mint_x: pair.token_mint_x,
+                    mint_y: pair.token_mint_y,
+                    bin_step: pair.bin_step,
+                    active_id: pair.active_id,
+                    symbol_x: None,
+                    symbol_y: None,
+                }),
+                Err(e) => {
+                    warn!("⚠️ Skipping undecodable pair account {}: {}", key, e);
+                    None
+                }
+            })
+            .collect();
+
+        Ok(PairIndex { entries })
+    }
+
+    pub fn mints(&self) -> Vec<Pubkey> {
+        let mut mints: Vec<Pubkey> = self
+            .entries
+            .iter()
+            .flat_map(|entry| [entry.mint_x, entry.mint_y])
+            .collect();
+        mints.sort();
+        mints.dedup();
+        mints
+    }
+
+    pub fn search(&self, query: &PairSearch) -> Vec<PairIndexEntry> {
+        let symbol = query.symbol.as_ref().map(|symbol| symbol.to_uppercase());
+
+        self.entries
+            .iter()
+            .filter(|entry| {
+                query
+                    .mint
+                    .is_none_or(|mint| entry.mint_x == mint || entry.mint_y == mint)
+            })
+            .filter(|entry| {
+                query
+                    .bin_step
+                    .is_none_or(|bin_step| entry.bin_step == bin_step)
+            })
+            .filter(|entry| {
+                symbol.as_ref().is_none_or(|symbol| {
+                    [&entry.symbol_x, &entry.symbol_y]
+                        .into_iter()
+                        .flatten()
+                        .any(|s| s.to_uppercase() == *symbol)
+                })
+            })
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(
+        mint_x: Pubkey,
+        mint_y: Pubkey,
+        bin_step: u8,
+        symbols: [Option<&str>; 2],
+    ) -> PairIndexEntry {
+        PairIndexEntry {
+            pair: Pubkey::new_unique(),
+            mint_x,
+            mint_y,
+            bin_step,
+            active_id: 8_388_608,
+            symbol_x: symbols[0].map(str::to_string),
+            symbol_y: symbols[1].map(str::to_string),
+        }
+    }
+
+    fn pairs(index: &PairIndex, query: &PairSearch) -> Vec<Pubkey> {
+        index.search(query).iter().map(|entry| entry.pair).collect()
+    }
+
+    #[test]
+    fn search_filters_by_mint_symbol_and_bin_step() {
+        let [sol, usdc, bonk] = [(); 3].map(|_| Pubkey::new_unique());
+        let index = PairIndex {
+            entries: vec![
+                entry(sol, usdc, 10, [Some("SOL"), Some("USDC")]),
+                entry(sol, usdc, 25, [Some("SOL"), Some("USDC")]),
+                entry(bonk, sol, 100, [Some("Bonk"), Some("SOL")]),
+                entry(bonk, usdc, 100, [None, None]),
+            ],
+        };
+        let pair = |i: usize| index.entries[i].pair;
+
+        assert_eq!(pairs(&index, &PairSearch::default()).len(), 4);
+
+        // Either side of the pair
+        let by_mint = PairSearch {
+            mint: Some(sol),
+            ..Default::default()
+        };
+        assert_eq!(pairs(&index, &by_mint), [pair(0), pair(1), pair(2)]);
+
+        // Case insensitive, pairs without resolved symbols never match
+        let by_symbol = PairSearch {
+            symbol: Some("bonk".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(pairs(&index, &by_symbol), [pair(2)]);
+
+        let combined = PairSearch {
+            mint: Some(usdc),
+            symbol: Some("sol".to_string()),
+            bin_step: Some(25),
+        };
+        assert_eq!(pairs(&index, &combined), [pair(1)]);
+
+        let none = PairSearch {
+            bin_step: Some(1),
+            ..Default::default()
+        };
+        assert!(pairs(&index, &none).is_empty());
+    }
+
+    #[test]
+    fn mints_are_sorted_and_unique() {
+        let [a, b, c] = [(); 3].map(|_| Pubkey::new_unique());
+        let index = PairIndex {
+            entries: vec![
+                entry(c, a, 10, [None, None]),
+                entry(a, b, 10, [None, None]),
+                entry(b, c, 25, [None, None]),
+            ],
+        };
+
+        let mut expected = vec![a, b, c];
+        expected.sort();
+        assert_eq!(index.mints(), expected);
+    }
+
+    #[test]
+    fn discriminator_is_the_anchor_account_hash() {
+        assert_eq!(
+            PairIndex::discriminator().as_slice(),
+            &hash(b"account:Pair").to_bytes()[..8]
+        );
+    }
+}
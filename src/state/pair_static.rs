@@ -0,0 +1,16 @@
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+
+/// Pair fields that never change once the pair is created
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PairStatic {
+    pub pair: Pubkey,
+    pub program_id: Pubkey,
+    pub mint_x: Pubkey,
+    pub mint_y: Pubkey,
+    pub vault_x: Pubkey,
+    pub vault_y: Pubkey,
+    pub token_program_x: Pubkey,
+    pub token_program_y: Pubkey,
+    pub bin_step: u8,
+}
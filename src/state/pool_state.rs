@@ -1,60 +1,77 @@
-use std::sync::Arc;
-
-use jupiter_amm_interface::KeyedAccount;
-use saros_sdk::state::bin_array::BinArray;
+use anyhow::{Context, Result};
+use saros_sdk::state::bin_array::{Bin, BinArray};
 use saros_sdk::state::pair::Pair;
 use saros_sdk::utils::helper::get_pair_bin_array;
-use solana_client::rpc_client::RpcClient;
 use solana_sdk::{program_pack::Pack, pubkey::Pubkey};
 
+use super::SlotAccounts;
+
+/// Number of bins stored in a single `BinArray` account.
+pub const BIN_ARRAY_SIZE: u32 = 256;
+
 pub struct PoolState {
     pub mint_x: Pubkey,
     pub mint_y: Pubkey,
-    pub _active_bin_array_lower: BinArray,
-    pub _active_bin_array_upper: BinArray,
+    pub bin_step: u8,
+    pub active_id: u32,
+    pub active_bin_array_lower: BinArray,
+    pub active_bin_array_upper: BinArray,
 }
 
 impl PoolState {
     pub fn new(
-        mint_x: Pubkey,
-        mint_y: Pubkey,
-        _active_bin_array_lower: BinArray,
-        _active_bin_array_upper: BinArray,
+        pair: &Pair,
+        active_bin_array_lower: BinArray,
+        active_bin_array_upper: BinArray,
     ) -> Self {
         PoolState {
-            mint_x,
-            mint_y,
-            _active_bin_array_lower,
-            _active_bin_array_upper,
+            mint_x: pair.token_mint_x,
+            mint_y: pair.token_mint_y,
+            bin_step: pair.bin_step,
+            active_id: pair.active_id,
+            active_bin_array_lower,
+            active_bin_array_upper,
         }
     }
 
-    pub fn fetch(client: Arc<RpcClient>, pair_account: KeyedAccount) -> Self {
-        let pair_state =
-            Pair::unpack(&pair_account.account.data).expect("Failed to unpack pair account");
-        let bin_array_index = pair_state.bin_array_index();
-        let (bin_array_lower_key, bin_array_upper_key) = get_pair_bin_array(
-            bin_array_index,
-            &pair_account.key,
-            &pair_account.account.owner,
-        );
-
-        let bin_array_lower_account = client
-            .get_account(&bin_array_lower_key)
-            .expect("Failed to get bin array lower account");
-        let bin_array_lower = BinArray::unpack(&bin_array_lower_account.data)
-            .expect("Failed to unpack bin array lower account");
-        let bin_array_upper_account = client
-            .get_account(&bin_array_upper_key)
-            .expect("Failed to get bin array upper account");
-        let bin_array_upper = BinArray::unpack(&bin_array_upper_account.data)
-            .expect("Failed to unpack bin array upper account");
-
-        PoolState::new(
-            pair_state.token_mint_x,
-            pair_state.token_mint_y,
-            bin_array_lower,
-            bin_array_upper,
-        )
+    /// Bin arrays a pair's pool state is built from, around its active bin
+    pub fn bin_array_keys(pair_key: &Pubkey, program_id: &Pubkey, pair: &Pair) -> [Pubkey; 2] {
+        let (bin_array_lower_key, bin_array_upper_key) =
+            get_pair_bin_array(pair.bin_array_index(), pair_key, program_id);
+        [bin_array_lower_key, bin_array_upper_key]
+    }
+
+    /// `None` when either bin array is not initialized yet, which happens
+    /// until liquidity is first added next to the active bin
+    pub fn from_accounts(
+        pair: &Pair,
+        bin_array_keys: [Pubkey; 2],
+        accounts: &SlotAccounts,
+    ) -> Result<Option<Self>> {
+        let [bin_array_lower_key, bin_array_upper_key] = bin_array_keys;
+        let (Some(lower), Some(upper)) = (
+            accounts.try_get(&bin_array_lower_key),
+            accounts.try_get(&bin_array_upper_key),
+        ) else {
+            return Ok(None);
+        };
+
+        let bin_array_lower =
+            BinArray::unpack(&lower.data).context("Failed to unpack bin array lower account")?;
+        let bin_array_upper =
+            BinArray::unpack(&upper.data).context("Failed to unpack bin array upper account")?;
+
+        Ok(Some(PoolState::new(pair, bin_array_lower, bin_array_upper)))
+    }
+
+    /// Look up a bin by its absolute id in the two loaded bin arrays.
+    pub fn bin(&self, bin_id: u32) -> Option<&Bin> {
+        let array_index = bin_id / BIN_ARRAY_SIZE;
+        let offset = (bin_id % BIN_ARRAY_SIZE) as usize;
+
+        [&self.active_bin_array_lower, &self.active_bin_array_upper]
+            .into_iter()
+            .find(|bin_array| bin_array.index == array_index)
+            .map(|bin_array| &bin_array.bins[offset])
     }
 }
@@ -0,0 +1,105 @@
+use std::{collections::HashMap, sync::Arc};
+
+use anyhow::Result;
+use saros_sdk::state::bin_array::BinArray;
+use serde::Serialize;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{program_pack::Pack, pubkey::Pubkey};
+
+use crate::state::{bin_array_key, decode_account, LiquidityPosition, BIN_ARRAY_SIZE};
+
+/// A position's claim on one bin's reserves
+#[derive(Debug, Clone, Serialize)]
+pub struct PositionBin {
+    pub bin_id: u32,
+    /// Liquidity shares, as a string since they exceed JSON number precision
+    pub shares: String,
+    /// Fraction of the bin's total supply the position owns
+    pub share_of_bin: f64,
+    pub amount_x: u64,
+    pub amount_y: u64,
+}
+
+/// A position with its current token amounts, bin by bin
+pub struct PositionState {
+    pub key: Pubkey,
+    pub pair: Pubkey,
+    pub lower_bin_id: u32,
+    pub upper_bin_id: u32,
+    pub bins: Vec<PositionBin>,
+    pub amount_x: u64,
+    pub amount_y: u64,
+}
+
+impl PositionState {
+    /// Read a position and the bin arrays it spans, valuing each bin's
+    /// shares against the bin's reserves.
+    pub fn fetch(client: Arc<RpcClient>, position_key: Pubkey) -> Result<PositionState> {
+        let account = client.get_account(&position_key)?;
+        let position: LiquidityPosition = decode_account(&account.data, "position")?;
+
+        let lower_bin_id = position.lower_bin_id.max(0) as u32;
+        let upper_bin_id = position.upper_bin_id.max(position.lower_bin_id).max(0) as u32;
+
+        let indexes: Vec<u32> =
+            (lower_bin_id / BIN_ARRAY_SIZE..=upper_bin_id / BIN_ARRAY_SIZE).collect();
+        let keys: Vec<Pubkey> = indexes
+            .iter()
+            .map(|index| bin_array_key(&position.pair, *index, &account.owner))
+            .collect();
+        let bin_arrays: HashMap<u32, BinArray> = client
+            .get_multiple_accounts(&keys)?
+            .into_iter()
+            .zip(indexes)
+            .filter_map(|(account, index)| {
+                let bin_array = BinArray::unpack(&account?.data).ok()?;
+                Some((index, bin_array))
+            })
+            .collect();
+
+        let bins: Vec<PositionBin> = (lower_bin_id..=upper_bin_id)
+            .zip(position.liquidity_shares.iter())
+            .filter(|(_, shares)| **shares > 0)
+            .map(|(bin_id, shares)| {
+                let bin = bin_arrays
+                    .get(&(bin_id / BIN_ARRAY_SIZE))
+                    .map(|bin_array| &bin_array.bins[(bin_id % BIN_ARRAY_SIZE) as usize]);
+                let (amount_x, amount_y, share_of_bin) = match bin {
+                    Some(bin) if bin.total_supply > 0 => (
+                        mul_div(bin.reserve_x, *shares, bin.total_supply),
+                        mul_div(bin.reserve_y, *shares, bin.total_supply),
+                        *shares as f64 / bin.total_supply as f64,
+                    ),
+                    _ => (0, 0, 0.0),
+                };
+
+                PositionBin {
+                    bin_id,
+                    shares: shares.to_string(),
+                    share_of_bin,
+                    amount_x,
+                    amount_y,
+                }
+            })
+            .collect();
+
+        Ok(PositionState {
+            key: position_key,
+            pair: position.pair,
+            lower_bin_id,
+            upper_bin_id,
+            amount_x: bins.iter().map(|bin| bin.amount_x).sum(),
+            amount_y: bins.iter().map(|bin| bin.amount_y).sum(),
+            bins,
+        })
+    }
+}
+
+/// `amount * numerator / denominator`, rounded down like the program does on withdrawal
+fn mul_div(amount: u64, numerator: u128, denominator: u128) -> u64 {
+    let product = (amount as u128).checked_mul(numerator).map_or_else(
+        || (amount as f64 * numerator as f64 / denominator as f64) as u128,
+        |product| product / denominator,
+    );
+    u64::try_from(product).unwrap_or(u64::MAX)
+}
@@ -0,0 +1,60 @@
+use std::collections::HashMap;
+
+use ahash::RandomState;
+use anyhow::{Context, Result};
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::{rpc_client::RpcClient, rpc_config::RpcAccountInfoConfig};
+use solana_sdk::{account::Account, pubkey::Pubkey};
+
+/// Accounts by key, in the map type `Amm::update` takes
+pub type AccountMap = HashMap<Pubkey, Account, RandomState>;
+
+/// Accounts read together in one `getMultipleAccounts`, all from the same slot
+pub struct SlotAccounts {
+    pub slot: u64,
+    accounts: HashMap<Pubkey, Account>,
+}
+
+impl SlotAccounts {
+    /// Fetch `keys` in one call, rejected by the node if it is behind `min_context_slot`
+    pub fn fetch(
+        client: &RpcClient,
+        keys: &[Pubkey],
+        min_context_slot: Option<u64>,
+    ) -> Result<SlotAccounts> {
+        let config = RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            commitment: Some(client.commitment()),
+            min_context_slot,
+            ..Default::default()
+        };
+
+        let response = client.get_multiple_accounts_with_config(keys, config)?;
+
+        let accounts = keys
+            .iter()
+            .zip(response.value)
+            .filter_map(|(key, account)| Some((*key, account?)))
+            .collect();
+
+        Ok(SlotAccounts {
+            slot: response.context.slot,
+            accounts,
+        })
+    }
+
+    pub fn get(&self, key: &Pubkey) -> Result<&Account> {
+        self.accounts
+            .get(key)
+            .with_context(|| format!("Account {} not found", key))
+    }
+
+    /// Accounts that do not exist are left out of the response
+    pub fn try_get(&self, key: &Pubkey) -> Option<&Account> {
+        self.accounts.get(key)
+    }
+
+    pub fn into_map<S: std::hash::BuildHasher + Default>(self) -> HashMap<Pubkey, Account, S> {
+        self.accounts.into_iter().collect()
+    }
+}
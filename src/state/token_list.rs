@@ -0,0 +1,52 @@
+use std::{collections::HashMap, path::Path, str::FromStr};
+
+use anyhow::Result;
+use serde::Deserialize;
+use solana_sdk::pubkey::Pubkey;
+use tracing::info;
+
+/// One token from a Solana or Jupiter token list
+#[derive(Debug, Clone, Deserialize)]
+pub struct TokenListEntry {
+    pub address: String,
+    pub symbol: String,
+    pub name: String,
+    #[serde(default, rename = "logoURI")]
+    pub logo_uri: Option<String>,
+}
+
+/// Solana token lists wrap tokens in an object, Jupiter serves a bare array
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum TokenListFile {
+    Solana { tokens: Vec<TokenListEntry> },
+    Jupiter(Vec<TokenListEntry>),
+}
+
+/// Local token list used when a mint has no on-chain metadata
+#[derive(Debug, Default)]
+pub struct TokenList {
+    tokens: HashMap<Pubkey, TokenListEntry>,
+}
+
+impl TokenList {
+    pub fn load(path: &Path) -> Result<TokenList> {
+        let file = std::fs::read_to_string(path)?;
+        let entries = match serde_json::from_str::<TokenListFile>(&file)? {
+            TokenListFile::Solana { tokens } => tokens,
+            TokenListFile::Jupiter(tokens) => tokens,
+        };
+
+        let tokens = entries
+            .into_iter()
+            .filter_map(|entry| Some((Pubkey::from_str(&entry.address).ok()?, entry)))
+            .collect::<HashMap<_, _>>();
+        info!("📜 Loaded {} tokens from {}", tokens.len(), path.display());
+
+        Ok(TokenList { tokens })
+    }
+
+    pub fn get(&self, mint: &Pubkey) -> Option<&TokenListEntry> {
+        self.tokens.get(mint)
+    }
+}
@@ -1,22 +1,112 @@
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 
 use anyhow::Result;
 use mpl_token_metadata::accounts::Metadata;
+use serde::{Deserialize, Serialize};
 use solana_client::rpc_client::RpcClient;
-use solana_sdk::{program_pack::Pack, pubkey::Pubkey};
+use solana_sdk::{account::Account, program_pack::Pack, pubkey::Pubkey};
 use spl_token_2022_interface::{
-    extension::{BaseStateWithExtensions, StateWithExtensions},
+    extension::{
+        interest_bearing_mint::InterestBearingConfig,
+        metadata_pointer::MetadataPointer,
+        transfer_fee::{TransferFee, TransferFeeConfig},
+        transfer_hook::TransferHook,
+        BaseStateWithExtensions, StateWithExtensions,
+    },
     state::Mint as Mint2022,
 };
 
 use spl_token_metadata_interface::state::TokenMetadata;
-use tracing::info;
+use tracing::{info, warn};
 
-#[derive(Clone)]
+use crate::state::{TokenList, TokenListEntry};
+
+/// Where a token's name and symbol were resolved from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MetadataSource {
+    OnChain,
+    TokenList,
+    Placeholder,
+}
+
+/// One side of a Token-2022 transfer fee schedule, as stored in the mint
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct TransferFeeRate {
+    pub epoch: u64,
+    pub maximum_fee: u64,
+    pub basis_points: u16,
+}
+
+impl From<&TransferFee> for TransferFeeRate {
+    fn from(fee: &TransferFee) -> Self {
+        TransferFeeRate {
+            epoch: u64::from(fee.epoch),
+            maximum_fee: u64::from(fee.maximum_fee),
+            basis_points: u16::from(fee.transfer_fee_basis_points),
+        }
+    }
+}
+
+impl From<TransferFeeRate> for TransferFee {
+    fn from(rate: TransferFeeRate) -> Self {
+        TransferFee {
+            epoch: rate.epoch.into(),
+            maximum_fee: rate.maximum_fee.into(),
+            transfer_fee_basis_points: rate.basis_points.into(),
+        }
+    }
+}
+
+/// Token-2022 `TransferFeeConfig` rates, the newer one applies from its epoch on
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct TransferFeeSchedule {
+    pub older: TransferFeeRate,
+    pub newer: TransferFeeRate,
+}
+
+impl TransferFeeSchedule {
+    /// The extension again, so fees come from the token program's own math
+    pub fn config(&self) -> TransferFeeConfig {
+        TransferFeeConfig {
+            older_transfer_fee: self.older.into(),
+            newer_transfer_fee: self.newer.into(),
+            ..Default::default()
+        }
+    }
+}
+
+impl From<&TransferFeeConfig> for TransferFeeSchedule {
+    fn from(config: &TransferFeeConfig) -> Self {
+        TransferFeeSchedule {
+            older: (&config.older_transfer_fee).into(),
+            newer: (&config.newer_transfer_fee).into(),
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
 pub struct TokenMeta {
     pub mint: Pubkey,
     pub symbol: String,
     pub decimals: u8,
+    pub name: String,
+    /// Off-chain JSON metadata URI
+    pub uri: String,
+    pub update_authority: Option<Pubkey>,
+    pub mint_authority: Option<Pubkey>,
+    pub freeze_authority: Option<Pubkey>,
+    pub supply: u64,
+    pub token_program: Pubkey,
+    /// Token-2022 `TransferFeeConfig` extension
+    pub transfer_fee: Option<TransferFeeSchedule>,
+    /// Token-2022 `InterestBearingConfig` current rate, in basis points
+    pub interest_rate_bps: Option<i16>,
+    /// Token-2022 `TransferHook` program, transfers need its extra accounts
+    pub transfer_hook_program: Option<Pubkey>,
+    /// Logo from the token list, when metadata came from there
+    pub logo_uri: Option<String>,
+    pub source: MetadataSource,
 }
 
 impl Default for TokenMeta {
@@ -25,86 +115,438 @@ impl Default for TokenMeta {
             mint: Pubkey::default(),
             symbol: "UNKNOWN".to_string(),
             decimals: 0,
+            name: String::new(),
+            uri: String::new(),
+            update_authority: None,
+            mint_authority: None,
+            freeze_authority: None,
+            supply: 0,
+            token_program: spl_token::ID,
+            transfer_fee: None,
+            interest_rate_bps: None,
+            transfer_hook_program: None,
+            logo_uri: None,
+            source: MetadataSource::Placeholder,
+        }
+    }
+}
+
+/// Name, symbol, URI and update authority, wherever the metadata lives
+struct OnChainMetadata {
+    name: String,
+    symbol: String,
+    uri: String,
+    update_authority: Option<Pubkey>,
+}
+
+impl OnChainMetadata {
+    /// Shortened mint (`AbCd…WxYz`) for tokens nobody has described
+    fn placeholder(mint: &Pubkey) -> Self {
+        let mint = mint.to_string();
+        let short = format!("{}…{}", &mint[..4], &mint[mint.len() - 4..]);
+
+        OnChainMetadata {
+            name: short.clone(),
+            symbol: short,
+            uri: String::new(),
+            update_authority: None,
+        }
+    }
+}
+
+impl From<&TokenListEntry> for OnChainMetadata {
+    fn from(entry: &TokenListEntry) -> Self {
+        OnChainMetadata {
+            name: entry.name.clone(),
+            symbol: entry.symbol.clone(),
+            uri: String::new(),
+            update_authority: None,
+        }
+    }
+}
+
+impl From<Metadata> for OnChainMetadata {
+    fn from(metadata: Metadata) -> Self {
+        OnChainMetadata {
+            name: trim_padding(&metadata.name),
+            symbol: trim_padding(&metadata.symbol),
+            uri: trim_padding(&metadata.uri),
+            update_authority: Some(metadata.update_authority),
+        }
+    }
+}
+
+impl From<TokenMetadata> for OnChainMetadata {
+    fn from(metadata: TokenMetadata) -> Self {
+        OnChainMetadata {
+            name: metadata.name,
+            symbol: metadata.symbol,
+            uri: metadata.uri,
+            update_authority: metadata.update_authority.into(),
         }
     }
 }
 
+/// Where a mint's name and symbol live, once the mint itself is decoded
+enum MetadataLookup {
+    /// Embedded in the mint, or nowhere
+    Resolved(Option<OnChainMetadata>),
+    /// In another account: the Metaplex PDA or a `MetadataPointer` target
+    Account(Pubkey),
+}
+
+/// Most accounts `getMultipleAccounts` returns per call
+const MAX_MULTIPLE_ACCOUNTS: usize = 100;
+
 impl TokenMeta {
-    pub fn fetch(client: Arc<RpcClient>, mint_key: Pubkey) -> Result<TokenMeta> {
+    /// Resolve a mint's metadata: on-chain first, then the local token list,
+    /// then a shortened-mint placeholder.
+    pub fn fetch(
+        client: Arc<RpcClient>,
+        mint_key: Pubkey,
+        token_list: Arc<TokenList>,
+    ) -> Result<TokenMeta> {
         let token_account = client.get_account(&mint_key)?;
+        let (token_meta, lookup) = Self::from_mint_account(mint_key, &token_account)?;
+
+        let on_chain = match lookup {
+            MetadataLookup::Resolved(on_chain) => on_chain,
+            MetadataLookup::Account(address) => get_optional_account(&client, &address)?
+                .and_then(|account| decode_metadata_account(&address, &account)),
+        };
+
+        Ok(token_meta.with_metadata(on_chain, &token_list))
+    }
+
+    /// Resolve many mints with batched reads: one `getMultipleAccounts` per
+    /// hundred mints, then one per hundred metadata accounts. Mints that are
+    /// missing or not SPL tokens are left out.
+    pub fn fetch_many(
+        client: &RpcClient,
+        mint_keys: &[Pubkey],
+        token_list: &TokenList,
+    ) -> Result<Vec<TokenMeta>> {
+        let mut pending = vec![];
+        for chunk in mint_keys.chunks(MAX_MULTIPLE_ACCOUNTS) {
+            let accounts = client.get_multiple_accounts(chunk)?;
+            for (mint_key, account) in chunk.iter().zip(accounts) {
+                let Some(account) = account else {
+                    warn!("⚠️ Mint {} not found", mint_key);
+                    continue;
+                };
+                match Self::from_mint_account(*mint_key, &account) {
+                    Ok(decoded) => pending.push(decoded),
+                    Err(e) => warn!("⚠️ Skipping mint {}: {}", mint_key, e),
+                }
+            }
+        }
+
+        let metadata_keys: Vec<Pubkey> = pending
+            .iter()
+            .filter_map(|(_, lookup)| match lookup {
+                MetadataLookup::Account(address) => Some(*address),
+                MetadataLookup::Resolved(_) => None,
+            })
+            .collect();
+        let mut metadata_accounts = HashMap::new();
+        for chunk in metadata_keys.chunks(MAX_MULTIPLE_ACCOUNTS) {
+            let accounts = client.get_multiple_accounts(chunk)?;
+            for (address, account) in chunk.iter().zip(accounts) {
+                if let Some(account) = account {
+                    metadata_accounts.insert(*address, account);
+                }
+            }
+        }
+
+        Ok(pending
+            .into_iter()
+            .map(|(token_meta, lookup)| {
+                let on_chain = match lookup {
+                    MetadataLookup::Resolved(on_chain) => on_chain,
+                    MetadataLookup::Account(address) => metadata_accounts
+                        .get(&address)
+                        .and_then(|account| decode_metadata_account(&address, account)),
+                };
+                token_meta.with_metadata(on_chain, token_list)
+            })
+            .collect())
+    }
+
+    /// Decode the mint and find where its metadata lives
+    fn from_mint_account(
+        mint_key: Pubkey,
+        token_account: &Account,
+    ) -> Result<(TokenMeta, MetadataLookup)> {
         match token_account.owner {
             spl_token::ID => {
                 let mint_account = spl_token::state::Mint::unpack(&token_account.data)?;
-                let token_meta = Self::get_spl_token_metadata(client, &mint_key)?;
-                return Ok(TokenMeta {
+                let (metadata_pda, _) = Pubkey::find_program_address(
+                    &[
+                        b"metadata",
+                        mpl_token_metadata::ID.as_ref(),
+                        mint_key.as_ref(),
+                    ],
+                    &mpl_token_metadata::ID,
+                );
+
+                let token_meta = TokenMeta {
                     mint: mint_key,
                     decimals: mint_account.decimals,
-                    symbol: token_meta.symbol,
+                    mint_authority: mint_account.mint_authority.into(),
+                    freeze_authority: mint_account.freeze_authority.into(),
+                    supply: mint_account.supply,
                     ..Default::default()
-                });
+                };
+                Ok((token_meta, MetadataLookup::Account(metadata_pda)))
             }
             spl_token_2022::ID => {
                 let mint_state = StateWithExtensions::<Mint2022>::unpack(&token_account.data)?;
-                // Get all extension types enabled on this mint
-                // let extension_types = mint_state.get_extension_types()?;
-                // info!("\nExtensions enabled: {:?}", extension_types);
-
-                // Deserialize the MetadataPointer extension data
-                // let metadata_pointer = mint_state.get_extension::<MetadataPointer>()?;
-                // info!("\n{:#?}", metadata_pointer);
-
-                // Deserialize the TokenMetadata extension data (variable-length)
-                let token_metadata = mint_state.get_variable_len_extension::<TokenMetadata>()?;
-                info!("\n{:#?}", token_metadata);
+                let lookup = Self::token_2022_metadata_lookup(&mint_key, &mint_state);
 
-                // info!("Fetched token metadata: {:?}", token_meta);
-                return Ok(TokenMeta {
+                let token_meta = TokenMeta {
                     mint: mint_key,
                     decimals: mint_state.base.decimals,
-                    symbol: token_metadata.symbol.clone(),
+                    mint_authority: mint_state.base.mint_authority.into(),
+                    freeze_authority: mint_state.base.freeze_authority.into(),
+                    supply: mint_state.base.supply,
+                    token_program: spl_token_2022::ID,
+                    transfer_fee: mint_state
+                        .get_extension::<TransferFeeConfig>()
+                        .ok()
+                        .map(TransferFeeSchedule::from),
+                    interest_rate_bps: mint_state
+                        .get_extension::<InterestBearingConfig>()
+                        .ok()
+                        .map(|config| i16::from(config.current_rate)),
+                    transfer_hook_program: mint_state
+                        .get_extension::<TransferHook>()
+                        .ok()
+                        .and_then(|hook| Option::<Pubkey>::from(hook.program_id)),
                     ..Default::default()
-                });
+                };
+                Ok((token_meta, lookup))
+            }
+            _ => Err(anyhow::anyhow!("Account is not owned by SPL Token program")),
+        }
+    }
+
+    /// The mint's own `TokenMetadata` extension, or the account its
+    /// `MetadataPointer` points at when that is another account
+    fn token_2022_metadata_lookup(
+        mint_key: &Pubkey,
+        mint_state: &StateWithExtensions<Mint2022>,
+    ) -> MetadataLookup {
+        let metadata_address = mint_state
+            .get_extension::<MetadataPointer>()
+            .ok()
+            .and_then(|pointer| Option::<Pubkey>::from(pointer.metadata_address));
+
+        match metadata_address {
+            Some(address) if address != *mint_key => MetadataLookup::Account(address),
+            _ => MetadataLookup::Resolved(
+                mint_state
+                    .get_variable_len_extension::<TokenMetadata>()
+                    .ok()
+                    .map(Into::into),
+            ),
+        }
+    }
+
+    /// Fill in name and symbol from on-chain metadata, falling back to the
+    /// token list and then a placeholder
+    fn with_metadata(
+        mut self,
+        on_chain: Option<OnChainMetadata>,
+        token_list: &TokenList,
+    ) -> TokenMeta {
+        let metadata = match on_chain {
+            Some(metadata) => {
+                self.source = MetadataSource::OnChain;
+                metadata
             }
-            _ => {
-                return Err(anyhow::anyhow!("Account is not owned by SPL Token program"));
+            None => {
+                info!("No on-chain metadata for {}", self.mint);
+                match token_list.get(&self.mint) {
+                    Some(entry) => {
+                        self.source = MetadataSource::TokenList;
+                        self.logo_uri = entry.logo_uri.clone();
+                        entry.into()
+                    }
+                    None => {
+                        self.source = MetadataSource::Placeholder;
+                        OnChainMetadata::placeholder(&self.mint)
+                    }
+                }
             }
+        };
+
+        self.name = metadata.name;
+        self.symbol = metadata.symbol;
+        self.uri = metadata.uri;
+        self.update_authority = metadata.update_authority;
+        self
+    }
+
+    /// Transfer fee withheld when moving `amount` of this token in `epoch`
+    pub fn transfer_fee(&self, epoch: u64, amount: u64) -> u64 {
+        self.transfer_fee
+            .and_then(|schedule| schedule.config().calculate_epoch_fee(epoch, amount))
+            .unwrap_or(0)
+    }
+
+    /// Amount to send so that `post_fee_amount` arrives after the transfer fee
+    pub fn pre_fee_amount(&self, epoch: u64, post_fee_amount: u64) -> u64 {
+        let fee = self
+            .transfer_fee
+            .and_then(|schedule| {
+                schedule
+                    .config()
+                    .calculate_inverse_epoch_fee(epoch, post_fee_amount)
+            })
+            .unwrap_or(0);
+        post_fee_amount.saturating_add(fee)
+    }
+
+    pub fn transfer_fee_bps(&self, epoch: u64) -> u16 {
+        self.transfer_fee
+            .map(|schedule| {
+                u16::from(
+                    schedule
+                        .config()
+                        .get_epoch_fee(epoch)
+                        .transfer_fee_basis_points,
+                )
+            })
+            .unwrap_or(0)
+    }
+}
+
+/// `None` when the account does not exist, unlike `get_account`
+fn get_optional_account(client: &RpcClient, key: &Pubkey) -> Result<Option<Account>> {
+    Ok(client
+        .get_account_with_commitment(key, client.commitment())?
+        .value)
+}
+
+/// Metaplex metadata or a Token-2022 mint carrying `TokenMetadata`. `None`
+/// when the account does not decode.
+fn decode_metadata_account(address: &Pubkey, account: &Account) -> Option<OnChainMetadata> {
+    match account.owner {
+        mpl_token_metadata::ID => match Metadata::safe_deserialize(&mut &account.data[..]) {
+            Ok(metadata) => Some(metadata.into()),
+            Err(e) => {
+                info!("Undecodable metadata {}: {}", address, e);
+                None
+            }
+        },
+        spl_token_2022::ID => StateWithExtensions::<Mint2022>::unpack(&account.data)
+            .and_then(|pointed| pointed.get_variable_len_extension::<TokenMetadata>())
+            .ok()
+            .map(Into::into),
+        owner => {
+            info!(
+                "Unsupported metadata account {} owned by {}",
+                address, owner
+            );
+            None
         }
     }
+}
 
-    fn get_spl_token_metadata(client: Arc<RpcClient>, mint_key: &Pubkey) -> Result<Metadata> {
-        let (metadata_pda, _) = Pubkey::find_program_address(
-            &[
-                b"metadata",
-                mpl_token_metadata::ID.as_ref(),
-                mint_key.as_ref(),
-            ],
-            &mpl_token_metadata::ID,
-        );
+fn trim_padding(value: &str) -> String {
+    value.trim_end_matches('\0').to_string()
+}
 
-        let account = client.get_account_data(&metadata_pda)?;
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        let metadata = Metadata::safe_deserialize(&mut &account[..])?;
-        let symbol = String::from_utf8_lossy(metadata.symbol.as_bytes())
-            .trim_end_matches('\0')
-            .to_string();
+    /// 1% capped at 1,000,000 until epoch 10, then 5% capped at 50
+    fn token_with_fee() -> TokenMeta {
+        TokenMeta {
+            token_program: spl_token_2022::ID,
+            transfer_fee: Some(TransferFeeSchedule {
+                older: TransferFeeRate {
+                    epoch: 0,
+                    maximum_fee: 1_000_000,
+                    basis_points: 100,
+                },
+                newer: TransferFeeRate {
+                    epoch: 10,
+                    maximum_fee: 50,
+                    basis_points: 500,
+                },
+            }),
+            ..Default::default()
+        }
+    }
 
-        Ok(Metadata {
-            name: metadata.name,
-            symbol,
-            uri: metadata.uri,
-            key: metadata.key,
-            update_authority: metadata.update_authority,
-            mint: metadata.mint,
-            seller_fee_basis_points: metadata.seller_fee_basis_points,
-            creators: metadata.creators,
-            primary_sale_happened: metadata.primary_sale_happened,
-            is_mutable: metadata.is_mutable,
-            edition_nonce: metadata.edition_nonce,
-            token_standard: metadata.token_standard,
-            collection: metadata.collection,
-            uses: metadata.uses,
-            collection_details: metadata.collection_details,
-            programmable_config: metadata.programmable_config,
-        })
+    #[test]
+    fn no_fee_without_a_schedule() {
+        let token = TokenMeta::default();
+        assert_eq!(token.transfer_fee(5, 1_000_000), 0);
+        assert_eq!(token.pre_fee_amount(5, 1_000_000), 1_000_000);
+        assert_eq!(token.transfer_fee_bps(5), 0);
+    }
+
+    #[test]
+    fn fee_follows_the_epoch_rate() {
+        let token = token_with_fee();
+
+        assert_eq!(token.transfer_fee_bps(0), 100);
+        assert_eq!(token.transfer_fee_bps(9), 100);
+        assert_eq!(token.transfer_fee_bps(10), 500);
+
+        assert_eq!(token.transfer_fee(5, 0), 0);
+        assert_eq!(token.transfer_fee(5, 10_000), 100);
+        // Rounded up like the token program
+        assert_eq!(token.transfer_fee(5, 10_001), 101);
+        assert_eq!(token.transfer_fee(10, 100), 5);
+        // Capped at the maximum fee
+        assert_eq!(token.transfer_fee(10, 10_000), 50);
+    }
+
+    #[test]
+    fn pre_fee_amount_delivers_the_post_fee_amount() {
+        let token = token_with_fee();
+
+        assert_eq!(token.pre_fee_amount(5, 0), 0);
+        assert_eq!(token.pre_fee_amount(5, 9_900), 10_000);
+        assert_eq!(token.pre_fee_amount(10, 1_000), 1_050);
+
+        for (epoch, post_fee_amount) in [
+            (5, 1),
+            (5, 99),
+            (5, 9_999),
+            (5, 123_456_789),
+            (10, 1),
+            (10, 100),
+            (10, 1_000),
+            (10, 123_456_789),
+        ] {
+            let pre_fee_amount = token.pre_fee_amount(epoch, post_fee_amount);
+            assert_eq!(
+                pre_fee_amount - token.transfer_fee(epoch, pre_fee_amount),
+                post_fee_amount,
+                "epoch {} post fee amount {}",
+                epoch,
+                post_fee_amount
+            );
+        }
+    }
+
+    #[test]
+    fn schedule_round_trips_through_the_extension() {
+        let schedule = token_with_fee().transfer_fee.unwrap();
+        let round_tripped = TransferFeeSchedule::from(&schedule.config());
+
+        for (rate, expected) in [
+            (round_tripped.older, schedule.older),
+            (round_tripped.newer, schedule.newer),
+        ] {
+            assert_eq!(rate.epoch, expected.epoch);
+            assert_eq!(rate.maximum_fee, expected.maximum_fee);
+            assert_eq!(rate.basis_points, expected.basis_points);
+        }
     }
 }
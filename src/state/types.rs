@@ -1,6 +1,13 @@
+use anyhow::{bail, Result};
+use jupiter_amm_interface::SwapMode;
 use serde::{Deserialize, Serialize};
 use solana_sdk::pubkey::Pubkey;
 
+use crate::{
+    dlmm::{DepositSide, StrategyParams, StrategyShape},
+    state::{MetadataSource, OffChainMeta, TokenMeta},
+};
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Status {
     #[serde(rename = "ok")]
@@ -23,6 +30,18 @@ pub struct QuoteResponse {
     pub out_amount: u64,
     pub fee_amount: u64,
     pub fee_mint: Pubkey,
+    pub spot_price: f64,
+    pub execution_price: f64,
+    pub price_impact_pct: f64,
+    pub bins_crossed: u32,
+    pub end_active_id: u32,
+    /// Token-2022 transfer fee withheld on the input transfer
+    pub input_transfer_fee: u64,
+    /// Token-2022 transfer fee withheld on the output transfer
+    pub output_transfer_fee: u64,
+    pub warnings: Vec<String>,
+    /// Slot the pair and bin arrays were read at
+    pub context_slot: u64,
 }
 impl Default for QuoteResponse {
     fn default() -> Self {
@@ -31,6 +50,15 @@ impl Default for QuoteResponse {
             out_amount: 0,
             fee_amount: 0,
             fee_mint: Pubkey::default(),
+            spot_price: 0.0,
+            execution_price: 0.0,
+            price_impact_pct: 0.0,
+            bins_crossed: 0,
+            end_active_id: 0,
+            input_transfer_fee: 0,
+            output_transfer_fee: 0,
+            warnings: vec![],
+            context_slot: 0,
         }
     }
 }
@@ -43,11 +71,170 @@ pub struct QuoteRequest {
     pub amount_in: u64,
 }
 
+#[derive(Deserialize, Debug, Clone, Copy, Default)]
+pub enum QuoteMode {
+    #[default]
+    #[serde(rename = "exact_in")]
+    ExactIn,
+    #[serde(rename = "exact_out")]
+    ExactOut,
+}
+
+impl From<QuoteMode> for SwapMode {
+    fn from(mode: QuoteMode) -> Self {
+        match mode {
+            QuoteMode::ExactIn => SwapMode::ExactIn,
+            QuoteMode::ExactOut => SwapMode::ExactOut,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct BatchQuoteItem {
+    pub pair_address: String,
+    pub input_mint: String,
+    pub amount: u64,
+    #[serde(default)]
+    pub mode: QuoteMode,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct RouteRequest {
+    pub input_mint: String,
+    pub output_mint: String,
+    pub amount: u64,
+    /// Pairs to route through, defaults to every cached pair
+    #[serde(default)]
+    pub pairs: Option<Vec<String>>,
+    /// When set, the chained swap transaction is built for this wallet
+    #[serde(default)]
+    pub signer: Option<String>,
+    #[serde(default)]
+    pub slippage_bps: Option<u16>,
+}
+
+/// Shape, range and amounts of a deposit, shared by preview and instruction
+#[derive(Deserialize, Debug)]
+pub struct LiquidityStrategy {
+    #[serde(default)]
+    pub shape: StrategyShape,
+    #[serde(default)]
+    pub side: DepositSide,
+    /// Range relative to the active bin, inclusive
+    #[serde(default)]
+    pub min_bin_offset: Option<i32>,
+    #[serde(default)]
+    pub max_bin_offset: Option<i32>,
+    /// Range as UI prices (Y per X), used when offsets are not given
+    #[serde(default)]
+    pub lower_price: Option<f64>,
+    #[serde(default)]
+    pub upper_price: Option<f64>,
+    #[serde(default)]
+    pub amount_x: u64,
+    #[serde(default)]
+    pub amount_y: u64,
+}
+
+impl LiquidityStrategy {
+    /// Resolve the range around `active_id`, `decimals_scale` converts UI
+    /// prices to raw bin prices
+    pub fn params(
+        &self,
+        bin_step: u8,
+        active_id: u32,
+        decimals_scale: f64,
+    ) -> Result<StrategyParams> {
+        let (min_bin_offset, max_bin_offset) = match (
+            self.min_bin_offset,
+            self.max_bin_offset,
+            self.lower_price,
+            self.upper_price,
+        ) {
+            (Some(min_bin_offset), Some(max_bin_offset), _, _) => (min_bin_offset, max_bin_offset),
+            (_, _, Some(lower_price), Some(upper_price)) => StrategyParams::offsets_from_prices(
+                bin_step,
+                active_id,
+                lower_price / decimals_scale,
+                upper_price / decimals_scale,
+            )?,
+            _ => bail!("Pass min_bin_offset and max_bin_offset, or lower_price and upper_price"),
+        };
+
+        Ok(StrategyParams {
+            shape: self.shape,
+            side: self.side,
+            min_bin_offset,
+            max_bin_offset,
+        })
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct LiquidityPreviewRequest {
+    pub pair_address: String,
+    #[serde(flatten)]
+    pub strategy: LiquidityStrategy,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct SendTxRequest {
+    /// Base64 encoded, fully signed transaction
+    pub transaction: String,
+    /// Overrides the configured `skipPreflight` for the submission
+    #[serde(default)]
+    pub skip_preflight: Option<bool>,
+    /// Overrides the configured RPC `maxRetries`
+    #[serde(default)]
+    pub max_retries: Option<usize>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct SimulateSignedTxRequest {
+    /// Base64 encoded transaction exactly as the wallet will send it, legacy or v0
+    pub transaction: String,
+    /// Addresses whose post-simulation account data is returned
+    #[serde(default)]
+    pub accounts: Vec<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TokenResponse {
     pub symbol: String,
-    pub mint: Pubkey,
+    pub mint: String,
     pub decimals: u8,
+    pub name: String,
+    pub uri: String,
+    pub logo: Option<String>,
+    pub description: Option<String>,
+    pub update_authority: Option<String>,
+    pub mint_authority: Option<String>,
+    pub freeze_authority: Option<String>,
+    pub supply: u64,
+    pub token_program: String,
+    pub source: MetadataSource,
+}
+
+impl TokenResponse {
+    pub fn new(meta: &TokenMeta, off_chain: Option<&OffChainMeta>) -> Self {
+        TokenResponse {
+            symbol: meta.symbol.clone(),
+            mint: meta.mint.to_string(),
+            decimals: meta.decimals,
+            name: meta.name.clone(),
+            uri: meta.uri.clone(),
+            logo: off_chain
+                .and_then(|off_chain| off_chain.image.clone())
+                .or_else(|| meta.logo_uri.clone()),
+            description: off_chain.and_then(|off_chain| off_chain.description.clone()),
+            update_authority: meta.update_authority.map(|key| key.to_string()),
+            mint_authority: meta.mint_authority.map(|key| key.to_string()),
+            freeze_authority: meta.freeze_authority.map(|key| key.to_string()),
+            supply: meta.supply,
+            token_program: meta.token_program.to_string(),
+            source: meta.source,
+        }
+    }
 }
 
 #[derive(Deserialize, Debug)]
@@ -78,3 +265,12 @@ pub struct SwapInstructionParams {
     pub min_out_amount: u64,
     pub signer: String,
 }
+
+#[derive(Deserialize, Debug)]
+pub struct AddLiquidityInstructionParams {
+    /// Existing position the liquidity is deposited into
+    pub position: String,
+    pub signer: String,
+    #[serde(flatten)]
+    pub strategy: LiquidityStrategy,
+}
@@ -0,0 +1,107 @@
+use std::{
+    fs,
+    path::PathBuf,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::Result;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use tracing::{info, warn};
+
+/// Bump whenever a persisted type changes shape, old files are then ignored
+pub const STORE_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize)]
+struct StoreFile<T> {
+    version: u32,
+    entries: Vec<StoredEntry<T>>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct StoredEntry<T> {
+    /// Unix seconds when the value was fetched from chain
+    pub updated_at: u64,
+    pub value: T,
+}
+
+impl<T> StoredEntry<T> {
+    pub fn new(value: T, age: Duration) -> Self {
+        StoredEntry {
+            updated_at: unix_now().saturating_sub(age.as_secs()),
+            value,
+        }
+    }
+
+    pub fn age(&self) -> Duration {
+        Duration::from_secs(unix_now().saturating_sub(self.updated_at))
+    }
+}
+
+/// Versioned JSON files under a local directory
+pub struct DiskStore {
+    dir: PathBuf,
+}
+
+impl DiskStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Result<DiskStore> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        Ok(DiskStore { dir })
+    }
+
+    /// Load a collection, treating missing, corrupt or outdated files as empty
+    pub fn load<T: DeserializeOwned>(&self, name: &str) -> Vec<StoredEntry<T>> {
+        let path = self.path(name);
+        let Ok(data) = fs::read(&path) else {
+            return vec![];
+        };
+
+        match serde_json::from_slice::<StoreFile<T>>(&data) {
+            Ok(file) if file.version == STORE_VERSION => {
+                info!(
+                    "💾 Loaded {} {} entries from disk",
+                    file.entries.len(),
+                    name
+                );
+                file.entries
+            }
+            Ok(file) => {
+                info!(
+                    "💾 Ignoring {} store version {} (expected {})",
+                    name, file.version, STORE_VERSION
+                );
+                vec![]
+            }
+            Err(e) => {
+                warn!("⚠️ Ignoring unreadable {} store: {}", name, e);
+                vec![]
+            }
+        }
+    }
+
+    /// Write a collection atomically (temp file + rename)
+    pub fn save<T: Serialize>(&self, name: &str, entries: Vec<StoredEntry<T>>) -> Result<()> {
+        let file = StoreFile {
+            version: STORE_VERSION,
+            entries,
+        };
+
+        let path = self.path(name);
+        let tmp_path = path.with_extension("json.tmp");
+        fs::write(&tmp_path, serde_json::to_vec(&file)?)?;
+        fs::rename(tmp_path, path)?;
+
+        Ok(())
+    }
+
+    fn path(&self, name: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", name))
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
@@ -0,0 +1,164 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+use serde_json::Value;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SolBalanceChange {
+    pub account: String,
+    pub pre: u64,
+    pub post: u64,
+    pub change: i128,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TokenBalanceChange {
+    pub account: String,
+    pub mint: String,
+    pub owner: Option<String>,
+    pub decimals: u8,
+    pub pre: u64,
+    pub post: u64,
+    pub change: i128,
+}
+
+/// Net change of one mint across every token account an owner holds
+#[derive(Debug, Clone, Serialize)]
+pub struct OwnerDelta {
+    pub owner: String,
+    pub mint: String,
+    pub change: i128,
+    pub decimals: u8,
+    pub ui_change: f64,
+}
+
+/// Account keys of a `getTransaction` result (json encoding): static keys
+/// first, then keys loaded from lookup tables, as balances and instructions
+/// index them
+pub fn transaction_account_keys(result: &Value) -> Vec<String> {
+    let meta = &result["meta"];
+    [
+        &result["transaction"]["message"]["accountKeys"],
+        &meta["loadedAddresses"]["writable"],
+        &meta["loadedAddresses"]["readonly"],
+    ]
+    .into_iter()
+    .filter_map(Value::as_array)
+    .flatten()
+    .filter_map(|key| key.as_str().map(str::to_string))
+    .collect()
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BalanceChanges {
+    pub sol: Vec<SolBalanceChange>,
+    pub tokens: Vec<TokenBalanceChange>,
+}
+
+impl BalanceChanges {
+    /// Diff the pre/post balances of a `getTransaction` result (json encoding)
+    pub fn from_transaction(result: &Value) -> BalanceChanges {
+        BalanceChanges::from_meta(&result["meta"], &transaction_account_keys(result))
+    }
+
+    pub fn from_meta(meta: &Value, account_keys: &[String]) -> BalanceChanges {
+        let account = |index: usize| account_keys.get(index).cloned().unwrap_or_default();
+
+        let pre_balances = u64_array(&meta["preBalances"]);
+        let post_balances = u64_array(&meta["postBalances"]);
+        let sol = pre_balances
+            .iter()
+            .zip(post_balances.iter())
+            .enumerate()
+            .filter(|(_, (pre, post))| pre != post)
+            .map(|(index, (pre, post))| SolBalanceChange {
+                account: account(index),
+                pre: *pre,
+                post: *post,
+                change: *post as i128 - *pre as i128,
+            })
+            .collect();
+
+        // Token accounts can be created or closed by the transaction, so either side may be missing
+        let mut token_accounts: BTreeMap<usize, (Option<&Value>, Option<&Value>)> = BTreeMap::new();
+        for (balances, is_post) in [
+            (&meta["preTokenBalances"], false),
+            (&meta["postTokenBalances"], true),
+        ] {
+            for balance in balances.as_array().into_iter().flatten() {
+                let Some(index) = balance["accountIndex"].as_u64() else {
+                    continue;
+                };
+                let entry = token_accounts.entry(index as usize).or_default();
+                if is_post {
+                    entry.1 = Some(balance);
+                } else {
+                    entry.0 = Some(balance);
+                }
+            }
+        }
+
+        let tokens = token_accounts
+            .into_iter()
+            .filter_map(|(index, (pre, post))| {
+                let balance = post.or(pre)?;
+                let pre = pre.map(token_amount).unwrap_or(0);
+                let post = post.map(token_amount).unwrap_or(0);
+                (pre != post).then(|| TokenBalanceChange {
+                    account: account(index),
+                    mint: balance["mint"].as_str().unwrap_or_default().to_string(),
+                    owner: balance["owner"].as_str().map(str::to_string),
+                    decimals: balance["uiTokenAmount"]["decimals"].as_u64().unwrap_or(0) as u8,
+                    pre,
+                    post,
+                    change: post as i128 - pre as i128,
+                })
+            })
+            .collect();
+
+        BalanceChanges { sol, tokens }
+    }
+
+    /// Sum token changes per (owner, mint), using the decimals reported by the RPC
+    pub fn owner_deltas(&self) -> Vec<OwnerDelta> {
+        let mut deltas: BTreeMap<(String, String), OwnerDelta> = BTreeMap::new();
+        for token in self.tokens.iter() {
+            let owner = token.owner.clone().unwrap_or_else(|| token.account.clone());
+            let delta = deltas
+                .entry((owner.clone(), token.mint.clone()))
+                .or_insert_with(|| OwnerDelta {
+                    owner,
+                    mint: token.mint.clone(),
+                    change: 0,
+                    decimals: token.decimals,
+                    ui_change: 0.0,
+                });
+            delta.change += token.change;
+        }
+
+        deltas
+            .into_values()
+            .filter(|delta| delta.change != 0)
+            .map(|mut delta| {
+                delta.ui_change = delta.change as f64 / 10f64.powi(delta.decimals as i32);
+                delta
+            })
+            .collect()
+    }
+}
+
+fn u64_array(value: &Value) -> Vec<u64> {
+    value
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|balance| balance.as_u64().unwrap_or(0))
+        .collect()
+}
+
+fn token_amount(balance: &Value) -> u64 {
+    balance["uiTokenAmount"]["amount"]
+        .as_str()
+        .and_then(|amount| amount.parse().ok())
+        .unwrap_or(0)
+}
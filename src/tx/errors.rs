@@ -0,0 +1,294 @@
+use serde::Serialize;
+use serde_json::Value;
+
+/// Anchor framework errors (`anchor_lang::error::ErrorCode`) a DLMM
+/// instruction can hit; program errors start at 6000, see `DLMM_ERRORS`.
+const ANCHOR_ERRORS: &[(u32, &str, &str)] = &[
+    (
+        100,
+        "InstructionMissing",
+        "8 byte instruction identifier not provided",
+    ),
+    (
+        101,
+        "InstructionFallbackNotFound",
+        "Fallback functions are not supported",
+    ),
+    (
+        102,
+        "InstructionDidNotDeserialize",
+        "The program could not deserialize the given instruction",
+    ),
+    (
+        103,
+        "InstructionDidNotSerialize",
+        "The program could not serialize the given instruction",
+    ),
+    (2000, "ConstraintMut", "A mut constraint was violated"),
+    (
+        2001,
+        "ConstraintHasOne",
+        "A has one constraint was violated",
+    ),
+    (2002, "ConstraintSigner", "A signer constraint was violated"),
+    (2003, "ConstraintRaw", "A raw constraint was violated"),
+    (2004, "ConstraintOwner", "An owner constraint was violated"),
+    (2006, "ConstraintSeeds", "A seeds constraint was violated"),
+    (
+        2009,
+        "ConstraintAssociated",
+        "An associated constraint was violated",
+    ),
+    (2011, "ConstraintClose", "A close constraint was violated"),
+    (
+        2012,
+        "ConstraintAddress",
+        "An address constraint was violated",
+    ),
+    (
+        2014,
+        "ConstraintTokenMint",
+        "A token mint constraint was violated",
+    ),
+    (
+        2015,
+        "ConstraintTokenOwner",
+        "A token owner constraint was violated",
+    ),
+    (
+        3001,
+        "AccountDiscriminatorNotFound",
+        "No 8 byte discriminator was found on the account",
+    ),
+    (
+        3002,
+        "AccountDiscriminatorMismatch",
+        "8 byte discriminator did not match what was expected",
+    ),
+    (
+        3003,
+        "AccountDidNotDeserialize",
+        "Failed to deserialize the account",
+    ),
+    (
+        3005,
+        "AccountNotEnoughKeys",
+        "Not enough account keys given to the instruction",
+    ),
+    (
+        3006,
+        "AccountNotMutable",
+        "The given account is not mutable",
+    ),
+    (
+        3007,
+        "AccountOwnedByWrongProgram",
+        "The given account is owned by a different program than expected",
+    ),
+    (3008, "InvalidProgramId", "Program ID was not as expected"),
+    (
+        3009,
+        "InvalidProgramExecutable",
+        "Program account is not executable",
+    ),
+    (3010, "AccountNotSigner", "The given account did not sign"),
+    (
+        3012,
+        "AccountNotInitialized",
+        "The program expected this account to be already initialized",
+    ),
+];
+
+/// Saros liquidity book program errors, for failures whose logs were not
+/// returned (e.g. `getSignatureStatuses`)
+const DLMM_ERRORS: &[(u32, &str, &str)] = &[
+    (6000, "InvalidProtocolFee", "Invalid protocol fee"),
+    (6001, "InvalidBinStep", "Invalid bin step"),
+    (6002, "InvalidBaseFactor", "Invalid base factor"),
+    (6003, "SlippageExceeded", "Slippage exceeded"),
+    (6004, "InsufficientLiquidity", "Insufficient liquidity"),
+    (6005, "InvalidAmountIn", "Invalid amount in"),
+    (6006, "InvalidAmountOut", "Invalid amount out"),
+    (6007, "ActiveIdOverflow", "Active id overflow"),
+    (6008, "ActiveIdUnderflow", "Active id underflow"),
+    (6009, "BinArrayIndexMismatch", "Bin array index mismatch"),
+    (6010, "InvalidBinArray", "Invalid bin array"),
+    (6011, "InvalidBinId", "Invalid bin id"),
+    (6012, "InvalidPositionRange", "Invalid position range"),
+    (6013, "PositionNotEmpty", "Position is not empty"),
+    (
+        6014,
+        "InvalidDistribution",
+        "Invalid liquidity distribution",
+    ),
+    (6015, "InvalidHook", "Invalid hook"),
+    (6016, "MathOverflow", "Math overflow"),
+    (6017, "MathUnderflow", "Math underflow"),
+];
+
+/// A failed instruction with its error resolved to a name and message
+#[derive(Debug, Clone, Serialize)]
+pub struct DecodedError {
+    pub instruction: Option<u64>,
+    pub code: Option<u32>,
+    pub name: Option<String>,
+    pub message: String,
+}
+
+impl DecodedError {
+    /// Resolve a `TransactionError` JSON value, using Anchor's error log line
+    /// for program errors when the logs are available.
+    pub fn decode(error: &Value, logs: &[String]) -> DecodedError {
+        let Some([index, instruction_error]) =
+            error["InstructionError"].as_array().map(Vec::as_slice)
+        else {
+            return DecodedError {
+                instruction: None,
+                code: None,
+                name: None,
+                message: error
+                    .as_str()
+                    .map(str::to_string)
+                    .unwrap_or_else(|| error.to_string()),
+            };
+        };
+
+        let instruction = index.as_u64();
+        let Some(code) = instruction_error["Custom"].as_u64().map(|code| code as u32) else {
+            return DecodedError {
+                instruction,
+                code: None,
+                name: instruction_error.as_str().map(str::to_string),
+                message: format!("Instruction failed: {}", instruction_error),
+            };
+        };
+
+        if let Some((name, message)) = anchor_error_from_logs(logs, code) {
+            return DecodedError {
+                instruction,
+                code: Some(code),
+                name: Some(name),
+                message,
+            };
+        }
+
+        match DLMM_ERRORS
+            .iter()
+            .chain(ANCHOR_ERRORS)
+            .find(|(known, _, _)| *known == code)
+        {
+            Some((_, name, message)) => DecodedError {
+                instruction,
+                code: Some(code),
+                name: Some(name.to_string()),
+                message: message.to_string(),
+            },
+            None => DecodedError {
+                instruction,
+                code: Some(code),
+                name: None,
+                message: format!("Custom program error {:#x}", code),
+            },
+        }
+    }
+}
+
+/// Parse `AnchorError ... Error Code: <name>. Error Number: <code>. Error Message: <message>.`
+fn anchor_error_from_logs(logs: &[String], code: u32) -> Option<(String, String)> {
+    logs.iter().find_map(|log| {
+        let error = log.split_once("AnchorError")?.1;
+        let name = error.split_once("Error Code: ")?.1.split_once(". ")?.0;
+        let number = error.split_once("Error Number: ")?.1.split_once(". ")?.0;
+        let message = error.split_once("Error Message: ")?.1.trim_end_matches('.');
+
+        (number.parse::<u32>().ok()? == code).then(|| (name.to_string(), message.to_string()))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn logs(lines: &[&str]) -> Vec<String> {
+        lines.iter().map(|line| line.to_string()).collect()
+    }
+
+    #[test]
+    fn names_program_errors_from_anchor_logs() {
+        let logs = logs(&[
+            "Program log: Instruction: Swap",
+            "Program log: AnchorError occurred. Error Code: SlippageExceeded. Error Number: 6003. Error Message: Slippage exceeded.",
+        ]);
+        let decoded = DecodedError::decode(
+            &json!({ "InstructionError": [2, { "Custom": 6003 }] }),
+            &logs,
+        );
+
+        assert_eq!(decoded.instruction, Some(2));
+        assert_eq!(decoded.code, Some(6003));
+        assert_eq!(decoded.name.as_deref(), Some("SlippageExceeded"));
+        assert_eq!(decoded.message, "Slippage exceeded");
+    }
+
+    #[test]
+    fn falls_back_to_known_anchor_errors() {
+        // A log line for another error number does not name this one
+        let logs = logs(&[
+            "Program log: AnchorError thrown in src/lib.rs:10. Error Code: Other. Error Number: 6001. Error Message: Other.",
+        ]);
+        let decoded = DecodedError::decode(
+            &json!({ "InstructionError": [0, { "Custom": 3012 }] }),
+            &logs,
+        );
+        assert_eq!(decoded.name.as_deref(), Some("AccountNotInitialized"));
+        assert_eq!(
+            decoded.message,
+            "The program expected this account to be already initialized"
+        );
+
+        let unknown =
+            DecodedError::decode(&json!({ "InstructionError": [0, { "Custom": 7000 }] }), &[]);
+        assert_eq!((unknown.code, unknown.name), (Some(7000), None));
+        assert_eq!(unknown.message, "Custom program error 0x1b58");
+    }
+
+    #[test]
+    fn names_program_errors_without_logs() {
+        let decoded =
+            DecodedError::decode(&json!({ "InstructionError": [1, { "Custom": 6003 }] }), &[]);
+
+        assert_eq!(decoded.instruction, Some(1));
+        assert_eq!(decoded.code, Some(6003));
+        assert_eq!(decoded.name.as_deref(), Some("SlippageExceeded"));
+        assert_eq!(decoded.message, "Slippage exceeded");
+    }
+
+    #[test]
+    fn decodes_builtin_and_transaction_errors() {
+        let decoded = DecodedError::decode(
+            &json!({ "InstructionError": [1, "InvalidAccountData"] }),
+            &[],
+        );
+        assert_eq!((decoded.instruction, decoded.code), (Some(1), None));
+        assert_eq!(decoded.name.as_deref(), Some("InvalidAccountData"));
+        assert_eq!(
+            decoded.message,
+            "Instruction failed: \"InvalidAccountData\""
+        );
+
+        let decoded = DecodedError::decode(&json!("BlockhashNotFound"), &[]);
+        assert_eq!((decoded.instruction, decoded.code), (None, None));
+        assert_eq!(decoded.message, "BlockhashNotFound");
+
+        let decoded = DecodedError::decode(
+            &json!({ "InsufficientFundsForRent": { "account_index": 0 } }),
+            &[],
+        );
+        assert_eq!(
+            decoded.message,
+            r#"{"InsufficientFundsForRent":{"account_index":0}}"#
+        );
+    }
+}
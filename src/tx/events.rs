@@ -0,0 +1,377 @@
+use std::collections::BTreeSet;
+
+use anchor_lang::{solana_program::hash::hash, AnchorDeserialize};
+use base64::{engine::general_purpose, Engine as _};
+use serde::Serialize;
+use serde_json::{json, Value};
+use solana_sdk::pubkey::Pubkey;
+
+/// Prefix of the self-CPI instruction Anchor uses for `emit_cpi!`
+pub const EVENT_IX_TAG_LE: [u8; 8] = 0x1d9a_cb51_2ea5_45e4u64.to_le_bytes();
+const PROGRAM_DATA_LOG: &str = "Program data: ";
+
+// Layouts follow the liquidity book program IDL
+
+#[derive(AnchorDeserialize)]
+#[borsh(crate = "anchor_lang::prelude::borsh")]
+struct BinSwapEvent {
+    pair: Pubkey,
+    swap_for_y: bool,
+    protocol_fee: u64,
+    bin_id: u32,
+    amount_in: u64,
+    amount_out: u64,
+    volatility_accumulator: u32,
+    fee: u64,
+}
+
+#[derive(AnchorDeserialize)]
+#[borsh(crate = "anchor_lang::prelude::borsh")]
+struct PositionLiquidityEvent {
+    pair: Pubkey,
+    position: Pubkey,
+    bin_ids: Vec<u32>,
+    amounts_x: Vec<u64>,
+    amounts_y: Vec<u64>,
+}
+
+#[derive(AnchorDeserialize)]
+#[borsh(crate = "anchor_lang::prelude::borsh")]
+struct PositionClaimFeesEvent {
+    pair: Pubkey,
+    position: Pubkey,
+    fee_x: u64,
+    fee_y: u64,
+}
+
+enum DlmmEvent {
+    Swap(BinSwapEvent),
+    AddLiquidity(PositionLiquidityEvent),
+    RemoveLiquidity(PositionLiquidityEvent),
+    CollectFees(PositionClaimFeesEvent),
+}
+
+impl DlmmEvent {
+    /// Decode `discriminator || borsh(event)`
+    fn decode(data: &[u8]) -> Option<DlmmEvent> {
+        let (discriminator, mut event) = data.split_at_checked(8)?;
+
+        let event = if discriminator == event_discriminator("BinSwapEvent") {
+            DlmmEvent::Swap(BinSwapEvent::deserialize(&mut event).ok()?)
+        } else if discriminator == event_discriminator("PositionIncreaseEvent") {
+            DlmmEvent::AddLiquidity(PositionLiquidityEvent::deserialize(&mut event).ok()?)
+        } else if discriminator == event_discriminator("PositionDecreaseEvent") {
+            DlmmEvent::RemoveLiquidity(PositionLiquidityEvent::deserialize(&mut event).ok()?)
+        } else if discriminator == event_discriminator("PositionClaimFeesEvent") {
+            DlmmEvent::CollectFees(PositionClaimFeesEvent::deserialize(&mut event).ok()?)
+        } else {
+            return None;
+        };
+
+        Some(event)
+    }
+
+    fn to_json(&self) -> Value {
+        match self {
+            DlmmEvent::Swap(event) => json!({
+                "type": "swap",
+                "pair": event.pair.to_string(),
+                "swap_for_y": event.swap_for_y,
+                "bin_id": event.bin_id,
+                "amount_in": event.amount_in,
+                "amount_out": event.amount_out,
+                "fee": event.fee,
+                "protocol_fee": event.protocol_fee,
+                "volatility_accumulator": event.volatility_accumulator,
+            }),
+            DlmmEvent::AddLiquidity(event) => liquidity_json("add_liquidity", event),
+            DlmmEvent::RemoveLiquidity(event) => liquidity_json("remove_liquidity", event),
+            DlmmEvent::CollectFees(event) => json!({
+                "type": "collect_fees",
+                "pair": event.pair.to_string(),
+                "position": event.position.to_string(),
+                "fee_x": event.fee_x,
+                "fee_y": event.fee_y,
+            }),
+        }
+    }
+}
+
+fn liquidity_json(kind: &str, event: &PositionLiquidityEvent) -> Value {
+    json!({
+        "type": kind,
+        "pair": event.pair.to_string(),
+        "position": event.position.to_string(),
+        "bin_ids": event.bin_ids,
+        "amounts_x": event.amounts_x,
+        "amounts_y": event.amounts_y,
+    })
+}
+
+/// Every DLMM event in a transaction, with swap totals across bins
+#[derive(Debug, Default, Serialize)]
+pub struct DecodedEvents {
+    pub events: Vec<Value>,
+    pub bins_touched: Vec<u32>,
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub fee: u64,
+    pub protocol_fee: u64,
+}
+
+impl DecodedEvents {
+    /// Decode events from `emit_cpi!` inner instructions and `emit!` log lines
+    /// of a simulation or transaction meta. Only events `program_id` emitted
+    /// count, any other program could write the same bytes.
+    ///
+    /// `account_keys` resolves inner instruction program indexes, static keys
+    /// then loaded addresses.
+    pub fn decode(
+        inner_instructions: &Value,
+        logs: &[String],
+        account_keys: &[String],
+        program_id: &Pubkey,
+    ) -> DecodedEvents {
+        let program_id = program_id.to_string();
+
+        let cpi_events = inner_instructions
+            .as_array()
+            .into_iter()
+            .flatten()
+            .flat_map(|inner| inner["instructions"].as_array().into_iter().flatten())
+            .filter(|instruction| {
+                instruction_program(instruction, account_keys) == Some(program_id.as_str())
+            })
+            .filter_map(|instruction| bs58::decode(instruction["data"].as_str()?).into_vec().ok())
+            .filter_map(|data| {
+                let event = data.strip_prefix(EVENT_IX_TAG_LE.as_slice())?;
+                DlmmEvent::decode(event)
+            });
+
+        let log_events = program_data_logs(logs, &program_id)
+            .into_iter()
+            .filter_map(|data| general_purpose::STANDARD.decode(data).ok())
+            .filter_map(|data| DlmmEvent::decode(&data));
+
+        let mut decoded = DecodedEvents::default();
+        let mut bins_touched = BTreeSet::new();
+
+        for event in cpi_events.chain(log_events) {
+            match &event {
+                DlmmEvent::Swap(swap) => {
+                    bins_touched.insert(swap.bin_id);
+                    decoded.amount_in += swap.amount_in;
+                    decoded.amount_out += swap.amount_out;
+                    decoded.fee += swap.fee;
+                    decoded.protocol_fee += swap.protocol_fee;
+                }
+                DlmmEvent::AddLiquidity(liquidity) | DlmmEvent::RemoveLiquidity(liquidity) => {
+                    bins_touched.extend(liquidity.bin_ids.iter().copied());
+                }
+                DlmmEvent::CollectFees(_) => {}
+            }
+            decoded.events.push(event.to_json());
+        }
+
+        decoded.bins_touched = bins_touched.into_iter().collect();
+        decoded
+    }
+}
+
+/// Program an inner instruction invoked, by index (json) or id (jsonParsed)
+fn instruction_program<'a>(instruction: &'a Value, account_keys: &'a [String]) -> Option<&'a str> {
+    match instruction["programIdIndex"].as_u64() {
+        Some(index) => account_keys.get(index as usize).map(String::as_str),
+        None => instruction["programId"].as_str(),
+    }
+}
+
+/// `Program data:` payloads logged while `program_id` was the executing
+/// program, following the invoke stack through CPIs
+fn program_data_logs<'a>(logs: &'a [String], program_id: &str) -> Vec<&'a str> {
+    let mut stack: Vec<&str> = vec![];
+    let mut data = vec![];
+
+    for log in logs {
+        if let Some(payload) = log.strip_prefix(PROGRAM_DATA_LOG) {
+            if stack.last() == Some(&program_id) {
+                data.push(payload);
+            }
+        } else if let Some(rest) = log.strip_prefix("Program ") {
+            let mut words = rest.split_whitespace();
+            let (Some(program), Some(action)) = (words.next(), words.next()) else {
+                continue;
+            };
+            if action == "invoke" {
+                // The depth shows where we are even if earlier lines were truncated
+                let depth = words
+                    .next()
+                    .and_then(|depth| depth.trim_matches(['[', ']']).parse::<usize>().ok())
+                    .unwrap_or(stack.len() + 1);
+                stack.truncate(depth.saturating_sub(1));
+                stack.push(program);
+            } else if action == "success" || action == "failed:" {
+                if stack.last() == Some(&program) {
+                    stack.pop();
+                }
+            }
+        }
+    }
+
+    data
+}
+
+fn event_discriminator(name: &str) -> [u8; 8] {
+    let mut discriminator = [0u8; 8];
+    discriminator.copy_from_slice(&hash(format!("event:{}", name).as_bytes()).to_bytes()[..8]);
+    discriminator
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn swap_event(pair: &Pubkey, bin_id: u32, amount_in: u64, fee: u64) -> Vec<u8> {
+        let mut data = event_discriminator("BinSwapEvent").to_vec();
+        data.extend_from_slice(pair.as_ref());
+        data.push(1);
+        data.extend_from_slice(&(fee / 10).to_le_bytes());
+        data.extend_from_slice(&bin_id.to_le_bytes());
+        data.extend_from_slice(&amount_in.to_le_bytes());
+        data.extend_from_slice(&(amount_in * 2).to_le_bytes());
+        data.extend_from_slice(&5u32.to_le_bytes());
+        data.extend_from_slice(&fee.to_le_bytes());
+        data
+    }
+
+    fn add_liquidity_event(pair: &Pubkey, bin_ids: &[u32]) -> Vec<u8> {
+        let mut data = event_discriminator("PositionIncreaseEvent").to_vec();
+        data.extend_from_slice(pair.as_ref());
+        data.extend_from_slice(Pubkey::new_unique().as_ref());
+        data.extend_from_slice(&(bin_ids.len() as u32).to_le_bytes());
+        for bin_id in bin_ids {
+            data.extend_from_slice(&bin_id.to_le_bytes());
+        }
+        for _ in 0..2 {
+            data.extend_from_slice(&(bin_ids.len() as u32).to_le_bytes());
+            for _ in bin_ids {
+                data.extend_from_slice(&100u64.to_le_bytes());
+            }
+        }
+        data
+    }
+
+    fn data_log(event: &[u8]) -> String {
+        format!(
+            "{}{}",
+            PROGRAM_DATA_LOG,
+            general_purpose::STANDARD.encode(event)
+        )
+    }
+
+    fn cpi(program_index: usize, event: &[u8]) -> Value {
+        json!({
+            "programIdIndex": program_index,
+            "data": bs58::encode([EVENT_IX_TAG_LE.as_slice(), event].concat()).into_string(),
+        })
+    }
+
+    #[test]
+    fn decodes_log_and_cpi_events() {
+        let program_id = Pubkey::new_unique();
+        let pair = Pubkey::new_unique();
+        let account_keys = vec!["payer".to_string(), program_id.to_string()];
+        let logs = vec![
+            format!("Program {} invoke [1]", program_id),
+            data_log(&swap_event(&pair, 10, 1_000, 30)),
+            data_log(&add_liquidity_event(&pair, &[12, 11])),
+            format!("Program {} success", program_id),
+        ];
+        let inner =
+            json!([{ "index": 0, "instructions": [cpi(1, &swap_event(&pair, 11, 500, 20))] }]);
+
+        let decoded = DecodedEvents::decode(&inner, &logs, &account_keys, &program_id);
+
+        assert_eq!(decoded.events.len(), 3);
+        // CPI events first, then log events
+        assert_eq!(decoded.events[0]["type"], "swap");
+        assert_eq!(decoded.events[0]["bin_id"], 11);
+        assert_eq!(decoded.events[2]["type"], "add_liquidity");
+        assert_eq!(decoded.events[2]["pair"], pair.to_string());
+        assert_eq!(decoded.bins_touched, [10, 11, 12]);
+        assert_eq!(
+            (
+                decoded.amount_in,
+                decoded.amount_out,
+                decoded.fee,
+                decoded.protocol_fee
+            ),
+            (1_500, 3_000, 50, 5)
+        );
+    }
+
+    #[test]
+    fn ignores_events_from_other_programs() {
+        let program_id = Pubkey::new_unique();
+        let other = Pubkey::new_unique();
+        let pair = Pubkey::new_unique();
+        let account_keys = vec![program_id.to_string(), other.to_string()];
+        let logs = vec![
+            format!("Program {} invoke [1]", program_id),
+            format!("Program {} invoke [2]", other),
+            data_log(&swap_event(&pair, 1, 999, 9)),
+            format!("Program {} success", other),
+            data_log(&swap_event(&pair, 2, 100, 3)),
+            format!("Program {} success", program_id),
+            format!("Program {} invoke [1]", other),
+            data_log(&swap_event(&pair, 3, 999, 9)),
+            format!("Program {} success", other),
+        ];
+        let inner =
+            json!([{ "index": 0, "instructions": [cpi(1, &swap_event(&pair, 4, 999, 9))] }]);
+
+        let decoded = DecodedEvents::decode(&inner, &logs, &account_keys, &program_id);
+
+        assert_eq!(decoded.events.len(), 1);
+        assert_eq!(decoded.bins_touched, [2]);
+        assert_eq!(decoded.amount_in, 100);
+    }
+
+    #[test]
+    fn skips_undecodable_data() {
+        let program_id = Pubkey::new_unique();
+        let pair = Pubkey::new_unique();
+        let swap = swap_event(&pair, 1, 100, 3);
+        let mut unknown = swap.clone();
+        unknown[0] ^= 0xff;
+        let logs = vec![
+            format!("Program {} invoke [1]", program_id),
+            data_log(&swap[..swap.len() - 1]),
+            data_log(&unknown),
+            data_log(&swap[..4]),
+            format!("{}not base64!", PROGRAM_DATA_LOG),
+            format!("Program {} success", program_id),
+        ];
+
+        let decoded = DecodedEvents::decode(&Value::Null, &logs, &[], &program_id);
+        assert!(decoded.events.is_empty());
+        assert!(decoded.bins_touched.is_empty());
+    }
+
+    #[test]
+    fn follows_the_invoke_depth_through_truncated_logs() {
+        let program_id = Pubkey::new_unique().to_string();
+        let other = Pubkey::new_unique().to_string();
+        // `other` never logs success, the next depth 1 invoke resets the stack
+        let logs = vec![
+            format!("Program {} invoke [1]", other),
+            "Program data: AAAA".to_string(),
+            format!("Program {} invoke [1]", program_id),
+            "Program data: BBBB".to_string(),
+            format!("Program {} failed: custom program error: 0x1", program_id),
+            "Program data: CCCC".to_string(),
+        ];
+
+        assert_eq!(program_data_logs(&logs, &program_id), ["BBBB"]);
+    }
+}
@@ -0,0 +1,47 @@
+use anyhow::{Context, Result};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    address_lookup_table::state::AddressLookupTable, message::VersionedMessage, pubkey::Pubkey,
+};
+
+/// Full account key list of a message: static keys, then the writable and
+/// readonly addresses loaded from lookup tables, matching balance indexes.
+pub fn resolve_account_keys(client: &RpcClient, message: &VersionedMessage) -> Result<Vec<Pubkey>> {
+    let mut keys = message.static_account_keys().to_vec();
+
+    let Some(lookups) = message.address_table_lookups() else {
+        return Ok(keys);
+    };
+
+    let table_keys: Vec<Pubkey> = lookups.iter().map(|lookup| lookup.account_key).collect();
+    let tables = client.get_multiple_accounts(&table_keys)?;
+
+    let mut writable = vec![];
+    let mut readonly = vec![];
+    for (lookup, account) in lookups.iter().zip(tables) {
+        let account =
+            account.with_context(|| format!("Lookup table {} not found", lookup.account_key))?;
+        let table = AddressLookupTable::deserialize(&account.data)
+            .with_context(|| format!("Invalid lookup table {}", lookup.account_key))?;
+
+        let address = |index: &u8| {
+            table
+                .addresses
+                .get(*index as usize)
+                .copied()
+                .with_context(|| {
+                    format!("Index {} out of lookup table {}", index, lookup.account_key)
+                })
+        };
+        for index in lookup.writable_indexes.iter() {
+            writable.push(address(index)?);
+        }
+        for index in lookup.readonly_indexes.iter() {
+            readonly.push(address(index)?);
+        }
+    }
+
+    keys.extend(writable);
+    keys.extend(readonly);
+    Ok(keys)
+}
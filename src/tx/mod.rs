@@ -0,0 +1,15 @@
+mod balances;
+mod errors;
+mod events;
+mod lookup;
+mod rpc;
+mod send;
+mod tracker;
+
+pub use balances::*;
+pub use errors::*;
+pub use events::*;
+pub use lookup::*;
+pub use rpc::*;
+pub use send::*;
+pub use tracker::*;
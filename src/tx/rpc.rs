@@ -0,0 +1,37 @@
+use anyhow::Result;
+use serde_json::{json, Value};
+use solana_client::{
+    client_error::{reqwest, ClientError},
+    rpc_request::{RpcError, RpcResponseErrorData},
+};
+
+/// Raw JSON-RPC call, returning the `result` field
+pub async fn rpc_call(
+    http: &reqwest::Client,
+    url: &str,
+    method: &str,
+    params: Value,
+) -> Result<Value> {
+    let request = json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": method,
+        "params": params,
+    });
+
+    let mut response: Value = http.post(url).json(&request).send().await?.json().await?;
+
+    if let Some(error) = response.get("error") {
+        let message = error["message"].as_str().unwrap_or("unknown RPC error");
+        // Keep the code so `is_transient` can tell a lagging node from a bad request
+        let rpc_error = RpcError::RpcResponseError {
+            code: error["code"].as_i64().unwrap_or_default(),
+            message: message.to_string(),
+            data: RpcResponseErrorData::Empty,
+        };
+        return Err(anyhow::Error::new(ClientError::from(rpc_error))
+            .context(format!("{} failed: {}", method, message)));
+    }
+
+    Ok(response["result"].take())
+}
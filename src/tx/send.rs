@@ -0,0 +1,86 @@
+use anyhow::{Context, Result};
+use serde_json::{json, Value};
+use solana_client::client_error::reqwest;
+
+use super::rpc_call;
+
+/// How signed transactions are submitted
+#[derive(Debug, Clone)]
+pub struct SendConfig {
+    /// Times `sendTransaction` itself is attempted before giving up
+    pub attempts: u32,
+    /// `maxRetries` passed to the RPC node, `None` leaves it to the node
+    pub max_retries: Option<usize>,
+    /// The transaction was already simulated, so the node's preflight is skipped by default
+    pub skip_preflight: bool,
+    /// Signatures tracked at once for `/api/tx/{signature}/events`
+    pub max_trackers: usize,
+}
+
+impl Default for SendConfig {
+    fn default() -> Self {
+        SendConfig {
+            attempts: 3,
+            max_retries: Some(5),
+            skip_preflight: true,
+            max_trackers: 256,
+        }
+    }
+}
+
+/// `simulateTransaction` on a base64 transaction, returning the full JSON-RPC response.
+/// `accounts` are returned with their post-simulation state.
+pub async fn simulate_transaction(
+    http: &reqwest::Client,
+    url: &str,
+    tx_b64: &str,
+    sig_verify: bool,
+    accounts: &[String],
+) -> Result<Value> {
+    let mut config = json!({
+        "encoding": "base64",
+        "sigVerify": sig_verify,
+        "innerInstructions": true,
+        // A signed transaction must keep its blockhash for the signatures to verify
+        "replaceRecentBlockhash": !sig_verify
+    });
+    if !accounts.is_empty() {
+        config["accounts"] = json!({ "encoding": "base64", "addresses": accounts });
+    }
+
+    let request = json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "simulateTransaction",
+        "params": [tx_b64, config]
+    });
+
+    Ok(http.post(url).json(&request).send().await?.json().await?)
+}
+
+/// `sendTransaction`, returning the signature
+pub async fn send_transaction(
+    http: &reqwest::Client,
+    url: &str,
+    tx_b64: &str,
+    skip_preflight: bool,
+    max_retries: Option<usize>,
+) -> Result<String> {
+    let result = rpc_call(
+        http,
+        url,
+        "sendTransaction",
+        json!([tx_b64, {
+            "encoding": "base64",
+            "skipPreflight": skip_preflight,
+            "preflightCommitment": "confirmed",
+            "maxRetries": max_retries
+        }]),
+    )
+    .await?;
+
+    let signature = result
+        .as_str()
+        .context("sendTransaction returned no signature")?;
+    Ok(signature.to_string())
+}
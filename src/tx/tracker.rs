@@ -0,0 +1,416 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    time::Duration,
+};
+
+use anyhow::{bail, Result};
+use serde::Serialize;
+use serde_json::{json, Value};
+use solana_client::client_error::reqwest;
+use tokio::{
+    sync::{broadcast, mpsc},
+    time::Instant,
+};
+use tracing::{info, warn};
+
+use super::{rpc_call, BalanceChanges, DecodedError};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+/// A blockhash is valid for ~150 slots, an unseen signature after that never lands
+const NOT_FOUND_TIMEOUT: Duration = Duration::from_secs(90);
+const TRACK_TIMEOUT: Duration = Duration::from_secs(180);
+
+/// One update in the life of a submitted transaction
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum TxEvent {
+    Status {
+        status: String,
+        slot: u64,
+        confirmations: Option<u64>,
+    },
+    Failed {
+        error: Value,
+        message: String,
+    },
+    Balances(BalanceChanges),
+    Expired {
+        message: String,
+    },
+    Done,
+}
+
+impl TxEvent {
+    /// SSE event name
+    pub fn name(&self) -> &'static str {
+        match self {
+            TxEvent::Status { .. } => "status",
+            TxEvent::Failed { .. } => "failed",
+            TxEvent::Balances(_) => "balances",
+            TxEvent::Expired { .. } => "expired",
+            TxEvent::Done => "done",
+        }
+    }
+}
+
+/// Poll `getSignatureStatuses` until the signature is finalized or given up
+/// on, sending every status change and the final balance changes.
+pub fn spawn_tracker(rpc_url: String, signature: String) -> mpsc::Receiver<TxEvent> {
+    let (sender, receiver) = mpsc::channel(16);
+
+    tokio::spawn(async move {
+        let http = reqwest::Client::new();
+        let started = Instant::now();
+        let mut last_status: Option<String> = None;
+        let mut failed = false;
+
+        loop {
+            if sender.is_closed() {
+                return;
+            }
+
+            let status = match rpc_call(
+                &http,
+                &rpc_url,
+                "getSignatureStatuses",
+                json!([[signature], { "searchTransactionHistory": true }]),
+            )
+            .await
+            {
+                Ok(result) => result["value"][0].clone(),
+                Err(e) => {
+                    warn!("⚠️ Failed to poll status of {}: {}", signature, e);
+                    Value::Null
+                }
+            };
+
+            if !status.is_null() {
+                let confirmation_status = status["confirmationStatus"]
+                    .as_str()
+                    .unwrap_or("processed")
+                    .to_string();
+
+                if last_status.as_ref() != Some(&confirmation_status) {
+                    let event = TxEvent::Status {
+                        status: confirmation_status.clone(),
+                        slot: status["slot"].as_u64().unwrap_or(0),
+                        confirmations: status["confirmations"].as_u64(),
+                    };
+                    if sender.send(event).await.is_err() {
+                        return;
+                    }
+                    last_status = Some(confirmation_status.clone());
+                }
+
+                if !status["err"].is_null() && !failed {
+                    // Program errors are named in the logs, which are only served
+                    // once confirmed, so wait for them unless this is the last poll
+                    let logs = transaction_logs(&http, &rpc_url, &signature).await;
+                    if logs.is_some() || confirmation_status == "finalized" {
+                        failed = true;
+                        let event = TxEvent::Failed {
+                            message: DecodedError::decode(
+                                &status["err"],
+                                &logs.unwrap_or_default(),
+                            )
+                            .message,
+                            error: status["err"].clone(),
+                        };
+                        if sender.send(event).await.is_err() {
+                            return;
+                        }
+                    }
+                }
+
+                if confirmation_status == "finalized" {
+                    break;
+                }
+            }
+
+            let elapsed = started.elapsed();
+            let expired = if last_status.is_none() {
+                elapsed > NOT_FOUND_TIMEOUT
+            } else {
+                elapsed > TRACK_TIMEOUT
+            };
+            if expired {
+                let message = match &last_status {
+                    None => {
+                        "Transaction was not found, its blockhash has likely expired".to_string()
+                    }
+                    Some(status) => format!("Transaction stuck at {} status", status),
+                };
+                let _ = sender.send(TxEvent::Expired { message }).await;
+                return;
+            }
+
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+
+        match rpc_call(
+            &http,
+            &rpc_url,
+            "getTransaction",
+            json!([signature, {
+                "encoding": "json",
+                "commitment": "finalized",
+                "maxSupportedTransactionVersion": 0
+            }]),
+        )
+        .await
+        {
+            Ok(result) if !result.is_null() => {
+                let changes = BalanceChanges::from_transaction(&result);
+                let _ = sender.send(TxEvent::Balances(changes)).await;
+            }
+            Ok(_) => warn!("⚠️ Finalized transaction {} not returned", signature),
+            Err(e) => warn!("⚠️ Failed to fetch transaction {}: {}", signature, e),
+        }
+
+        info!("✅ Finished tracking {}", signature);
+        let _ = sender.send(TxEvent::Done).await;
+    });
+
+    receiver
+}
+
+/// Log messages of a confirmed transaction, `None` until it can be fetched
+async fn transaction_logs(
+    http: &reqwest::Client,
+    rpc_url: &str,
+    signature: &str,
+) -> Option<Vec<String>> {
+    let result = rpc_call(
+        http,
+        rpc_url,
+        "getTransaction",
+        json!([signature, {
+            "encoding": "json",
+            "commitment": "confirmed",
+            "maxSupportedTransactionVersion": 0
+        }]),
+    )
+    .await
+    .inspect_err(|e| warn!("⚠️ Failed to fetch logs of {}: {}", signature, e))
+    .ok()?;
+
+    let logs = result["meta"]["logMessages"].as_array()?;
+    Some(
+        logs.iter()
+            .filter_map(|log| log.as_str().map(str::to_string))
+            .collect(),
+    )
+}
+
+/// A running tracker: what it sent so far, for late subscribers, and the live feed
+struct SharedTracker {
+    history: Vec<TxEvent>,
+    sender: broadcast::Sender<TxEvent>,
+}
+
+/// One tracker per signature however many clients follow it, and a cap on
+/// how many signatures are polled at once
+pub struct TxTrackers {
+    rpc_url: String,
+    max_trackers: usize,
+    trackers: Arc<Mutex<HashMap<String, SharedTracker>>>,
+}
+
+impl TxTrackers {
+    pub fn new(rpc_url: String, max_trackers: usize) -> TxTrackers {
+        TxTrackers {
+            rpc_url,
+            max_trackers,
+            trackers: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Every event of `signature` from the start, joining its tracker when
+    /// one is already running
+    pub fn subscribe(&self, signature: &str) -> Result<mpsc::Receiver<TxEvent>> {
+        let mut trackers = lock(&self.trackers);
+
+        if let Some(tracker) = trackers.get(signature) {
+            return Ok(forward(tracker.history.clone(), tracker.sender.subscribe()));
+        }
+        if trackers.len() >= self.max_trackers {
+            bail!(
+                "Already tracking {} transactions, try again later",
+                trackers.len()
+            );
+        }
+
+        let (sender, receiver) = broadcast::channel(16);
+        trackers.insert(
+            signature.to_string(),
+            SharedTracker {
+                history: vec![],
+                sender,
+            },
+        );
+
+        let mut events = spawn_tracker(self.rpc_url.clone(), signature.to_string());
+        let shared = self.trackers.clone();
+        let signature = signature.to_string();
+        tokio::spawn(async move {
+            while let Some(event) = events.recv().await {
+                let mut trackers = lock(&shared);
+                let Some(tracker) = trackers.get_mut(&signature) else {
+                    return;
+                };
+                tracker.history.push(event.clone());
+                // Nobody is listening any more, dropping `events` stops the poller
+                if tracker.sender.send(event).is_err() {
+                    trackers.remove(&signature);
+                    return;
+                }
+            }
+            lock(&shared).remove(&signature);
+        });
+
+        Ok(forward(vec![], receiver))
+    }
+}
+
+fn lock(
+    trackers: &Mutex<HashMap<String, SharedTracker>>,
+) -> MutexGuard<'_, HashMap<String, SharedTracker>> {
+    trackers.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Replay `history`, then pass on live events until the tracker finishes or
+/// the subscriber goes away
+fn forward(
+    history: Vec<TxEvent>,
+    mut events: broadcast::Receiver<TxEvent>,
+) -> mpsc::Receiver<TxEvent> {
+    let (sender, receiver) = mpsc::channel(16);
+
+    tokio::spawn(async move {
+        for event in history {
+            if sender.send(event).await.is_err() {
+                return;
+            }
+        }
+        loop {
+            match events.recv().await {
+                Ok(event) => {
+                    if sender.send(event).await.is_err() {
+                        return;
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    warn!("⚠️ Transaction event subscriber skipped {} events", skipped);
+                }
+                Err(broadcast::error::RecvError::Closed) => return,
+            }
+        }
+    });
+
+    receiver
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use axum::{routing::post, Json, Router};
+
+    use super::*;
+
+    /// Seen on the second poll
+    const LANDING: &str = "landing";
+    /// Never seen
+    const PENDING: &str = "pending";
+
+    /// JSON-RPC node answering `getSignatureStatuses` and `getTransaction`,
+    /// counting status polls of `LANDING`
+    async fn mock_rpc(landing_polls: Arc<AtomicUsize>) -> String {
+        let app = Router::new().route(
+            "/",
+            post(move |Json(request): Json<Value>| {
+                let landing_polls = landing_polls.clone();
+                async move {
+                    let result = match request["method"].as_str() {
+                        Some("getSignatureStatuses") => {
+                            let landed = request["params"][0][0] == LANDING
+                                && landing_polls.fetch_add(1, Ordering::SeqCst) > 0;
+                            let status = if landed {
+                                json!({
+                                    "slot": 100,
+                                    "confirmations": null,
+                                    "err": null,
+                                    "confirmationStatus": "finalized"
+                                })
+                            } else {
+                                Value::Null
+                            };
+                            json!({ "context": { "slot": 100 }, "value": [status] })
+                        }
+                        Some("getTransaction") => json!({
+                            "slot": 100,
+                            "blockTime": 1_700_000_000,
+                            "meta": {
+                                "err": null,
+                                "preBalances": [1_000_000, 0],
+                                "postBalances": [995_000, 5_000],
+                                "preTokenBalances": [],
+                                "postTokenBalances": []
+                            },
+                            "transaction": { "message": { "accountKeys": ["payer", "recipient"] } }
+                        }),
+                        _ => Value::Null,
+                    };
+                    Json(json!({ "jsonrpc": "2.0", "id": 1, "result": result }))
+                }
+            }),
+        );
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        url
+    }
+
+    async fn event_names(mut events: mpsc::Receiver<TxEvent>) -> Vec<&'static str> {
+        let mut names = vec![];
+        while let Some(event) = tokio::time::timeout(Duration::from_secs(10), events.recv())
+            .await
+            .unwrap()
+        {
+            names.push(event.name());
+        }
+        names
+    }
+
+    #[tokio::test]
+    async fn subscribers_share_one_tracker() {
+        let landing_polls = Arc::new(AtomicUsize::new(0));
+        let trackers = TxTrackers::new(mock_rpc(landing_polls.clone()).await, 4);
+
+        let first = trackers.subscribe(LANDING).unwrap();
+        let second = trackers.subscribe(LANDING).unwrap();
+        assert_eq!(lock(&trackers.trackers).len(), 1);
+
+        let (first, second) = tokio::join!(event_names(first), event_names(second));
+        assert_eq!(first, ["status", "balances", "done"]);
+        assert_eq!(second, first);
+        // One poller: the miss, then the finalized status
+        assert_eq!(landing_polls.load(Ordering::SeqCst), 2);
+
+        // A subscriber arriving afterwards starts over and replays the outcome
+        let late = trackers.subscribe(LANDING).unwrap();
+        assert_eq!(event_names(late).await, ["status", "balances", "done"]);
+    }
+
+    #[tokio::test]
+    async fn caps_concurrent_trackers() {
+        let trackers = TxTrackers::new(mock_rpc(Arc::new(AtomicUsize::new(0))).await, 1);
+
+        let _pending = trackers.subscribe(PENDING).unwrap();
+        assert!(trackers.subscribe(LANDING).is_err());
+        // Joining a running tracker does not count against the cap
+        assert!(trackers.subscribe(PENDING).is_ok());
+    }
+}
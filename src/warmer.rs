@@ -0,0 +1,190 @@
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use anyhow::Result;
+use serde::Serialize;
+use solana_sdk::pubkey::Pubkey;
+use tokio::{sync::RwLock, time::Instant};
+use tracing::{info, warn};
+
+use crate::app::AppContext;
+
+/// Refresh this fraction of a TTL in, so entries are replaced before they expire
+const REFRESH_RATIO: f64 = 0.8;
+
+#[derive(Debug, Default)]
+struct WatchState {
+    pair_refreshed: Option<Instant>,
+    tokens_refreshed: Option<Instant>,
+    refreshes: u64,
+    failures: u64,
+    last_error: Option<String>,
+}
+
+/// Staleness of one watched pair, for the admin endpoint
+#[derive(Debug, Clone, Serialize)]
+pub struct WatchStatus {
+    pub pair: String,
+    pub pair_age_secs: Option<f64>,
+    pub tokens_age_secs: Option<f64>,
+    pub stale: bool,
+    pub refreshes: u64,
+    pub failures: u64,
+    pub last_error: Option<String>,
+}
+
+/// Keeps the watchlist pairs, their pool states, mints and token metadata
+/// refreshed ahead of their TTLs.
+pub struct CacheWarmer {
+    ctx: Arc<AppContext>,
+    states: RwLock<HashMap<Pubkey, WatchState>>,
+}
+
+impl CacheWarmer {
+    pub fn new(ctx: Arc<AppContext>) -> CacheWarmer {
+        let states = ctx
+            .config
+            .watchlist
+            .iter()
+            .map(|pair| (*pair, WatchState::default()))
+            .collect();
+
+        CacheWarmer {
+            ctx,
+            states: RwLock::new(states),
+        }
+    }
+
+    pub fn spawn(self: Arc<Self>) {
+        if self.ctx.config.watchlist.is_empty() {
+            return;
+        }
+
+        let interval = self.pair_ttl().mul_f64(REFRESH_RATIO);
+        // Nothing stays cached with a zero TTL, and `interval` panics on zero
+        if interval.is_zero() {
+            warn!("⚠️ Pair cache TTL is zero, not warming watched pairs");
+            return;
+        }
+        info!(
+            "🔥 Warming {} watched pairs every {:?}",
+            self.ctx.config.watchlist.len(),
+            interval
+        );
+
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                self.refresh_all().await;
+            }
+        });
+    }
+
+    pub async fn status(&self) -> Vec<WatchStatus> {
+        let pair_ttl = self.pair_ttl();
+        let token_ttl = self.ctx.config.cache_ttl.token_ttl;
+
+        self.states
+            .read()
+            .await
+            .iter()
+            .map(|(pair, state)| {
+                let pair_age = state.pair_refreshed.map(|at| at.elapsed());
+                let tokens_age = state.tokens_refreshed.map(|at| at.elapsed());
+
+                WatchStatus {
+                    pair: pair.to_string(),
+                    pair_age_secs: pair_age.map(|age| age.as_secs_f64()),
+                    tokens_age_secs: tokens_age.map(|age| age.as_secs_f64()),
+                    stale: pair_age.is_none_or(|age| age > pair_ttl)
+                        || tokens_age.is_none_or(|age| age > token_ttl),
+                    refreshes: state.refreshes,
+                    failures: state.failures,
+                    last_error: state.last_error.clone(),
+                }
+            })
+            .collect()
+    }
+
+    async fn refresh_all(&self) {
+        if let Err(e) = self.refresh_clock().await {
+            warn!("⚠️ Failed to refresh clock: {}", e);
+        }
+
+        for pair in self.ctx.config.watchlist.iter() {
+            let result = self.refresh_pair(*pair).await;
+
+            let mut states = self.states.write().await;
+            let state = states.entry(*pair).or_default();
+            match result {
+                Ok(()) => {
+                    state.refreshes += 1;
+                    state.last_error = None;
+                }
+                Err(e) => {
+                    warn!("⚠️ Failed to warm pair {}: {}", pair, e);
+                    state.failures += 1;
+                    state.last_error = Some(e.to_string());
+                }
+            }
+        }
+    }
+
+    async fn refresh_clock(&self) -> Result<()> {
+        let clock = self.ctx.fetch_clock().await?;
+        self.ctx
+            .clock_cache
+            .insert(solana_sdk::sysvar::clock::ID, clock)
+            .await;
+
+        Ok(())
+    }
+
+    async fn refresh_pair(&self, pair: Pubkey) -> Result<()> {
+        let (pair_account, slot) = self.ctx.load_pair_account(pair).await?;
+        self.ctx
+            .pair_accounts
+            .insert_at_slot(pair, pair_account, slot)
+            .await;
+        self.mark(pair, |state| state.pair_refreshed = Some(Instant::now()))
+            .await;
+
+        let token_refresh = self.ctx.config.cache_ttl.token_ttl.mul_f64(REFRESH_RATIO);
+        let tokens_fresh = self
+            .states
+            .read()
+            .await
+            .get(&pair)
+            .and_then(|state| state.tokens_refreshed)
+            .is_some_and(|at| at.elapsed() < token_refresh);
+        if tokens_fresh {
+            return Ok(());
+        }
+
+        // Builds the client and records the pair's static data
+        let client = self.ctx.get_or_spawn_client(pair).await?;
+        let mints = {
+            let saros_dlmm = client.saros_dlmm.read().await;
+            [saros_dlmm.pair.token_mint_x, saros_dlmm.pair.token_mint_y]
+        };
+
+        for mint in mints {
+            let token_meta = self.ctx.load_token_meta(mint).await?;
+            self.ctx.token_meta_cache.insert(mint, token_meta).await;
+        }
+        self.mark(pair, |state| state.tokens_refreshed = Some(Instant::now()))
+            .await;
+
+        Ok(())
+    }
+
+    async fn mark(&self, pair: Pubkey, update: impl FnOnce(&mut WatchState)) {
+        update(self.states.write().await.entry(pair).or_default());
+    }
+
+    /// Pair accounts and pool states share a refresh, so use the shorter TTL
+    fn pair_ttl(&self) -> Duration {
+        let ttl = &self.ctx.config.cache_ttl;
+        ttl.pool_ttl.min(ttl.bin_ttl)
+    }
+}
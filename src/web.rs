@@ -1,43 +1,87 @@
-use anchor_lang::prelude::AccountMeta;
-use saros_sdk::{
-    instruction::build_swap_instruction_data, math::swap_manager::SwapType,
-    utils::helper::is_swap_for_y,
-};
+use saros_dlmm::SarosDlmm;
+use saros_sdk::utils::helper::is_swap_for_y;
 use solana_client::client_error::reqwest;
 use solana_sdk::{
-    compute_budget::ComputeBudgetInstruction, instruction::Instruction, message::Message,
-    pubkey::Pubkey, signature::Signature, transaction::Transaction,
+    compute_budget::ComputeBudgetInstruction, instruction::Instruction, pubkey::Pubkey,
+    signature::Signature, transaction::VersionedTransaction,
+};
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    str::FromStr,
+    sync::{atomic::Ordering, Arc},
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
-use std::{collections::HashMap, net::SocketAddr, sync::Arc};
 use tracing::info;
 
 use axum::{
-    extract::{Query, State},
+    extract::{Path, Query, Request, State},
+    http::{header, StatusCode},
+    middleware::{self, Next},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
     routing::{get, post},
     Json, Router,
 };
 use serde_json::{json, Value};
+use tokio_stream::{wrappers::ReceiverStream, Stream, StreamExt};
 use tower_http::{
     cors::{Any, CorsLayer},
     services::ServeDir,
 };
 
 use crate::{
+    analytics::{compute_position_pnl, DepositInput},
     app::{AppConfig, AppContext},
+    cache,
+    dlmm::{
+        apply_slippage, build_add_liquidity_instruction, build_route_transaction,
+        build_swap_instruction, build_v0_transaction, find_best_route, generate_distribution,
+        preview_deposit, DLMMClient, Route,
+    },
+    indexer::{Candle, CandleInterval, Indexer, RpcSource, TradeCursor},
+    retry::RetryPolicy,
     state::{
-        InstructionRequest, InstructionType, QuoteRequest, Status, SwapInstructionParams,
+        fetch_liquidity_position, fetch_position_pair, AddLiquidityInstructionParams,
+        BatchQuoteItem, InstructionRequest, InstructionType, LiquidityPreviewRequest, PairSearch,
+        QuoteRequest, QuoteResponse, RouteRequest, SendTxRequest, SimulateSignedTxRequest,
+        State as DlmmState, Status, SwapInstructionParams, TokenMeta, TokenResponse,
         WebJsonResponse,
     },
+    tx::{
+        resolve_account_keys, send_transaction, simulate_transaction, BalanceChanges, DecodedError,
+        DecodedEvents, OwnerDelta, TxEvent,
+    },
+    warmer::CacheWarmer,
 };
 use anyhow::Result;
-use spl_associated_token_account::get_associated_token_address_with_program_id;
 
-use jupiter_amm_interface::{Amm, QuoteParams, SwapMode};
+use jupiter_amm_interface::{QuoteParams, SwapMode};
 
 use base64::{engine::general_purpose, Engine as _};
 
 pub async fn start_web_server(config: AppConfig) -> Result<()> {
     let app_state = Arc::new(AppContext::new(config));
+    app_state.load_persisted().await;
+    app_state.clone().spawn_pair_index_refresher();
+    app_state.clone().spawn_persister(Duration::from_secs(60));
+    let warmer = Arc::new(CacheWarmer::new(app_state.clone()));
+    warmer.clone().spawn();
+    if let Some(db) = app_state.index_db.clone() {
+        Indexer::new(
+            db,
+            RpcSource::new(app_state.rpc_client.url()),
+            app_state.config.lb_program_id,
+            app_state.config.index_backfill,
+        )
+        .spawn(
+            app_state.config.watchlist.clone(),
+            app_state.config.index_interval,
+        );
+    }
+    cache::spawn_sweeper(app_state.caches(), Duration::from_secs(30));
 
     let static_files = ServeDir::new(format!("{}/web/dist", env!("CARGO_MANIFEST_DIR")));
 
@@ -48,51 +92,219 @@ pub async fn start_web_server(config: AppConfig) -> Result<()> {
 
     let public_routes = Router::new().route("/api/network/status", get(ping));
 
+    let admin_routes = Router::new()
+        .route("/api/admin/cache", get(get_cache_stats))
+        .route("/api/admin/cache/flush", post(flush_cache))
+        .route(
+            "/api/admin/watchlist",
+            get(move || get_watchlist_status(warmer.clone())),
+        )
+        .route_layer(middleware::from_fn_with_state(
+            app_state.clone(),
+            require_admin_token,
+        ));
+
     let sdk_routes = Router::new()
         .route("/api/pair", get(get_pair))
+        .route("/api/pair/bins", get(get_pair_bins))
+        .route("/api/pair/stats", get(get_pair_stats))
+        .route("/api/pair/trades", get(get_pair_trades))
+        .route("/api/pair/candles", get(get_pair_candles))
+        .route("/api/pairs", get(search_pairs))
+        .route(
+            "/api/positions/{position}/rewards",
+            get(get_position_rewards),
+        )
+        .route("/api/positions/{position}/pnl", get(get_position_pnl))
+        .route("/api/token", get(get_token))
         .route("/api/quote", post(get_quote))
+        .route("/api/quotes", post(get_quotes))
+        .route("/api/route", post(get_route))
+        .route("/api/liquidity/preview", post(preview_liquidity))
         .route("/api/instruction", post(get_instruction))
-        .route("/api/simulate_tx", post(simulate_tx));
+        .route("/api/simulate_tx", post(simulate_tx))
+        .route("/api/simulate_signed_tx", post(simulate_signed_tx))
+        .route("/api/send_tx", post(send_tx))
+        .route("/api/tx/{signature}/events", get(tx_events));
 
     // Define API routes
-    let app = Router::new()
-        .merge(public_routes)
-        .merge(sdk_routes)
+    let mut app = Router::new().merge(public_routes).merge(sdk_routes);
+    if app_state.config.admin_token.is_some() {
+        app = app.merge(admin_routes);
+    } else {
+        tracing::warn!("⚠️ ADMIN_TOKEN not set, admin routes are disabled");
+    }
+    let app = app
         .route("/api/ping", get(|| async { "pong 🦀" }))
         .fallback_service(static_files)
         .layer(cors)
-        .with_state(app_state);
+        .with_state(app_state.clone());
 
     let port = std::env::var("PORT").unwrap_or_else(|_| "8080".to_string());
     let addr: SocketAddr = format!("0.0.0.0:{}", port).parse().unwrap();
     let listener = tokio::net::TcpListener::bind(addr).await?;
     tracing::info!("Web server listening on http://{}", addr);
-    axum::serve(listener, app.into_make_service()).await?;
+    axum::serve(listener, app.into_make_service())
+        .with_graceful_shutdown(shutdown_signal())
+        .await?;
+
+    // Keep what was cached since the last periodic save
+    if let Err(e) = app_state.persist().await {
+        tracing::warn!("⚠️ Failed to persist caches on shutdown: {}", e);
+    }
 
     Ok(())
 }
 
+/// Resolves on Ctrl+C or SIGTERM
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            tracing::warn!("⚠️ Failed to listen for Ctrl+C: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(e) => {
+                tracing::warn!("⚠️ Failed to listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+    info!("🛑 Shutting down, saving caches");
+}
+
+/// Reject admin requests without `Authorization: Bearer <ADMIN_TOKEN>`
+async fn require_admin_token(
+    State(ctx): State<Arc<AppContext>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let provided = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+
+    match (&ctx.config.admin_token, provided) {
+        (Some(expected), Some(provided)) if constant_time_eq(expected, provided) => {
+            next.run(request).await
+        }
+        _ => StatusCode::UNAUTHORIZED.into_response(),
+    }
+}
+
+/// Compare secrets without returning early on the first differing byte
+fn constant_time_eq(a: &str, b: &str) -> bool {
+    a.len() == b.len()
+        && a.bytes()
+            .zip(b.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
 /// === Handlers ===
 async fn ping() -> &'static str {
     "pong 🦀"
 }
 
-/// Get pool info by pubkey
+/// Inspect cache counters, and entries with `?entries=true`
 #[axum::debug_handler]
-async fn get_pair(
+async fn get_cache_stats(
     State(ctx): State<Arc<AppContext>>,
     Query(params): Query<HashMap<String, String>>,
 ) -> Json<WebJsonResponse> {
-    let pair_address = params.get("address").cloned().unwrap_or_default();
-    if pair_address.len() < 20 {
+    let with_entries = params
+        .get("entries")
+        .is_some_and(|entries| entries == "true");
+
+    let mut caches = vec![];
+    for cache in ctx.caches() {
+        let mut data = json!(cache.stats().await);
+        if with_entries {
+            data["entries_info"] = json!(cache.entries_info().await);
+        }
+        caches.push(data);
+    }
+
+    Json(WebJsonResponse {
+        status: Status::Success,
+        message: "Cache stats fetched successfully".to_string(),
+        data: json!(caches),
+    })
+}
+
+/// Flush one cache by `?name=`, or all of them
+#[axum::debug_handler]
+async fn flush_cache(
+    State(ctx): State<Arc<AppContext>>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Json<WebJsonResponse> {
+    let name = params.get("name");
+
+    let mut flushed = serde_json::Map::new();
+    for cache in ctx.caches() {
+        if name.is_none_or(|name| name == cache.name()) {
+            flushed.insert(cache.name().to_string(), json!(cache.clear().await));
+        }
+    }
+
+    if flushed.is_empty() {
         return Json(WebJsonResponse {
             status: Status::Error,
-            message: "Invalid address format".to_string(),
+            message: format!("Unknown cache: {}", name.cloned().unwrap_or_default()),
             data: json!({}),
         });
     }
 
-    let pair_key = Pubkey::from_str_const(&pair_address);
+    info!("🧹 Flushed caches: {:?}", flushed);
+
+    Json(WebJsonResponse {
+        status: Status::Success,
+        message: "Cache flushed successfully".to_string(),
+        data: json!(flushed),
+    })
+}
+
+/// Freshness of every watchlist pair kept warm in the background
+async fn get_watchlist_status(warmer: Arc<CacheWarmer>) -> Json<WebJsonResponse> {
+    Json(WebJsonResponse {
+        status: Status::Success,
+        message: "Watchlist status fetched successfully".to_string(),
+        data: json!(warmer.status().await),
+    })
+}
+
+/// Get pool info by pubkey
+#[axum::debug_handler]
+async fn get_pair(
+    State(ctx): State<Arc<AppContext>>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Json<WebJsonResponse> {
+    let pair_address = params.get("address").cloned().unwrap_or_default();
+    let pair_key = match Pubkey::from_str(&pair_address) {
+        Ok(pair_key) => pair_key,
+        Err(_) => {
+            return Json(WebJsonResponse {
+                status: Status::Error,
+                message: "Invalid address format".to_string(),
+                data: json!({}),
+            });
+        }
+    };
 
     // Step 1: Get or create DLMM client
     let dlmm_client = match ctx.get_or_spawn_client(pair_key).await {
@@ -120,6 +332,10 @@ async fn get_pair(
         }
     };
 
+    let epoch = ctx.current_epoch.load(Ordering::Relaxed);
+    let pair_static = ctx.pair_statics.read().await.get(&pair_key).cloned();
+    let hook = hook_json(&ctx, &saros_dlmm).await;
+
     Json(WebJsonResponse {
         status: Status::Success,
         message: "Pair fetched successfully".to_string(),
@@ -127,39 +343,104 @@ async fn get_pair(
             "pair_address": pair_address,
             "token_mint_x": saros_dlmm.pair.token_mint_x.to_string(),
             "token_mint_y": saros_dlmm.pair.token_mint_y.to_string(),
-            "token_a": {
-                "mint": mint_a_meta.mint.to_string(),
-                "symbol": mint_a_meta.symbol,
-                "decimals": mint_a_meta.decimals,
-            },
-            "token_b": {
-                "mint": mint_b_meta.mint.to_string(),
-                "symbol": mint_b_meta.symbol,
-                "decimals": mint_b_meta.decimals,
-            },
+            "context_slot": dlmm_client.slot.load(Ordering::Relaxed),
+            "bin_step": pair_static.as_ref().map(|pair_static| pair_static.bin_step),
+            "vault_x": pair_static.as_ref().map(|pair_static| pair_static.vault_x.to_string()),
+            "vault_y": pair_static.as_ref().map(|pair_static| pair_static.vault_y.to_string()),
+            "token_program_x": pair_static.as_ref().map(|pair_static| pair_static.token_program_x.to_string()),
+            "token_program_y": pair_static.as_ref().map(|pair_static| pair_static.token_program_y.to_string()),
+            "token_a": token_json(&mint_a_meta, epoch),
+            "token_b": token_json(&mint_b_meta, epoch),
+            "hook": hook,
         }),
     })
 }
 
+/// Reward config and per-bin accumulators of a pair's hook, or null when the
+/// pair has none
+async fn hook_json(ctx: &AppContext, saros_dlmm: &SarosDlmm) -> Value {
+    if saros_dlmm.hook == saros_dlmm.key {
+        return Value::Null;
+    }
+
+    let rewards = match ctx
+        .get_hook_rewards(saros_dlmm.hook, saros_dlmm.pair.active_id)
+        .await
+    {
+        Ok(rewards) => rewards,
+        Err(e) => {
+            tracing::warn!("⚠️ Failed to read hook {}: {}", saros_dlmm.hook, e);
+            return json!({
+                "address": saros_dlmm.hook.to_string(),
+                "error": e.to_string(),
+            });
+        }
+    };
+
+    let epoch = ctx.current_epoch.load(Ordering::Relaxed);
+    let reward_token = ctx
+        .get_token_meta(rewards.hook.reward_token_mint)
+        .await
+        .ok()
+        .map(|meta| token_json(&meta, epoch));
+
+    json!({
+        "address": rewards.hook_key.to_string(),
+        "pair": rewards.hook.pair.to_string(),
+        "authority": rewards.hook.authority.to_string(),
+        "reward_mint": rewards.hook.reward_token_mint.to_string(),
+        "reward_token": reward_token,
+        "reward_reserve": rewards.hook.hook_reserve.to_string(),
+        "rewards_per_second": rewards.hook.rewards_per_second,
+        "rewards_per_second_per_bin": rewards.rewards_per_second_per_bin(),
+        "end_time": rewards.hook.end_time,
+        "last_update": rewards.hook.last_update,
+        "active": rewards.is_active(),
+        "total_unclaimed_rewards": rewards.hook.total_unclaimed_rewards,
+        "delta_bin_a": rewards.hook.delta_bin_a,
+        "delta_bin_b": rewards.hook.delta_bin_b,
+        "bins": rewards.bins,
+    })
+}
+
+/// Estimate the hook rewards a position has accrued but not claimed
 #[axum::debug_handler]
-async fn get_quote(
+async fn get_position_rewards(
     State(ctx): State<Arc<AppContext>>,
-    Json(body): Json<QuoteRequest>,
+    Path(position): Path<String>,
 ) -> Json<WebJsonResponse> {
-    let pair_address = body.pair_address.clone();
-
-    info!("🔍 Getting quote for pair {}", pair_address);
-    info!("Body: {:?}", body);
-
-    let source_mint = Pubkey::from_str_const(&body.source_mint);
-    let destination_mint = Pubkey::from_str_const(&body.destination_mint);
+    let position_key = match Pubkey::from_str(&position) {
+        Ok(position_key) => position_key,
+        Err(_) => {
+            return Json(WebJsonResponse {
+                status: Status::Error,
+                message: "Invalid address format".to_string(),
+                data: json!({}),
+            });
+        }
+    };
 
-    // 1️⃣ take DLMM client
-    let dlmm_client = match ctx
-        .get_or_spawn_client(Pubkey::from_str_const(&pair_address))
+    let client = ctx.rpc_client.clone();
+    let pair_key = match ctx
+        .config
+        .retry
+        .run_blocking("position", move || {
+            fetch_position_pair(&client, position_key)
+        })
         .await
     {
-        Ok(client) => client,
+        Ok(pair_key) => pair_key,
+        Err(e) => {
+            return Json(WebJsonResponse {
+                status: Status::Error,
+                message: format!("Failed to read position: {}", e),
+                data: json!({}),
+            });
+        }
+    };
+
+    let hook_key = match ctx.get_or_spawn_client(pair_key).await {
+        Ok(client) => client.saros_dlmm.read().await.hook,
         Err(e) => {
             return Json(WebJsonResponse {
                 status: Status::Error,
@@ -169,381 +450,1290 @@ async fn get_quote(
         }
     };
 
-    tracing::info!(
-        "💱 Quoting swap: amount_in={}, source_mint={}",
-        body.amount_in,
-        body.source_mint
-    );
+    if hook_key == pair_key {
+        return Json(WebJsonResponse {
+            status: Status::Error,
+            message: format!("Pair {} has no reward hook", pair_key),
+            data: json!({}),
+        });
+    }
 
-    for _ in 0..3 {
-        if let Err(e) = dlmm_client.update(&ctx).await {
-            tracing::warn!("⚠️ Failed to update DLMM client: {}", e);
-            continue;
+    let rewards = match DlmmState::generate_position_rewards(
+        ctx.rpc_client.clone(),
+        position_key,
+        hook_key,
+        ctx.config.hook_program_id,
+        &ctx.config.retry,
+    )
+    .await
+    {
+        Ok(rewards) => rewards,
+        Err(e) => {
+            return Json(WebJsonResponse {
+                status: Status::Error,
+                message: format!("Failed to estimate rewards: {}", e),
+                data: json!({}),
+            });
         }
-    }
+    };
 
-    let client = dlmm_client.saros_dlmm.read().await;
+    Json(WebJsonResponse {
+        status: Status::Success,
+        message: "Position rewards estimated successfully".to_string(),
+        data: json!(rewards),
+    })
+}
 
-    let is_swap_for_y = is_swap_for_y(source_mint, client.pair.token_mint_x);
-    let swap_mode = if is_swap_for_y {
-        if source_mint == client.pair.token_mint_x {
-            SwapMode::ExactIn
-        } else {
-            SwapMode::ExactOut
-        }
-    } else {
-        if source_mint == client.pair.token_mint_x {
-            SwapMode::ExactOut
-        } else {
-            SwapMode::ExactIn
+/// Current value, impermanent loss, fees earned and net PnL of a position.
+/// `deposit_x`, `deposit_y` and `deposit_price` override the indexed deposit.
+#[axum::debug_handler]
+async fn get_position_pnl(
+    State(ctx): State<Arc<AppContext>>,
+    Path(position): Path<String>,
+    Query(input): Query<DepositInput>,
+) -> Json<WebJsonResponse> {
+    let position_key = match Pubkey::from_str(&position) {
+        Ok(position_key) => position_key,
+        Err(_) => {
+            return Json(WebJsonResponse {
+                status: Status::Error,
+                message: "Invalid address format".to_string(),
+                data: json!({}),
+            });
         }
     };
 
-    let req = QuoteParams {
-        amount: body.amount_in,
-        input_mint: source_mint,
-        swap_mode,
-        output_mint: destination_mint,
-    };
+    info!("📊 Computing PnL for position {}", position_key);
 
-    // 2️⃣ call get_quote() from DLMM client
-    let result = match client.quote(&req) {
-        Ok(quote) => Json(WebJsonResponse {
+    match compute_position_pnl(&ctx, position_key, &input).await {
+        Ok(pnl) => Json(WebJsonResponse {
             status: Status::Success,
-            message: "quote successful".to_string(),
-            data: json!({
-                "in_amount": quote.in_amount,
-                "out_amount": quote.out_amount,
-                "fee_amount": quote.fee_amount,
-                "fee_mint": quote.fee_mint.to_string(),
-            }),
+            message: "Position PnL computed successfully".to_string(),
+            data: json!(pnl),
         }),
         Err(e) => Json(WebJsonResponse {
             status: Status::Error,
-            message: format!("Failed to get quote: {}", e),
+            message: format!("Failed to compute position PnL: {}", e),
             data: json!({}),
         }),
-    };
+    }
+}
 
-    result
+fn token_json(meta: &TokenMeta, epoch: u64) -> Value {
+    json!({
+        "mint": meta.mint.to_string(),
+        "symbol": meta.symbol,
+        "decimals": meta.decimals,
+        "name": meta.name,
+        "uri": meta.uri,
+        "source": meta.source,
+        "token_program": meta.token_program.to_string(),
+        "transfer_fee_bps": meta.transfer_fee_bps(epoch),
+        "interest_rate_bps": meta.interest_rate_bps,
+        "transfer_hook_program": meta.transfer_hook_program.map(|program| program.to_string()),
+    })
 }
 
+/// Get token metadata, authorities, supply and logo by mint
 #[axum::debug_handler]
-async fn get_instruction(
+async fn get_token(
     State(ctx): State<Arc<AppContext>>,
-    Json(body): Json<InstructionRequest<serde_json::Value>>,
+    Query(params): Query<HashMap<String, String>>,
 ) -> Json<WebJsonResponse> {
-    info!("🔍 Simulating swap with body: {:?}", body);
-
-    let pair_address = body.pair_address.clone();
-    let params: SwapInstructionParams = serde_json::from_value(body.params.clone()).unwrap();
-
-    info!("🔍 Getting instruction for pair {}", pair_address);
-
-    let source_mint = Pubkey::from_str_const(&params.source_mint);
-    let in_amount = params.in_amount;
-    let min_out_amount = params.min_out_amount;
-
-    // 1️⃣ take DLMM client
-    let dlmm_client = match ctx
-        .get_or_spawn_client(Pubkey::from_str_const(&pair_address))
-        .await
-    {
-        Ok(client) => client,
-        Err(e) => {
+    let mint = match params.get("mint").map(|mint| Pubkey::from_str(mint)) {
+        Some(Ok(mint)) => mint,
+        _ => {
             return Json(WebJsonResponse {
                 status: Status::Error,
-                message: format!("Failed to get DLMM client: {}", e),
+                message: "Invalid mint format".to_string(),
                 data: json!({}),
             });
         }
     };
 
-    for _ in 0..3 {
-        if let Err(e) = dlmm_client.update(&ctx).await {
-            tracing::warn!("⚠️ Failed to update DLMM client: {}", e);
-            continue;
-        }
-    }
-
-    let client = dlmm_client.saros_dlmm.read().await;
-
-    let user = Pubkey::from_str_const(&params.signer);
-
-    // let source_token_account = get_associated_token_address(&user, &source_mint).to_string();
-    let user_token_vault_x = get_associated_token_address_with_program_id(
-        &user,
-        &client.pair.token_mint_x,
-        &client.token_program[0],
-    );
-
-    let user_token_vault_y = get_associated_token_address_with_program_id(
-        &user,
-        &client.pair.token_mint_y,
-        &client.token_program[1],
-    );
-
-    let is_swap_for_y = is_swap_for_y(source_mint, client.pair.token_mint_x);
-    let swap_mode = if is_swap_for_y {
-        if source_mint == client.pair.token_mint_x {
-            SwapType::ExactIn
-        } else {
-            SwapType::ExactOut
-        }
-    } else {
-        if source_mint == client.pair.token_mint_x {
-            SwapType::ExactOut
-        } else {
-            SwapType::ExactIn
+    let token_meta = match ctx.get_token_meta(mint).await {
+        Ok(token_meta) => token_meta,
+        Err(e) => {
+            return Json(WebJsonResponse {
+                status: Status::Error,
+                message: format!("Failed to fetch token metadata: {}", e),
+                data: json!({}),
+            });
         }
     };
 
-    let bin_for_swap = client.compute_bin_array_swap().unwrap();
-
-    let data = match body.instruction_type {
-        InstructionType::Swap => {
-            let swap_instruction_data = build_swap_instruction_data(
-                saros_sdk::instruction::BuildSwapInstructionDataParams {
-                    amount: in_amount,
-                    other_amount_threshold: min_out_amount,
-                    swap_for_y: is_swap_for_y,
-                    swap_mode,
-                },
-            )
-            .unwrap();
-
-            let mut account_metas = Vec::new();
-
-            {
-                account_metas.push(AccountMeta::new(client.key, false));
-                account_metas.push(AccountMeta::new_readonly(client.pair.token_mint_x, false));
-                account_metas.push(AccountMeta::new_readonly(client.pair.token_mint_y, false));
-                account_metas.push(AccountMeta::new(bin_for_swap.bin_array_keys[0], false));
-                account_metas.push(AccountMeta::new(bin_for_swap.bin_array_keys[1], false));
-                account_metas.push(AccountMeta::new(client.token_vault[0], false));
-                account_metas.push(AccountMeta::new(client.token_vault[1], false));
-                account_metas.push(AccountMeta::new(
-                    Pubkey::from_str_const(&user_token_vault_x.to_string()),
-                    false,
-                ));
-                account_metas.push(AccountMeta::new(
-                    Pubkey::from_str_const(&user_token_vault_y.to_string()),
-                    false,
-                ));
-                account_metas.push(AccountMeta::new_readonly(user, true));
-                account_metas.push(AccountMeta::new_readonly(client.token_program[0], false));
-                account_metas.push(AccountMeta::new_readonly(client.token_program[1], false));
-                account_metas.push(AccountMeta::new_readonly(
-                    Pubkey::from_str_const("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr"),
-                    false,
-                ));
-            }
-
-            // If pair does not have hook, hook should be pair key (dummy)
-            account_metas.push(AccountMeta::new(client.hook, false));
-            account_metas.push(AccountMeta::new_readonly(ctx.config.hook_program_id, false));
-            // This expect as the last of swap instruction
-            account_metas.push(AccountMeta::new_readonly(client.event_authority, false));
-            account_metas.push(AccountMeta::new_readonly(client.program_id, false));
-
-            // Remaining accounts for hook CPI call
-            if client.hook != client.key {
-                let bin_array_index = client.pair.bin_array_index();
-                let (hook_bin_array_lower, _) = Pubkey::find_program_address(
-                    &[
-                        b"bin_array".as_ref(),
-                        client.hook.as_ref(),
-                        (bin_array_index).to_le_bytes().as_ref(),
-                    ],
-                    &ctx.config.hook_program_id,
-                );
-
-                let (hook_bin_array_upper, _) = Pubkey::find_program_address(
-                    &[
-                        b"bin_array".as_ref(),
-                        client.hook.as_ref(),
-                        (bin_array_index + 1).to_le_bytes().as_ref(),
-                    ],
-                    &ctx.config.hook_program_id,
-                );
-
-                account_metas.push(AccountMeta::new(hook_bin_array_lower, false));
-                account_metas.push(AccountMeta::new(hook_bin_array_upper, false));
+    // A missing or broken off-chain JSON only costs us the logo
+    let off_chain = if token_meta.uri.is_empty() {
+        None
+    } else {
+        match ctx.get_off_chain_meta(&token_meta.uri).await {
+            Ok(off_chain) => Some(off_chain),
+            Err(e) => {
+                tracing::warn!("⚠️ Failed to resolve {}: {}", token_meta.uri, e);
+                None
             }
-
-            let swap_instruction = Instruction {
-                program_id: client.program_id,
-                accounts: account_metas,
-                data: swap_instruction_data,
-            };
-
-            tracing::info!("Swap instruction: {:?}", swap_instruction);
-
-            json!({
-               "instruction_type": "swap",
-               "data": swap_instruction
-            })
         }
-        _ => json!({
-            "instruction_type": "unsupported",
-            "data": ""
-        }),
     };
-    // Implementation for fetching instruction details goes here
+
     Json(WebJsonResponse {
         status: Status::Success,
-        message: "Instruction fetched successfully".to_string(),
-        data: data,
+        message: "Token fetched successfully".to_string(),
+        data: json!(TokenResponse::new(&token_meta, off_chain.as_ref())),
     })
 }
 
+/// Maximum number of bins returned on each side of the active bin
+const MAX_BIN_RANGE: u32 = 500;
+
+/// Liquidity depth around the active bin, for charts
 #[axum::debug_handler]
-async fn simulate_tx(
+async fn get_pair_bins(
     State(ctx): State<Arc<AppContext>>,
-    Json(body): Json<InstructionRequest<serde_json::Value>>,
+    Query(params): Query<HashMap<String, String>>,
 ) -> Json<WebJsonResponse> {
-    info!("🔍 Simulating swap with body: {:?}", body);
-
-    let pair_address = body.pair_address.clone();
-    let params: SwapInstructionParams = serde_json::from_value(body.params.clone()).unwrap();
+    let pair_key = match params
+        .get("address")
+        .map(|address| Pubkey::from_str(address))
+    {
+        Some(Ok(pair_key)) => pair_key,
+        _ => {
+            return Json(WebJsonResponse {
+                status: Status::Error,
+                message: "Invalid address format".to_string(),
+                data: json!({}),
+            });
+        }
+    };
 
-    info!("🔍 Getting instruction for pair {}", pair_address);
+    let range = params
+        .get("range")
+        .and_then(|range| range.parse::<u32>().ok())
+        .unwrap_or(50)
+        .min(MAX_BIN_RANGE);
 
-    let source_mint = Pubkey::from_str_const(&params.source_mint);
-    let in_amount = params.in_amount;
-    let min_out_amount = params.min_out_amount;
+    info!(
+        "📊 Fetching {} bins around active id for pair {}",
+        range, pair_key
+    );
 
-    // 1️⃣ take DLMM client
-    let dlmm_client = match ctx
-        .get_or_spawn_client(Pubkey::from_str_const(&pair_address))
-        .await
+    let bin_range = match DlmmState::generate_bin_range(
+        ctx.rpc_client.clone(),
+        pair_key,
+        range,
+        &ctx.config.retry,
+    )
+    .await
     {
-        Ok(client) => client,
+        Ok(bin_range) => bin_range,
         Err(e) => {
             return Json(WebJsonResponse {
                 status: Status::Error,
-                message: format!("Failed to get DLMM client: {}", e),
+                message: format!("Failed to fetch bins: {}", e),
                 data: json!({}),
             });
         }
     };
 
-    for _ in 0..3 {
-        if let Err(e) = dlmm_client.update(&ctx).await {
-            tracing::warn!("⚠️ Failed to update DLMM client: {}", e);
-            continue;
-        }
+    Json(WebJsonResponse {
+        status: Status::Success,
+        message: "Bins fetched successfully".to_string(),
+        data: json!({
+            "pair_address": pair_key.to_string(),
+            "active_id": bin_range.active_id,
+            "bin_step": bin_range.bin_step,
+            "bins": bin_range.bins,
+        }),
+    })
+}
+
+/// TVL, 24h volume and fees, and fee APR around the active bin
+#[axum::debug_handler]
+async fn get_pair_stats(
+    State(ctx): State<Arc<AppContext>>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Json<WebJsonResponse> {
+    let pair_key = match params
+        .get("address")
+        .map(|address| Pubkey::from_str(address))
+    {
+        Some(Ok(pair_key)) => pair_key,
+        _ => {
+            return Json(WebJsonResponse {
+                status: Status::Error,
+                message: "Invalid address format".to_string(),
+                data: json!({}),
+            });
+        }
+    };
+
+    info!("📈 Computing stats for pair {}", pair_key);
+
+    match ctx.get_pair_stats(pair_key).await {
+        Ok(stats) => Json(WebJsonResponse {
+            status: Status::Success,
+            message: "Pair stats computed successfully".to_string(),
+            data: json!(stats),
+        }),
+        Err(e) => Json(WebJsonResponse {
+            status: Status::Error,
+            message: format!("Failed to compute pair stats: {}", e),
+            data: json!({}),
+        }),
+    }
+}
+
+/// Maximum number of trades returned per page
+const MAX_TRADES_PAGE: usize = 500;
+
+/// Indexed swaps of a pair, newest first, paged with `before`
+#[axum::debug_handler]
+async fn get_pair_trades(
+    State(ctx): State<Arc<AppContext>>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Json<WebJsonResponse> {
+    let pair_key = match params
+        .get("address")
+        .map(|address| Pubkey::from_str(address))
+    {
+        Some(Ok(pair_key)) => pair_key,
+        _ => {
+            return Json(WebJsonResponse {
+                status: Status::Error,
+                message: "Invalid address format".to_string(),
+                data: json!({}),
+            });
+        }
+    };
+
+    let before = match params
+        .get("before")
+        .map(|before| TradeCursor::parse(before))
+    {
+        Some(Some(cursor)) => Some(cursor),
+        Some(None) => {
+            return Json(WebJsonResponse {
+                status: Status::Error,
+                message: "Invalid cursor".to_string(),
+                data: json!({}),
+            });
+        }
+        None => None,
+    };
+
+    let limit = params
+        .get("limit")
+        .and_then(|limit| limit.parse::<usize>().ok())
+        .unwrap_or(50)
+        .clamp(1, MAX_TRADES_PAGE);
+
+    let Some(db) = &ctx.index_db else {
+        return Json(WebJsonResponse {
+            status: Status::Error,
+            message: "Swap indexer is disabled".to_string(),
+            data: json!({}),
+        });
+    };
+
+    let pair = pair_key.to_string();
+    let query_pair = pair.clone();
+    let (trades, cursor) = match db
+        .run(move |db| {
+            let trades = db.trades(&query_pair, before.as_ref(), limit)?;
+            Ok((trades, db.cursor(&query_pair)?))
+        })
+        .await
+    {
+        Ok(page) => page,
+        Err(e) => {
+            return Json(WebJsonResponse {
+                status: Status::Error,
+                message: format!("Failed to read trades: {}", e),
+                data: json!({}),
+            });
+        }
+    };
+
+    let next_cursor = (trades.len() == limit)
+        .then(|| trades.last().map(TradeCursor::of))
+        .flatten();
+
+    Json(WebJsonResponse {
+        status: Status::Success,
+        message: format!("{} trades found", trades.len()),
+        data: json!({
+            "pair_address": pair,
+            "trades": trades,
+            "next_cursor": next_cursor,
+            "index": cursor,
+        }),
+    })
+}
+
+/// Maximum number of candles returned per request
+const MAX_CANDLES: usize = 1_000;
+
+/// OHLCV candles built from indexed swaps, in UI prices (Y per X)
+#[axum::debug_handler]
+async fn get_pair_candles(
+    State(ctx): State<Arc<AppContext>>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Json<WebJsonResponse> {
+    let pair_key = match params
+        .get("address")
+        .map(|address| Pubkey::from_str(address))
+    {
+        Some(Ok(pair_key)) => pair_key,
+        _ => {
+            return Json(WebJsonResponse {
+                status: Status::Error,
+                message: "Invalid address format".to_string(),
+                data: json!({}),
+            });
+        }
+    };
+
+    let Some(interval) = CandleInterval::parse(params.get("interval").map_or("1h", |i| i.as_str()))
+    else {
+        return Json(WebJsonResponse {
+            status: Status::Error,
+            message: "Invalid interval, expected 1m, 5m, 1h or 1d".to_string(),
+            data: json!({}),
+        });
+    };
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|now| now.as_secs() as i64)
+        .unwrap_or_default();
+    let to = params
+        .get("to")
+        .and_then(|to| to.parse::<i64>().ok())
+        .unwrap_or(now);
+    let from = params
+        .get("from")
+        .and_then(|from| from.parse::<i64>().ok())
+        .unwrap_or(to - interval.seconds() * MAX_CANDLES as i64);
+
+    let Some(db) = &ctx.index_db else {
+        return Json(WebJsonResponse {
+            status: Status::Error,
+            message: "Swap indexer is disabled".to_string(),
+            data: json!({}),
+        });
+    };
+
+    let dlmm_client = match ctx.get_or_spawn_client(pair_key).await {
+        Ok(client) => client,
+        Err(e) => {
+            return Json(WebJsonResponse {
+                status: Status::Error,
+                message: format!("Failed to get DLMM client: {}", e),
+                data: json!({}),
+            });
+        }
+    };
+    let saros_dlmm = dlmm_client.saros_dlmm.read().await;
+    let [meta_x, meta_y] = match ctx.fetch_pair_token_info(&saros_dlmm).await {
+        Ok(metas) => metas,
+        Err(e) => {
+            return Json(WebJsonResponse {
+                status: Status::Error,
+                message: format!("Failed to fetch token metadata: {}", e),
+                data: json!({}),
+            });
+        }
+    };
+
+    let pair = pair_key.to_string();
+    let query_pair = pair.clone();
+    let (candles, cursor) = match db
+        .run(move |db| {
+            let candles = db.candles(
+                &query_pair,
+                interval,
+                interval.open_time(from),
+                to,
+                MAX_CANDLES,
+            )?;
+            Ok((candles, db.cursor(&query_pair)?))
+        })
+        .await
+    {
+        Ok(candles) => candles,
+        Err(e) => {
+            return Json(WebJsonResponse {
+                status: Status::Error,
+                message: format!("Failed to read candles: {}", e),
+                data: json!({}),
+            });
+        }
+    };
+
+    let candles: Vec<Candle> = candles
+        .iter()
+        .map(|candle| {
+            Candle::from_bins(
+                candle,
+                saros_dlmm.pair.bin_step,
+                meta_x.decimals,
+                meta_y.decimals,
+            )
+        })
+        .collect();
+
+    Json(WebJsonResponse {
+        status: Status::Success,
+        message: format!("{} candles found", candles.len()),
+        data: json!({
+            "pair_address": pair,
+            "interval": interval.as_str(),
+            "from": from,
+            "to": to,
+            "candles": candles,
+            "index": cursor,
+        }),
+    })
+}
+
+/// Search the pair index by mint, symbol and bin step
+#[axum::debug_handler]
+async fn search_pairs(
+    State(ctx): State<Arc<AppContext>>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Json<WebJsonResponse> {
+    let query = PairSearch {
+        mint: params
+            .get("mint")
+            .and_then(|mint| Pubkey::from_str(mint).ok()),
+        symbol: params.get("symbol").cloned(),
+        bin_step: params
+            .get("bin_step")
+            .and_then(|bin_step| bin_step.parse().ok()),
+    };
+
+    if params.contains_key("mint") && query.mint.is_none() {
+        return Json(WebJsonResponse {
+            status: Status::Error,
+            message: "Invalid mint format".to_string(),
+            data: json!({}),
+        });
+    }
+    if params.contains_key("bin_step") && query.bin_step.is_none() {
+        return Json(WebJsonResponse {
+            status: Status::Error,
+            message: "Invalid bin_step, expected an integer from 0 to 255".to_string(),
+            data: json!({}),
+        });
+    }
+
+    let index = ctx.pair_index.read().await;
+    // Only the background refresher builds the index, its first pass takes a while
+    if index.entries.is_empty() {
+        return Json(WebJsonResponse {
+            status: Status::Error,
+            message: "Pair index is still being built, try again shortly".to_string(),
+            data: json!({}),
+        });
+    }
+    let pairs = index.search(&query);
+    drop(index);
+
+    Json(WebJsonResponse {
+        status: Status::Success,
+        message: format!("{} pairs found", pairs.len()),
+        data: json!(pairs
+            .iter()
+            .map(|entry| json!({
+                "pair_address": entry.pair.to_string(),
+                "token_mint_x": entry.mint_x.to_string(),
+                "token_mint_y": entry.mint_y.to_string(),
+                "symbol_x": entry.symbol_x,
+                "symbol_y": entry.symbol_y,
+                "bin_step": entry.bin_step,
+                "active_id": entry.active_id,
+            }))
+            .collect::<Vec<_>>()),
+    })
+}
+
+#[axum::debug_handler]
+async fn get_quote(
+    State(ctx): State<Arc<AppContext>>,
+    Json(body): Json<QuoteRequest>,
+) -> Json<WebJsonResponse> {
+    let pair_address = body.pair_address.clone();
+
+    info!("🔍 Getting quote for pair {}", pair_address);
+    info!("Body: {:?}", body);
+
+    let (pair_key, source_mint, destination_mint) = match (
+        Pubkey::from_str(&pair_address),
+        Pubkey::from_str(&body.source_mint),
+        Pubkey::from_str(&body.destination_mint),
+    ) {
+        (Ok(pair_key), Ok(source_mint), Ok(destination_mint)) => {
+            (pair_key, source_mint, destination_mint)
+        }
+        _ => {
+            return Json(WebJsonResponse {
+                status: Status::Error,
+                message: "Invalid address format".to_string(),
+                data: json!({}),
+            });
+        }
+    };
+
+    // 1️⃣ take DLMM client
+    let dlmm_client = match ctx.get_or_spawn_client(pair_key).await {
+        Ok(client) => client,
+        Err(e) => {
+            return Json(WebJsonResponse {
+                status: Status::Error,
+                message: format!("Failed to get DLMM client: {}", e),
+                data: json!({}),
+            });
+        }
+    };
+
+    tracing::info!(
+        "💱 Quoting swap: amount_in={}, source_mint={}",
+        body.amount_in,
+        body.source_mint
+    );
+
+    if let Err(e) = dlmm_client.refresh(&ctx).await {
+        return Json(WebJsonResponse {
+            status: Status::Error,
+            message: format!("Failed to update DLMM client: {}", e),
+            data: json!({}),
+        });
+    }
+
+    let token_mint_x = dlmm_client.saros_dlmm.read().await.pair.token_mint_x;
+
+    let is_swap_for_y = is_swap_for_y(source_mint, token_mint_x);
+    let swap_mode = if is_swap_for_y {
+        if source_mint == token_mint_x {
+            SwapMode::ExactIn
+        } else {
+            SwapMode::ExactOut
+        }
+    } else {
+        if source_mint == token_mint_x {
+            SwapMode::ExactOut
+        } else {
+            SwapMode::ExactIn
+        }
+    };
+
+    let req = QuoteParams {
+        amount: body.amount_in,
+        input_mint: source_mint,
+        swap_mode,
+        output_mint: destination_mint,
+    };
+
+    // 2️⃣ call get_quote() from DLMM client
+    let result = match dlmm_client.quote(&ctx, &req).await {
+        Ok(quote) => Json(WebJsonResponse {
+            status: Status::Success,
+            message: "quote successful".to_string(),
+            data: quote_json(&quote),
+        }),
+        Err(e) => Json(WebJsonResponse {
+            status: Status::Error,
+            message: format!("Failed to get quote: {}", e),
+            data: json!({}),
+        }),
+    };
+
+    result
+}
+
+/// Quote many `(pair, input_mint, amount, mode)` entries, refreshing each pair once
+#[axum::debug_handler]
+async fn get_quotes(
+    State(ctx): State<Arc<AppContext>>,
+    Json(body): Json<Vec<BatchQuoteItem>>,
+) -> Json<WebJsonResponse> {
+    info!("🔍 Getting {} quotes", body.len());
+
+    // 1️⃣ take and refresh each distinct pair a single time
+    let mut clients: HashMap<String, Result<Arc<DLMMClient>, String>> = HashMap::new();
+    for item in body.iter() {
+        if clients.contains_key(&item.pair_address) {
+            continue;
+        }
+
+        let client = match Pubkey::from_str(&item.pair_address) {
+            Ok(pair_key) => match ctx.get_or_spawn_client(pair_key).await {
+                Ok(client) => match client.refresh(&ctx).await {
+                    Ok(()) => Ok(client),
+                    Err(e) => Err(format!("Failed to update DLMM client: {}", e)),
+                },
+                Err(e) => Err(format!("Failed to get DLMM client: {}", e)),
+            },
+            Err(e) => Err(format!("Invalid pair address: {}", e)),
+        };
+
+        clients.insert(item.pair_address.clone(), client);
+    }
+
+    // 2️⃣ quote every entry against its refreshed pair
+    let mut results = Vec::with_capacity(body.len());
+    for (index, item) in body.iter().enumerate() {
+        let quote = match &clients[&item.pair_address] {
+            Ok(client) => quote_batch_item(&ctx, client, item).await,
+            Err(e) => Err(anyhow::anyhow!(e.clone())),
+        };
+
+        results.push(match quote {
+            Ok(quote) => json!({
+                "index": index,
+                "status": Status::Success,
+                "pair_address": item.pair_address,
+                "data": quote_json(&quote),
+            }),
+            Err(e) => json!({
+                "index": index,
+                "status": Status::Error,
+                "pair_address": item.pair_address,
+                "message": e.to_string(),
+            }),
+        });
+    }
+
+    Json(WebJsonResponse {
+        status: Status::Success,
+        message: "quotes fetched successfully".to_string(),
+        data: json!(results),
+    })
+}
+
+async fn quote_batch_item(
+    ctx: &AppContext,
+    client: &DLMMClient,
+    item: &BatchQuoteItem,
+) -> Result<QuoteResponse> {
+    let input_mint = Pubkey::from_str(&item.input_mint)?;
+    let [mint_x, mint_y] = {
+        let saros_dlmm = client.saros_dlmm.read().await;
+        [saros_dlmm.pair.token_mint_x, saros_dlmm.pair.token_mint_y]
+    };
+
+    let output_mint = if input_mint == mint_x {
+        mint_y
+    } else if input_mint == mint_y {
+        mint_x
+    } else {
+        return Err(anyhow::anyhow!(
+            "Input mint {} is not part of pair",
+            input_mint
+        ));
+    };
+
+    let req = QuoteParams {
+        amount: item.amount,
+        input_mint,
+        swap_mode: item.mode.into(),
+        output_mint,
+    };
+
+    client.quote(ctx, &req).await
+}
+
+fn quote_json(quote: &QuoteResponse) -> Value {
+    json!({
+        "in_amount": quote.in_amount,
+        "out_amount": quote.out_amount,
+        "fee_amount": quote.fee_amount,
+        "fee_mint": quote.fee_mint.to_string(),
+        "spot_price": quote.spot_price,
+        "execution_price": quote.execution_price,
+        "price_impact_pct": quote.price_impact_pct,
+        "bins_crossed": quote.bins_crossed,
+        "end_active_id": quote.end_active_id,
+        "input_transfer_fee": quote.input_transfer_fee,
+        "output_transfer_fee": quote.output_transfer_fee,
+        "warnings": quote.warnings,
+        "context_slot": quote.context_slot,
+    })
+}
+
+/// Find the best 1- or 2-hop route, optionally with the chained swap transaction
+#[axum::debug_handler]
+async fn get_route(
+    State(ctx): State<Arc<AppContext>>,
+    Json(body): Json<RouteRequest>,
+) -> Json<WebJsonResponse> {
+    info!("🔍 Routing with body: {:?}", body);
+
+    let (input_mint, output_mint) = match (
+        Pubkey::from_str(&body.input_mint),
+        Pubkey::from_str(&body.output_mint),
+    ) {
+        (Ok(input_mint), Ok(output_mint)) => (input_mint, output_mint),
+        _ => {
+            return Json(WebJsonResponse {
+                status: Status::Error,
+                message: "Invalid mint address".to_string(),
+                data: json!({}),
+            });
+        }
+    };
+
+    let pairs = match &body.pairs {
+        Some(pairs) => match pairs
+            .iter()
+            .map(|pair| Pubkey::from_str(pair))
+            .collect::<Result<Vec<_>, _>>()
+        {
+            Ok(pairs) => pairs,
+            Err(e) => {
+                return Json(WebJsonResponse {
+                    status: Status::Error,
+                    message: format!("Invalid pair address: {}", e),
+                    data: json!({}),
+                });
+            }
+        },
+        None => ctx.pair_accounts.keys().await,
+    };
+
+    let route = match find_best_route(&ctx, &pairs, input_mint, output_mint, body.amount).await {
+        Ok(route) => route,
+        Err(e) => {
+            return Json(WebJsonResponse {
+                status: Status::Error,
+                message: format!("Failed to find route: {}", e),
+                data: json!({}),
+            });
+        }
+    };
+
+    let slippage_bps = body.slippage_bps.unwrap_or(50);
+    let mut data = route_json(&route, slippage_bps);
+
+    if let Some(signer) = &body.signer {
+        let user = match Pubkey::from_str(signer) {
+            Ok(user) => user,
+            Err(e) => {
+                return Json(WebJsonResponse {
+                    status: Status::Error,
+                    message: format!("Invalid signer: {}", e),
+                    data: json!({}),
+                });
+            }
+        };
+
+        let (instructions, tx) =
+            match build_route_transaction(&ctx, &route, user, slippage_bps).await {
+                Ok(built) => built,
+                Err(e) => {
+                    return Json(WebJsonResponse {
+                        status: Status::Error,
+                        message: format!("Failed to build transaction: {}", e),
+                        data: json!({}),
+                    });
+                }
+            };
+        let serialized = match bincode::serialize(&tx.transaction) {
+            Ok(serialized) => serialized,
+            Err(e) => {
+                return Json(WebJsonResponse {
+                    status: Status::Error,
+                    message: format!("Failed to serialize transaction: {}", e),
+                    data: json!({}),
+                });
+            }
+        };
+
+        data["instructions"] = json!(instructions);
+        data["transaction"] = json!(general_purpose::STANDARD.encode(serialized));
+    }
+
+    Json(WebJsonResponse {
+        status: Status::Success,
+        message: "route found".to_string(),
+        data,
+    })
+}
+
+fn route_json(route: &Route, slippage_bps: u16) -> Value {
+    json!({
+        "in_amount": route.in_amount,
+        "out_amount": route.out_amount,
+        "min_out_amount": apply_slippage(route.out_amount, slippage_bps),
+        "slippage_bps": slippage_bps,
+        "hops": route
+            .hops
+            .iter()
+            .map(|hop| json!({
+                "pair_address": hop.pair.to_string(),
+                "input_mint": hop.input_mint.to_string(),
+                "output_mint": hop.output_mint.to_string(),
+                "quote": quote_json(&hop.quote),
+            }))
+            .collect::<Vec<_>>(),
+    })
+}
+
+/// Per-bin distribution and deposit amounts for a liquidity strategy
+#[axum::debug_handler]
+async fn preview_liquidity(
+    State(ctx): State<Arc<AppContext>>,
+    Json(body): Json<LiquidityPreviewRequest>,
+) -> Json<WebJsonResponse> {
+    let pair_key = match Pubkey::from_str(&body.pair_address) {
+        Ok(pair_key) => pair_key,
+        Err(_) => {
+            return Json(WebJsonResponse {
+                status: Status::Error,
+                message: "Invalid address format".to_string(),
+                data: json!({}),
+            });
+        }
+    };
+
+    let dlmm_client = match ctx.get_or_spawn_client(pair_key).await {
+        Ok(client) => client,
+        Err(e) => {
+            return Json(WebJsonResponse {
+                status: Status::Error,
+                message: format!("Failed to get DLMM client: {}", e),
+                data: json!({}),
+            });
+        }
+    };
+
+    let saros_dlmm = dlmm_client.saros_dlmm.read().await;
+    let bin_step = saros_dlmm.pair.bin_step;
+    let active_id = saros_dlmm.pair.active_id;
+
+    let [meta_x, meta_y] = match ctx.fetch_pair_token_info(&saros_dlmm).await {
+        Ok(metas) => metas,
+        Err(e) => {
+            return Json(WebJsonResponse {
+                status: Status::Error,
+                message: format!("Failed to fetch token info: {}", e),
+                data: json!({}),
+            });
+        }
+    };
+    // UI prices are Y per X after decimals, bins are priced in raw units
+    let decimals_scale = 10f64.powi(meta_x.decimals as i32 - meta_y.decimals as i32);
+
+    let params = match body.strategy.params(bin_step, active_id, decimals_scale) {
+        Ok(params) => params,
+        Err(e) => {
+            return Json(WebJsonResponse {
+                status: Status::Error,
+                message: e.to_string(),
+                data: json!({}),
+            });
+        }
+    };
+
+    let distribution = match generate_distribution(&params, active_id) {
+        Ok(distribution) => distribution,
+        Err(e) => {
+            return Json(WebJsonResponse {
+                status: Status::Error,
+                message: format!("Invalid strategy: {}", e),
+                data: json!({}),
+            });
+        }
+    };
+
+    let bins = preview_deposit(
+        &distribution,
+        active_id,
+        bin_step,
+        body.strategy.amount_x,
+        body.strategy.amount_y,
+    );
+    let deposited_x: u64 = bins.iter().map(|bin| bin.amount_x).sum();
+    let deposited_y: u64 = bins.iter().map(|bin| bin.amount_y).sum();
+
+    let mut warnings = vec![];
+    if body.strategy.amount_x > 0 && distribution.iter().all(|bin| bin.distribution_x == 0) {
+        warnings.push("Range has no bins taking X, amount_x is not deposited".to_string());
+    }
+    if body.strategy.amount_y > 0 && distribution.iter().all(|bin| bin.distribution_y == 0) {
+        warnings.push("Range has no bins taking Y, amount_y is not deposited".to_string());
+    }
+
+    Json(WebJsonResponse {
+        status: Status::Success,
+        message: "Liquidity preview generated successfully".to_string(),
+        data: json!({
+            "pair": pair_key.to_string(),
+            "active_id": active_id,
+            "bin_step": bin_step,
+            "shape": params.shape,
+            "side": params.side,
+            "min_bin_id": active_id.saturating_add_signed(params.min_bin_offset),
+            "max_bin_id": active_id.saturating_add_signed(params.max_bin_offset),
+            "distribution": distribution,
+            "bins": bins
+                .iter()
+                .map(|bin| json!({
+                    "bin_id": bin.bin_id,
+                    "price": bin.price * decimals_scale,
+                    "amount_x": bin.amount_x,
+                    "amount_y": bin.amount_y,
+                }))
+                .collect::<Vec<_>>(),
+            "amount_x": deposited_x,
+            "amount_y": deposited_y,
+            "warnings": warnings,
+        }),
+    })
+}
+
+#[axum::debug_handler]
+async fn get_instruction(
+    State(ctx): State<Arc<AppContext>>,
+    Json(body): Json<InstructionRequest<serde_json::Value>>,
+) -> Json<WebJsonResponse> {
+    info!("🔍 Simulating swap with body: {:?}", body);
+
+    if let InstructionType::AddLiquidity = body.instruction_type {
+        return match add_liquidity_instruction(&ctx, &body).await {
+            Ok(add_liquidity) => Json(WebJsonResponse {
+                status: Status::Success,
+                message: "Instruction fetched successfully".to_string(),
+                data: add_liquidity.data,
+            }),
+            Err(e) => Json(WebJsonResponse {
+                status: Status::Error,
+                message: format!("Failed to build add liquidity instruction: {}", e),
+                data: json!({}),
+            }),
+        };
+    }
+
+    let pair_address = body.pair_address.clone();
+    let (params, [pair_key, source_mint, user]) = match parse_swap_request(&body) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            return Json(WebJsonResponse {
+                status: Status::Error,
+                message: e.to_string(),
+                data: json!({}),
+            });
+        }
+    };
+
+    info!("🔍 Getting instruction for pair {}", pair_address);
+
+    let in_amount = params.in_amount;
+    let min_out_amount = params.min_out_amount;
+
+    // 1️⃣ take DLMM client
+    let dlmm_client = match ctx.get_or_spawn_client(pair_key).await {
+        Ok(client) => client,
+        Err(e) => {
+            return Json(WebJsonResponse {
+                status: Status::Error,
+                message: format!("Failed to get DLMM client: {}", e),
+                data: json!({}),
+            });
+        }
+    };
+
+    if let Err(e) = dlmm_client.refresh(&ctx).await {
+        return Json(WebJsonResponse {
+            status: Status::Error,
+            message: format!("Failed to update DLMM client: {}", e),
+            data: json!({}),
+        });
     }
 
     let client = dlmm_client.saros_dlmm.read().await;
 
-    let user = Pubkey::from_str_const(&params.signer);
+    let data = match body.instruction_type {
+        InstructionType::Swap => {
+            let swap_instruction = match build_swap_instruction(
+                &client,
+                ctx.config.hook_program_id,
+                user,
+                source_mint,
+                in_amount,
+                min_out_amount,
+            ) {
+                Ok(instruction) => instruction,
+                Err(e) => {
+                    return Json(WebJsonResponse {
+                        status: Status::Error,
+                        message: format!("Failed to build swap instruction: {}", e),
+                        data: json!({}),
+                    });
+                }
+            };
 
-    // let source_token_account = get_associated_token_address(&user, &source_mint).to_string();
-    let user_token_vault_x = get_associated_token_address_with_program_id(
-        &user,
-        &client.pair.token_mint_x,
-        &client.token_program[0],
-    );
+            tracing::info!("Swap instruction: {:?}", swap_instruction);
 
-    let user_token_vault_y = get_associated_token_address_with_program_id(
-        &user,
-        &client.pair.token_mint_y,
-        &client.token_program[1],
-    );
+            json!({
+               "instruction_type": "swap",
+               "data": swap_instruction,
+               "warnings": transfer_warnings(&ctx, &client).await,
+            })
+        }
+        _ => json!({
+            "instruction_type": "unsupported",
+            "data": ""
+        }),
+    };
+    // Implementation for fetching instruction details goes here
+    Json(WebJsonResponse {
+        status: Status::Success,
+        message: "Instruction fetched successfully".to_string(),
+        data: data,
+    })
+}
 
-    let is_swap_for_y = is_swap_for_y(source_mint, client.pair.token_mint_x);
-    let swap_mode = if is_swap_for_y {
-        if source_mint == client.pair.token_mint_x {
-            SwapType::ExactIn
-        } else {
-            SwapType::ExactOut
+/// Swap instruction params with the pair, source mint and signer keys parsed
+fn parse_swap_request(
+    body: &InstructionRequest<serde_json::Value>,
+) -> Result<(SwapInstructionParams, [Pubkey; 3])> {
+    let params: SwapInstructionParams = serde_json::from_value(body.params.clone())
+        .map_err(|e| anyhow::anyhow!("Invalid params: {}", e))?;
+    let keys = match (
+        Pubkey::from_str(&body.pair_address),
+        Pubkey::from_str(&params.source_mint),
+        Pubkey::from_str(&params.signer),
+    ) {
+        (Ok(pair_key), Ok(source_mint), Ok(user)) => [pair_key, source_mint, user],
+        _ => anyhow::bail!("Invalid address format"),
+    };
+
+    Ok((params, keys))
+}
+
+/// An add-liquidity instruction with the response describing it
+struct AddLiquidity {
+    user: Pubkey,
+    instruction: Instruction,
+    data: Value,
+}
+
+/// Deposit the strategy's distribution into an existing position
+async fn add_liquidity_instruction(
+    ctx: &AppContext,
+    body: &InstructionRequest<serde_json::Value>,
+) -> Result<AddLiquidity> {
+    let params: AddLiquidityInstructionParams = serde_json::from_value(body.params.clone())
+        .map_err(|e| anyhow::anyhow!("Invalid params: {}", e))?;
+    let pair_key = Pubkey::from_str(&body.pair_address)
+        .map_err(|_| anyhow::anyhow!("Invalid pair address {}", body.pair_address))?;
+    let position_key = Pubkey::from_str(&params.position)
+        .map_err(|_| anyhow::anyhow!("Invalid position address {}", params.position))?;
+    let user = Pubkey::from_str(&params.signer)
+        .map_err(|_| anyhow::anyhow!("Invalid signer address {}", params.signer))?;
+
+    let dlmm_client = ctx.get_or_spawn_client(pair_key).await?;
+    dlmm_client.refresh(ctx).await?;
+    let client = dlmm_client.saros_dlmm.read().await;
+    let bin_step = client.pair.bin_step;
+    let active_id = client.pair.active_id;
+
+    let [meta_x, meta_y] = ctx.fetch_pair_token_info(&client).await?;
+    let decimals_scale = 10f64.powi(meta_x.decimals as i32 - meta_y.decimals as i32);
+    let strategy = params
+        .strategy
+        .params(bin_step, active_id, decimals_scale)?;
+    let distribution = generate_distribution(&strategy, active_id)?;
+
+    let rpc_client = ctx.rpc_client.clone();
+    let position = ctx
+        .config
+        .retry
+        .run_blocking("position", move || {
+            fetch_liquidity_position(&rpc_client, position_key)
+        })
+        .await?;
+
+    let instruction = build_add_liquidity_instruction(
+        &client,
+        ctx.config.hook_program_id,
+        user,
+        position_key,
+        &position,
+        [params.strategy.amount_x, params.strategy.amount_y],
+        &distribution,
+    )?;
+
+    let data = json!({
+        "instruction_type": "add_liquidity",
+        "data": instruction,
+        "active_id": active_id,
+        "distribution": distribution,
+        "warnings": transfer_warnings(ctx, &client).await,
+    });
+
+    Ok(AddLiquidity {
+        user,
+        instruction,
+        data,
+    })
+}
+
+/// Simulate the add-liquidity instruction as `get_instruction` builds it
+async fn simulate_add_liquidity(
+    ctx: &AppContext,
+    body: &InstructionRequest<serde_json::Value>,
+) -> Result<Value> {
+    let mut add_liquidity = add_liquidity_instruction(ctx, body).await?;
+
+    let instructions = vec![
+        ComputeBudgetInstruction::set_compute_unit_limit(1_400_000),
+        ComputeBudgetInstruction::set_compute_unit_price(1),
+        add_liquidity.instruction,
+    ];
+    let tx = build_v0_transaction(ctx, add_liquidity.user, &instructions).await?;
+    let account_keys: Vec<String> = tx.account_keys.iter().map(|key| key.to_string()).collect();
+    let tx_b64 = general_purpose::STANDARD.encode(bincode::serialize(&tx.transaction)?);
+
+    let http = reqwest::Client::new();
+    let response = simulate_transaction(&http, &ctx.rpc_client.url(), &tx_b64, false, &[]).await?;
+
+    let mut result = parse_simulation_result(&response, &account_keys, &ctx.config.lb_program_id);
+    let changes = BalanceChanges::from_meta(&response["result"]["value"], &account_keys);
+    result["balance_deltas"] = json!(owner_deltas(ctx, &changes).await);
+    result["distribution"] = add_liquidity.data["distribution"].take();
+    result["warnings"] = add_liquidity.data["warnings"].take();
+    Ok(result)
+}
+
+/// Token-2022 extensions the swap instruction does not account for
+async fn transfer_warnings(ctx: &AppContext, client: &SarosDlmm) -> Vec<String> {
+    let epoch = ctx.current_epoch.load(Ordering::Relaxed);
+    let mut warnings = vec![];
+
+    let Ok(metas) = ctx.fetch_pair_token_info(client).await else {
+        return warnings;
+    };
+
+    for meta in metas.iter() {
+        if let Some(program) = meta.transfer_hook_program {
+            warnings.push(format!(
+                "Mint {} has a transfer hook ({}), extra accounts are not included",
+                meta.mint, program
+            ));
         }
-    } else {
-        if source_mint == client.pair.token_mint_x {
-            SwapType::ExactOut
+        let fee_bps = meta.transfer_fee_bps(epoch);
+        if fee_bps > 0 {
+            warnings.push(format!(
+                "Mint {} withholds a {} bps transfer fee on every transfer",
+                meta.mint, fee_bps
+            ));
+        }
+    }
+
+    warnings
+}
+
+#[axum::debug_handler]
+async fn simulate_tx(
+    State(ctx): State<Arc<AppContext>>,
+    Json(body): Json<InstructionRequest<serde_json::Value>>,
+) -> Json<WebJsonResponse> {
+    info!("🔍 Simulating swap with body: {:?}", body);
+
+    if let InstructionType::AddLiquidity = body.instruction_type {
+        return match simulate_add_liquidity(&ctx, &body).await {
+            Ok(data) => Json(WebJsonResponse {
+                status: Status::Success,
+                message: "Simulation successful".to_string(),
+                data,
+            }),
+            Err(e) => Json(WebJsonResponse {
+                status: Status::Error,
+                message: format!("Failed to simulate add liquidity: {}", e),
+                data: json!({}),
+            }),
+        };
+    }
+
+    let pair_address = body.pair_address.clone();
+    let (params, [pair_key, source_mint, user]) = match parse_swap_request(&body) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            return Json(WebJsonResponse {
+                status: Status::Error,
+                message: e.to_string(),
+                data: json!({}),
+            });
+        }
+    };
+
+    info!("🔍 Getting instruction for pair {}", pair_address);
+
+    let in_amount = params.in_amount;
+    let min_out_amount = params.min_out_amount;
+
+    // 1️⃣ take DLMM client
+    let dlmm_client = match ctx.get_or_spawn_client(pair_key).await {
+        Ok(client) => client,
+        Err(e) => {
+            return Json(WebJsonResponse {
+                status: Status::Error,
+                message: format!("Failed to get DLMM client: {}", e),
+                data: json!({}),
+            });
+        }
+    };
+
+    if let Err(e) = dlmm_client.refresh(&ctx).await {
+        return Json(WebJsonResponse {
+            status: Status::Error,
+            message: format!("Failed to update DLMM client: {}", e),
+            data: json!({}),
+        });
+    }
+
+    // Quote the same swap up front to compare with what the simulation delivers
+    let output_mint = {
+        let saros_dlmm = dlmm_client.saros_dlmm.read().await;
+        if source_mint == saros_dlmm.pair.token_mint_x {
+            saros_dlmm.pair.token_mint_y
         } else {
-            SwapType::ExactIn
+            saros_dlmm.pair.token_mint_x
         }
     };
+    let quote = dlmm_client
+        .quote(
+            &ctx,
+            &QuoteParams {
+                amount: in_amount,
+                input_mint: source_mint,
+                output_mint,
+                swap_mode: SwapMode::ExactIn,
+            },
+        )
+        .await;
 
-    let bin_for_swap = client.compute_bin_array_swap().unwrap();
+    let client = dlmm_client.saros_dlmm.read().await;
 
     let data_response = match body.instruction_type {
         InstructionType::Swap => {
-            let swap_instruction_data = build_swap_instruction_data(
-                saros_sdk::instruction::BuildSwapInstructionDataParams {
-                    amount: in_amount,
-                    other_amount_threshold: min_out_amount,
-                    swap_for_y: is_swap_for_y,
-                    swap_mode,
-                },
-            )
-            .unwrap();
-
-            let mut account_metas = Vec::new();
-
-            {
-                account_metas.push(AccountMeta::new(client.key, false));
-                account_metas.push(AccountMeta::new_readonly(client.pair.token_mint_x, false));
-                account_metas.push(AccountMeta::new_readonly(client.pair.token_mint_y, false));
-                account_metas.push(AccountMeta::new(bin_for_swap.bin_array_keys[0], false));
-                account_metas.push(AccountMeta::new(bin_for_swap.bin_array_keys[1], false));
-                account_metas.push(AccountMeta::new(client.token_vault[0], false));
-                account_metas.push(AccountMeta::new(client.token_vault[1], false));
-                account_metas.push(AccountMeta::new(
-                    Pubkey::from_str_const(&user_token_vault_x.to_string()),
-                    false,
-                ));
-                account_metas.push(AccountMeta::new(
-                    Pubkey::from_str_const(&user_token_vault_y.to_string()),
-                    false,
-                ));
-                account_metas.push(AccountMeta::new_readonly(user, true));
-                account_metas.push(AccountMeta::new_readonly(client.token_program[0], false));
-                account_metas.push(AccountMeta::new_readonly(client.token_program[1], false));
-                account_metas.push(AccountMeta::new_readonly(
-                    Pubkey::from_str_const("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr"),
-                    false,
-                ));
-            }
-
-            // If pair does not have hook, hook should be pair key (dummy)
-            account_metas.push(AccountMeta::new(client.hook, false));
-            account_metas.push(AccountMeta::new_readonly(ctx.config.hook_program_id, false));
-            // This expect as the last of swap instruction
-            account_metas.push(AccountMeta::new_readonly(client.event_authority, false));
-            account_metas.push(AccountMeta::new_readonly(client.program_id, false));
-
-            // Remaining accounts for hook CPI call
-            if client.hook != client.key {
-                let bin_array_index = client.pair.bin_array_index();
-                let (hook_bin_array_lower, _) = Pubkey::find_program_address(
-                    &[
-                        b"bin_array".as_ref(),
-                        client.hook.as_ref(),
-                        (bin_array_index).to_le_bytes().as_ref(),
-                    ],
-                    &ctx.config.hook_program_id,
-                );
-
-                let (hook_bin_array_upper, _) = Pubkey::find_program_address(
-                    &[
-                        b"bin_array".as_ref(),
-                        client.hook.as_ref(),
-                        (bin_array_index + 1).to_le_bytes().as_ref(),
-                    ],
-                    &ctx.config.hook_program_id,
-                );
-
-                account_metas.push(AccountMeta::new(hook_bin_array_lower, false));
-                account_metas.push(AccountMeta::new(hook_bin_array_upper, false));
-            }
-
-            let swap_instruction = Instruction {
-                program_id: client.program_id,
-                accounts: account_metas,
-                data: swap_instruction_data,
+            let swap_instruction = match build_swap_instruction(
+                &client,
+                ctx.config.hook_program_id,
+                user,
+                source_mint,
+                in_amount,
+                min_out_amount,
+            ) {
+                Ok(instruction) => instruction,
+                Err(e) => {
+                    return Json(WebJsonResponse {
+                        status: Status::Error,
+                        message: format!("Failed to build swap instruction: {}", e),
+                        data: json!({}),
+                    });
+                }
             };
 
             tracing::info!("Swap instruction: {:?}", swap_instruction);
@@ -553,53 +1743,65 @@ async fn simulate_tx(
             instructions.push(ComputeBudgetInstruction::set_compute_unit_price(1));
             instructions.push(swap_instruction);
 
-            let message = Message::new(&instructions, Some(&user));
-
-            let dummy_signature = Signature::new_unique();
-
-            let tx: Transaction = Transaction {
-                signatures: vec![dummy_signature],
-                message,
+            let tx = match build_v0_transaction(&ctx, user, &instructions).await {
+                Ok(tx) => tx,
+                Err(e) => {
+                    return Json(WebJsonResponse {
+                        status: Status::Error,
+                        message: format!("Failed to build transaction: {}", e),
+                        data: json!({}),
+                    });
+                }
             };
 
-            // 5. Serialize + base64
-            let serialized = bincode::serialize(&tx).unwrap();
-            let tx_b64 = general_purpose::STANDARD.encode(serialized);
-
-            let request = json!({
-                "jsonrpc": "2.0",
-                "id": 1,
-                "method": "simulateTransaction",
-                "params": [
-                    tx_b64,
-                    {
-                        "encoding": "base64",
-                        "sigVerify": false,
-                        "replaceRecentBlockhash": true
-                    }
-                ]
-            });
-
-            let client = reqwest::Client::new();
-
-            let res = client
-                .post(ctx.rpc_client.url())
-                .json(&request)
-                .send()
-                .await;
+            let account_keys: Vec<String> =
+                tx.account_keys.iter().map(|key| key.to_string()).collect();
 
-            let response: serde_json::Value = match res {
-                Ok(resp) => resp.json().await.unwrap(),
+            // 5. Serialize + base64
+            let serialized = match bincode::serialize(&tx.transaction) {
+                Ok(serialized) => serialized,
                 Err(e) => {
                     return Json(WebJsonResponse {
                         status: Status::Error,
-                        message: format!("Failed to simulate transaction: {}", e),
+                        message: format!("Failed to serialize transaction: {}", e),
                         data: json!({}),
                     });
                 }
             };
+            let tx_b64 = general_purpose::STANDARD.encode(serialized);
 
-            parse_simulation_result(&response)
+            let http = reqwest::Client::new();
+            let response =
+                match simulate_transaction(&http, &ctx.rpc_client.url(), &tx_b64, false, &[]).await
+                {
+                    Ok(response) => response,
+                    Err(e) => {
+                        return Json(WebJsonResponse {
+                            status: Status::Error,
+                            message: format!("Failed to simulate transaction: {}", e),
+                            data: json!({}),
+                        });
+                    }
+                };
+
+            let mut result =
+                parse_simulation_result(&response, &account_keys, &ctx.config.lb_program_id);
+
+            let changes = BalanceChanges::from_meta(&response["result"]["value"], &account_keys);
+            let deltas = owner_deltas(&ctx, &changes).await;
+
+            let realized_out = deltas
+                .iter()
+                .find(|delta| {
+                    delta.owner == user.to_string() && delta.mint == output_mint.to_string()
+                })
+                .map(|delta| delta.change.max(0) as u64);
+            let (quote_check, warnings) = quote_divergence(&ctx, quote.as_ref().ok(), realized_out);
+
+            result["balance_deltas"] = json!(deltas);
+            result["quote_check"] = quote_check;
+            result["warnings"] = json!(warnings);
+            result
         }
         _ => json!({"response": "unsupported"}),
     };
@@ -611,7 +1813,287 @@ async fn simulate_tx(
     });
 }
 
-pub fn parse_simulation_result(v: &Value) -> serde_json::Value {
+/// Simulate a wallet-assembled transaction as-is: signatures verified, its own
+/// blockhash kept and lookup tables resolved
+#[axum::debug_handler]
+async fn simulate_signed_tx(
+    State(ctx): State<Arc<AppContext>>,
+    Json(body): Json<SimulateSignedTxRequest>,
+) -> Json<WebJsonResponse> {
+    let account_keys = match signed_tx_account_keys(&ctx, &body.transaction).await {
+        Ok(account_keys) => account_keys,
+        Err(e) => {
+            return Json(WebJsonResponse {
+                status: Status::Error,
+                message: e.to_string(),
+                data: json!({}),
+            });
+        }
+    };
+
+    let http = reqwest::Client::new();
+    let response = match simulate_transaction(
+        &http,
+        &ctx.rpc_client.url(),
+        &body.transaction,
+        true,
+        &body.accounts,
+    )
+    .await
+    {
+        Ok(response) => response,
+        Err(e) => {
+            return Json(WebJsonResponse {
+                status: Status::Error,
+                message: format!("Failed to simulate transaction: {}", e),
+                data: json!({}),
+            });
+        }
+    };
+
+    if let Some(error) = response.get("error") {
+        return Json(WebJsonResponse {
+            status: Status::Error,
+            message: format!(
+                "Simulation rejected: {}",
+                error["message"].as_str().unwrap_or("invalid transaction")
+            ),
+            data: json!({ "error": error }),
+        });
+    }
+
+    let value = &response["result"]["value"];
+    let mut result = parse_simulation_result(&response, &account_keys, &ctx.config.lb_program_id);
+
+    let changes = BalanceChanges::from_meta(value, &account_keys);
+    result["balance_deltas"] = json!(owner_deltas(&ctx, &changes).await);
+    result["account_keys"] = json!(account_keys);
+
+    // Post-simulation state, in the order the addresses were requested
+    let accounts = value["accounts"].as_array().cloned().unwrap_or_default();
+    result["accounts"] = body
+        .accounts
+        .iter()
+        .zip(accounts)
+        .map(|(address, account)| (address.clone(), account))
+        .collect::<serde_json::Map<_, _>>()
+        .into();
+
+    Json(WebJsonResponse {
+        status: Status::Success,
+        message: "Simulation successful".to_string(),
+        data: result,
+    })
+}
+
+/// Net token changes per owner and mint, in UI units from the mint's metadata
+async fn owner_deltas(ctx: &AppContext, changes: &BalanceChanges) -> Vec<OwnerDelta> {
+    let mut deltas = changes.owner_deltas();
+    for delta in deltas.iter_mut() {
+        let Ok(mint) = Pubkey::from_str(&delta.mint) else {
+            continue;
+        };
+        if let Ok(meta) = ctx.get_token_meta(mint).await {
+            delta.decimals = meta.decimals;
+            delta.ui_change = delta.change as f64 / 10f64.powi(meta.decimals as i32);
+        }
+    }
+    deltas
+}
+
+/// Compare the simulated output with the quote, warning past the configured threshold
+fn quote_divergence(
+    ctx: &AppContext,
+    quote: Option<&QuoteResponse>,
+    realized_out: Option<u64>,
+) -> (Value, Vec<String>) {
+    let (Some(quote), Some(realized_out)) = (quote, realized_out) else {
+        return (
+            json!(null),
+            vec!["Could not compare the simulated output with a quote".to_string()],
+        );
+    };
+
+    let divergence_bps = if quote.out_amount == 0 {
+        0.0
+    } else {
+        (quote.out_amount as f64 - realized_out as f64) / quote.out_amount as f64 * 10_000.0
+    };
+
+    let mut warnings = vec![];
+    if divergence_bps.abs() > ctx.config.quote_divergence_bps as f64 {
+        warnings.push(format!(
+            "Simulated output {} differs from quoted {} by {:.1} bps",
+            realized_out, quote.out_amount, divergence_bps
+        ));
+    }
+
+    (
+        json!({
+            "quoted_out": quote.out_amount,
+            "realized_out": realized_out,
+            "divergence_bps": divergence_bps,
+            "threshold_bps": ctx.config.quote_divergence_bps,
+        }),
+        warnings,
+    )
+}
+
+/// Simulate a signed transaction and submit it only if the simulation succeeds
+#[axum::debug_handler]
+async fn send_tx(
+    State(ctx): State<Arc<AppContext>>,
+    Json(body): Json<SendTxRequest>,
+) -> Json<WebJsonResponse> {
+    let http = reqwest::Client::new();
+    let rpc_url = ctx.rpc_client.url();
+
+    let account_keys = match signed_tx_account_keys(&ctx, &body.transaction).await {
+        Ok(account_keys) => account_keys,
+        Err(e) => {
+            return Json(WebJsonResponse {
+                status: Status::Error,
+                message: e.to_string(),
+                data: json!({}),
+            });
+        }
+    };
+
+    let simulation = match simulate_transaction(&http, &rpc_url, &body.transaction, true, &[]).await
+    {
+        Ok(response) => response,
+        Err(e) => {
+            return Json(WebJsonResponse {
+                status: Status::Error,
+                message: format!("Failed to simulate transaction: {}", e),
+                data: json!({}),
+            });
+        }
+    };
+
+    if let Some(error) = simulation.get("error") {
+        return Json(WebJsonResponse {
+            status: Status::Error,
+            message: format!(
+                "Transaction rejected: {}",
+                error["message"].as_str().unwrap_or("invalid transaction")
+            ),
+            data: json!({ "error": error }),
+        });
+    }
+
+    let simulation_result =
+        parse_simulation_result(&simulation, &account_keys, &ctx.config.lb_program_id);
+    if simulation_result["status"] == "error" {
+        return Json(WebJsonResponse {
+            status: Status::Failure,
+            message: format!(
+                "Simulation failed: {}",
+                simulation_result["error"]["message"]
+                    .as_str()
+                    .unwrap_or("unknown error")
+            ),
+            data: json!({ "simulation": simulation_result }),
+        });
+    }
+
+    let send_config = &ctx.config.send;
+    let skip_preflight = body.skip_preflight.unwrap_or(send_config.skip_preflight);
+    let max_retries = body.max_retries.or(send_config.max_retries);
+    // A rejected transaction is rejected again, only transient node errors are retried
+    let policy = RetryPolicy {
+        max_attempts: send_config.attempts,
+        ..ctx.config.retry.clone()
+    };
+
+    match policy
+        .run("sendTransaction", || {
+            send_transaction(
+                &http,
+                &rpc_url,
+                &body.transaction,
+                skip_preflight,
+                max_retries,
+            )
+        })
+        .await
+    {
+        Ok(signature) => {
+            info!("📤 Sent transaction {}", signature);
+            Json(WebJsonResponse {
+                status: Status::Success,
+                message: "Transaction sent successfully".to_string(),
+                data: json!({
+                    "signature": signature,
+                    "events_url": format!("/api/tx/{}/events", signature),
+                    "simulation": simulation_result,
+                }),
+            })
+        }
+        Err(e) => Json(WebJsonResponse {
+            status: Status::Error,
+            message: format!("Failed to send transaction: {}", e),
+            data: json!({ "simulation": simulation_result }),
+        }),
+    }
+}
+
+/// Stream the status, error and final balance changes of a submitted transaction
+async fn tx_events(
+    State(ctx): State<Arc<AppContext>>,
+    Path(signature): Path<String>,
+) -> Result<Sse<impl Stream<Item = Result<Event, axum::Error>>>, (StatusCode, String)> {
+    info!("📡 Tracking transaction {}", signature);
+
+    let events = match Signature::from_str(&signature) {
+        Ok(_) => ctx
+            .tx_trackers
+            .subscribe(&signature)
+            .map_err(|e| (StatusCode::TOO_MANY_REQUESTS, e.to_string()))?,
+        Err(e) => {
+            let (sender, receiver) = tokio::sync::mpsc::channel(1);
+            let _ = sender.try_send(TxEvent::Failed {
+                error: json!(e.to_string()),
+                message: format!("Invalid signature: {}", e),
+            });
+            receiver
+        }
+    };
+
+    let stream = ReceiverStream::new(events)
+        .map(|event| Event::default().event(event.name()).json_data(event));
+
+    Ok(Sse::new(stream).keep_alive(KeepAlive::default()))
+}
+
+/// Account keys of a base64 signed transaction, lookup tables resolved
+async fn signed_tx_account_keys(ctx: &AppContext, transaction: &str) -> Result<Vec<String>> {
+    let tx = general_purpose::STANDARD
+        .decode(transaction)
+        .map_err(anyhow::Error::from)
+        .and_then(|bytes| Ok(bincode::deserialize::<VersionedTransaction>(&bytes)?))
+        .map_err(|e| anyhow::anyhow!("Invalid transaction: {}", e))?;
+
+    let client = ctx.rpc_client.clone();
+    let keys = ctx
+        .config
+        .retry
+        .run_blocking("lookup tables", move || {
+            resolve_account_keys(&client, &tx.message)
+        })
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to resolve lookup tables: {}", e))?;
+
+    Ok(keys.iter().map(|key| key.to_string()).collect())
+}
+
+/// Status, error, logs and `program_id` events of a `simulateTransaction`
+/// response, `account_keys` being the simulated message's keys
+pub fn parse_simulation_result(
+    v: &Value,
+    account_keys: &[String],
+    program_id: &Pubkey,
+) -> serde_json::Value {
     let value = &v["result"]["value"];
 
     let logs = value["logs"]
@@ -621,13 +2103,16 @@ pub fn parse_simulation_result(v: &Value) -> serde_json::Value {
         .filter_map(|x| x.as_str().map(|s| s.to_string()))
         .collect::<Vec<String>>();
 
-    // Extract error (if any)
+    // Extract error (if any), with the program's error name and message
     let error = if value["err"].is_null() {
         None
     } else {
-        Some(format!("{:?}", value["err"]))
+        Some(DecodedError::decode(&value["err"], &logs))
     };
 
+    let events =
+        DecodedEvents::decode(&value["innerInstructions"], &logs, account_keys, program_id);
+
     let slot = v["result"]["context"]["slot"].as_u64().unwrap_or(0);
     let fee = value["fee"].as_u64().unwrap_or(0);
     let units = value["unitsConsumed"].as_u64().unwrap_or(0);
@@ -642,6 +2127,7 @@ pub fn parse_simulation_result(v: &Value) -> serde_json::Value {
         "units": units,
         "error": error,
         "logs": logs,
+        "events": events,
         "preTokenBalances": pre,
         "postTokenBalances": post
     })
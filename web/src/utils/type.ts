@@ -4,4 +4,10 @@ export type QuoteData = {
     in_amount: number;
     out_amount: number;
     fee_amount: number;
+    spot_price: number;
+    execution_price: number;
+    price_impact_pct: number;
+    bins_crossed: number;
+    end_active_id: number;
+    context_slot: number;
 }